@@ -0,0 +1,16 @@
+//! Codec ALMA IE - ST2150 sans allocation, compatible `no_std`
+//!
+//! Ce crate reprend tel quel le module `sim_ie::st2150::nostd` (même source) pour un usage
+//! embarqué. Sa compilation sous `#![no_std]` garantit que le module n'utilise que `core`.
+//!
+//! Les tests du module sont dans `sim_ie` (ils comparent les trames avec le codec `std`) et
+//! ne sont pas compilés ici.
+
+#![no_std]
+
+#[cfg(not(test))]
+#[path = "../../src/st2150/nostd/mod.rs"]
+mod nostd;
+
+#[cfg(not(test))]
+pub use nostd::*;
@@ -1,10 +1,14 @@
 //! Helpers pour l'édition des informations du contexte
 
-use iced::widget::{Row, Text, TextInput};
+use iced::widget::{Column, Row, Text, TextInput};
+use iced::{theme, Color};
 
+use super::show_infos::with_origine_tooltip;
 use super::{Element, Message};
-use crate::context;
 use context::{Context, IdInfo};
+use sim_ie::context;
+use sim_ie::langue::tr;
+use sim_ie::st2150::messages::diagnostic::Anomalie;
 
 /// Affichage en édition d'un champ non défini
 const STR_INPUT_INFO_NONE: &str = "";
@@ -14,25 +18,70 @@ const LABEL_WIDTH: f32 = 120.0;
 /// Largeur harmonisée de tous les champs de saisie
 const INPUT_WIDTH: f32 = 80.0;
 
-/// Edition IHM d'un champ `IdInfo`
-pub fn input_info(context: &Context, id_info: IdInfo) -> Element<Message> {
+/// Couleur pour une saisie refusée
+const INVALID_INPUT_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+
+/// Couleur pour une anomalie du diagnostic de la requête (voir `diagnostic`)
+const ANOMALIE_COLOR: Color = Color::from_rgb(0.9, 0.5, 0.0);
+
+/// Saisie refusée pour un champ (saisie, raison du refus)
+pub type InvalidInput = (String, String);
+
+/// Edition IHM d'un champ `IdInfo` (suivi de son unité éventuelle)
+/// Une saisie refusée reste affichée en rouge avec la raison du refus
+/// Un champ avec une anomalie pour la requête est signalé en orange avec cette anomalie
+/// L'origine de la valeur est affichée au survol du champ de saisie
+pub fn input_info<'a>(
+    context: &Context,
+    id_info: IdInfo,
+    option_invalid_input: Option<&InvalidInput>,
+    option_anomalie: Option<&Anomalie>,
+) -> Element<'a, Message> {
     let row = Row::new();
 
     let txt = format!("{} : ", context.get_info_label(id_info));
-    let txt: Text = Text::new(txt).width(LABEL_WIDTH);
+    let mut txt: Text = Text::new(txt).width(LABEL_WIDTH);
+    if option_anomalie.is_some() {
+        txt = txt.style(theme::Text::Color(ANOMALIE_COLOR));
+    }
     let row = row.push(txt);
 
     let str_place_holder = context.get_info_label(id_info);
-    let str_value = context.get_info_to_string(id_info, STR_INPUT_INFO_NONE);
+    let str_value = match option_invalid_input {
+        Some((input, _)) => input.clone(),
+        None => context.get_info_to_string(id_info, STR_INPUT_INFO_NONE),
+    };
     let txt_input = TextInput::new(&str_place_holder, &str_value)
         .width(INPUT_WIDTH)
         .on_input(move |str| Message::InputInfo(str, id_info));
-    let row = row.push(txt_input);
+    let row = row.push(with_origine_tooltip(context, id_info, txt_input));
+
+    // Unité de la valeur (une saisie peut aussi être suivie d'une unité compatible)
+    let row = match context.get_info_unite(id_info) {
+        Some(unite) => row.push(Text::new(format!(" {unite}"))),
+        None => row,
+    };
 
-    row.into()
+    let (txt_raison, color) = match (option_invalid_input, option_anomalie) {
+        (None, None) => return row.into(),
+        (Some((_, raison)), _) => (tr(raison), INVALID_INPUT_COLOR),
+        (None, Some(anomalie)) => (anomalie.to_string(), ANOMALIE_COLOR),
+    };
+    let txt_raison = Text::new(txt_raison)
+        .style(theme::Text::Color(color))
+        .width(LABEL_WIDTH + INPUT_WIDTH);
+    Column::new().push(row).push(txt_raison).into()
 }
 
 /// Callback IHM modification de la valeur d'un champ identifié par son `IdInfo`
-pub fn callback_input_info(context: &mut Context, input: &str, id_info: IdInfo) {
-    context.set_info_from_string(id_info, input);
+/// # Errors
+/// Saisie refusée et sa raison si la valeur n'est pas acceptée par le contexte
+pub fn callback_input_info(
+    context: &mut Context,
+    input: &str,
+    id_info: IdInfo,
+) -> Result<(), InvalidInput> {
+    context
+        .set_info_from_string(id_info, input)
+        .map_err(|raison| (input.to_string(), raison))
 }
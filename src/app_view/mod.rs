@@ -4,24 +4,33 @@
 //!
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 mod input_infos;
+mod show_frame;
 mod show_infos;
 
 use super::APP_VERSION;
 
-use iced::widget::{checkbox, column, container, horizontal_rule, row, vertical_rule};
-use iced::widget::{Button, Column, Row, Text};
+use iced::widget::vertical_rule;
+use iced::widget::{checkbox, column, container, horizontal_rule, pick_list, row, scrollable};
+use iced::widget::{Button, Column, Row, Text, TextInput};
 use iced::{executor, theme, window, Length};
-use iced::{Application, Command, Element, Settings, Theme};
+use iced::{subscription, Application, Command, Element, Event, Settings, Subscription, Theme};
 
-use crate::context::IdInfo;
-use crate::st2150::messages::{
+use sim_ie::context::Context;
+use sim_ie::context::IdInfo;
+use sim_ie::context::{horodatage_to_string, save_presets, Preset};
+use sim_ie::context::{ProfilVehicule, PROFIL_VEHICULE_FILE_NAME};
+use sim_ie::context::{LAST_SESSION_FILE_NAME, PRESETS_FILE_NAME};
+use sim_ie::langue::{langue, set_langue, tr, Langue, LANGUES};
+use sim_ie::st2150::frame::Frame;
+use sim_ie::st2150::messages::{
     get_dyn_message, message00::Message00, CommonMessageTrait, ST2150_MESSAGE_NUMBERS,
 };
-use crate::st2150::Edition2150;
-use crate::Context;
-use crate::ST2150;
+use sim_ie::st2150::vacations::{TraceVacation, ValeurInfo};
+use sim_ie::st2150::{CodePage, DecodingMode, Edition2150, CODE_PAGES};
+use sim_ie::st2150::{ProtocolError, ST2150};
 
 /// Structure pour initialiser l'IHM
 /// Cette structure permet d'initialiser la structure `AppView` dans l'implémentation de `iced::Application`
@@ -29,6 +38,12 @@ use crate::ST2150;
 pub struct AppSettings {
     /// Protocole ST2150 configuré avec le port série défini par l'utilisateur (voir main.rs)
     st2150: ST2150,
+
+    /// Contexte initial (éventuellement restauré d'une session, voir main.rs)
+    context: Context,
+
+    /// Fichier session pour les actions de sauvegarde et de restauration
+    session_path: PathBuf,
 }
 
 /// Structure pour l'IHM iced
@@ -44,12 +59,65 @@ pub struct AppView {
 
     /// Editions de la ST2150 à afficher
     editions_st2150: HashMap<Edition2150, bool>,
+
+    /// Fichier session (en saisie)
+    session_path: String,
+
+    /// Compte rendu de la dernière action sur le fichier session
+    session_status: String,
+
+    /// Saisies refusées des champs de requête (affichées en rouge avec la raison du refus)
+    invalid_inputs: HashMap<IdInfo, input_infos::InvalidInput>,
+
+    /// Presets nommés de valeurs pour les requêtes (voir `PRESETS_FILE_NAME`)
+    presets: Vec<Preset>,
+
+    /// Nom d'un nouveau preset (en saisie)
+    preset_name: String,
+
+    /// Compte rendu de la dernière action sur les presets
+    presets_status: String,
+
+    /// Information dont l'historique des valeurs est affiché (None si pas d'affichage)
+    option_history_id_info: Option<IdInfo>,
+
+    /// Compte rendu du dernier export CSV d'un historique
+    history_status: String,
+
+    /// Compte rendu de la dernière action sur le profil du véhicule
+    profil_status: String,
+
+    /// Affichage du journal des vacations de la session
+    show_journal: bool,
+
+    /// Numéro de la vacation du journal dont le détail est affiché (None si pas de sélection)
+    option_num_vacation: Option<u32>,
+
+    /// Aperçu de la trame de la requête courante (voir `refresh_preview`)
+    preview: Result<Frame, ProtocolError>,
 }
 
+/// Nombre max de valeurs affichées dans l'historique d'une information
+/// (l'export CSV contient toutes les valeurs de l'historique)
+const HISTORY_VIEW_MAX_LEN: usize = 100;
+
+/// Nombre de segments (STX, SEP, champs, etc.) par ligne de l'aperçu de la requête
+const PREVIEW_SEGMENTS_LIGNE: usize = 8;
+
+/// Nombre de segments par ligne des trames de la dernière vacation
+const TRACE_SEGMENTS_LIGNE: usize = 24;
+
+/// Nombre de segments par ligne des trames d'une vacation du journal
+const JOURNAL_SEGMENTS_LIGNE: usize = 8;
+
 /// Point d'entrée de l'IHM
-pub fn run(st2150: ST2150) {
+pub fn run(st2150: ST2150, context: Context, session_path: PathBuf) {
     // Création de la structure pour initialiser l'application
-    let app_setting = AppSettings { st2150 };
+    let app_setting = AppSettings {
+        st2150,
+        context,
+        session_path,
+    };
 
     // Exécution de l'application IHM avec le runtime Iced
     // Ici on définit `flag` qui porte la sélection du port série et le protocole ST2150 associé
@@ -61,6 +129,8 @@ pub fn run(st2150: ST2150) {
             ..window::Settings::default()
         },
         default_text_size: 12.0,
+        // Sauvegarde de la dernière session avant la fermeture (voir `Message::CloseRequested`)
+        exit_on_close_request: false,
         flags: app_setting,
         ..Settings::default()
     });
@@ -73,12 +143,33 @@ pub enum Message {
     DoMessageVacation(u8),
     InputInfo(String, IdInfo),
     SelectionEditionST2150(Edition2150, bool),
+    SelectionDecodingMode(DecodingMode),
+    SelectionAutoPrerequis(bool),
+    SelectionCodePage(CodePage),
+    InputSessionPath(String),
+    LoadSession,
+    SaveSession,
+    ApplyPreset(usize),
+    InputPresetName(String),
+    SavePreset,
+    ShowHistory(IdInfo),
+    CloseHistory,
+    ExportHistoryCsv(Vec<IdInfo>, String),
+    ProfilFromMessage11,
+    SelectionLangue(Langue),
+    ShowJournal,
+    CloseJournal,
+    SelectionVacation(u32),
+    RerunVacation(u32),
+    CloseRequested,
 }
 
 impl AppView {
     /// Sélection du message courant
+    /// (les formats et libellés des informations suivent ce message)
     fn set_current_message_num(&mut self, message_num: u8) {
         self.dyn_message = get_dyn_message(message_num);
+        self.context.set_message_courant(Some(message_num));
     }
 
     /// Indique si une édition de la ST2150 est visible
@@ -111,7 +202,7 @@ impl AppView {
     }
 
     /// Zone pour sélection du message courant
-    pub fn body_message_selection(&self) -> Element<Message> {
+    pub fn body_message_selection(&self) -> Element<'_, Message> {
         // Numéro de message actuellement sélectionné
         let cur_message_num = self.dyn_message.message_num();
 
@@ -127,7 +218,7 @@ impl AppView {
             let text: Text = Text::new(format!(
                 "{:02} {}",
                 message_num,
-                get_dyn_message(*message_num).message_str()
+                tr(get_dyn_message(*message_num).message_str())
             ));
             let btn = if *message_num == cur_message_num {
                 // C'est le numéro de message actuellement sélectionné
@@ -157,27 +248,140 @@ impl AppView {
         row.into()
     }
 
+    /// Informations qui concernent le véhicule du profil actuel
+    /// (par exemple, pas de compartiment #7 pour un véhicule à 5 compartiments)
+    fn relevant_id_infos(&self, id_infos: Vec<IdInfo>) -> Vec<IdInfo> {
+        id_infos
+            .into_iter()
+            .filter(|id_info| self.context.is_info_relevant(*id_info))
+            .collect()
+    }
+
     /// Informations pour la requête courante
-    pub fn view_request(&self) -> Element<Message> {
-        let id_infos = self.dyn_message.id_infos_request();
+    pub fn view_request(&self) -> Element<'_, Message> {
+        let id_infos = self.relevant_id_infos(self.dyn_message.id_infos_request());
+        let diagnostic = ST2150::message_diagnostic(&self.context, self.dyn_message.message_num());
 
         let mut col = Column::new();
 
         if id_infos.is_empty() {
-            let txt = Text::new("(Pas de champ)");
+            let txt = Text::new(tr("(Pas de champ)"));
             col = col.push(txt);
         } else {
             for id_info in id_infos {
-                let w = input_infos::input_info(&self.context, id_info);
+                let w = input_infos::input_info(
+                    &self.context,
+                    id_info,
+                    self.invalid_inputs.get(&id_info),
+                    diagnostic
+                        .iter()
+                        .find(|diagnostic_info| diagnostic_info.id_info == id_info)
+                        .map(|diagnostic_info| &diagnostic_info.anomalie),
+                );
                 col = col.push(w);
             }
+            col = col.push(self.view_presets());
+        }
+        col = col.push(self.view_preview());
+
+        col.into()
+    }
+
+    /// Libellé d'un champ de l'aperçu de la requête : Son index, puis le libellé et la valeur
+    /// actuelle de chaque information encodée dans ce champ
+    fn label_champ(&self, index: usize, id_infos: &[IdInfo]) -> String {
+        let mut label = format!("#{index}");
+        for id_info in id_infos {
+            label.push('\n');
+            label.push_str(&show_infos::info_to_string(&self.context, *id_info));
+        }
+        label
+    }
+
+    /// Libellé d'un champ d'une trame d'une vacation du journal : Son index, puis le libellé et
+    /// la valeur de chaque information encodée ou décodée dans ce champ lors de cette vacation
+    /// (voir `TraceVacation::request_field_values` et `TraceVacation::response_field_values`)
+    fn label_champ_trace(
+        &self,
+        index: usize,
+        field_values: &[(IdInfo, Option<&ValeurInfo>)],
+    ) -> String {
+        let mut label = format!("#{index}");
+        for (id_info, option_valeur_info) in field_values {
+            label.push('\n');
+            label.push_str(&show_infos::valeur_info_to_string(
+                &self.context,
+                *id_info,
+                *option_valeur_info,
+            ));
+        }
+        label
+    }
+
+    /// Mise à jour de l'aperçu de la trame de la requête courante (exécution à blanc, sans envoi
+    /// sur le port), après chaque action de l'utilisateur plutôt qu'à chaque affichage
+    fn refresh_preview(&mut self) {
+        self.preview = self
+            .st2150
+            .message_dry_run(&self.context, self.dyn_message.message_num());
+    }
+
+    /// Aperçu de la trame de la requête courante
+    fn view_preview(&self) -> Element<'_, Message> {
+        let col = Column::new()
+            .spacing(5)
+            .push(Text::new(tr("Aperçu de la requête :")));
+        match &self.preview {
+            Ok(req) => {
+                let label_champ =
+                    |index: usize| self.label_champ(index, req.id_info(index).as_slice());
+                col.push(show_frame::show_frame(
+                    &req.to_frame(),
+                    &label_champ,
+                    &[],
+                    PREVIEW_SEGMENTS_LIGNE,
+                ))
+                .into()
+            }
+            Err(e) => col.push(Text::new(format!("({e})"))).into(),
+        }
+    }
+
+    /// Presets applicables au message courant et création d'un nouveau preset
+    fn view_presets(&self) -> Element<'_, Message> {
+        let message_num = self.dyn_message.message_num();
+
+        let mut col = Column::new().spacing(5).padding([10, 0]);
+
+        for (index, preset) in self.presets.iter().enumerate() {
+            if preset.is_for_message(message_num) {
+                let btn = Button::new(Text::new(tr(&format!("Preset : {}", preset.name))))
+                    .on_press(Message::ApplyPreset(index))
+                    .style(theme::Button::Secondary);
+                col = col.push(btn);
+            }
+        }
+
+        let row_new_preset = row![
+            TextInput::new(&tr("Nom du preset"), &self.preset_name)
+                .width(120)
+                .on_input(Message::InputPresetName),
+            Button::new(Text::new(tr("Nouveau preset")))
+                .on_press(Message::SavePreset)
+                .style(theme::Button::Secondary),
+        ]
+        .spacing(10);
+        col = col.push(row_new_preset);
+
+        if !self.presets_status.is_empty() {
+            col = col.push(Text::new(&self.presets_status));
         }
 
         col.into()
     }
 
     /// Informations pour la réponse courante
-    pub fn view_response(&self) -> Element<Message> {
+    pub fn view_response(&self) -> Element<'_, Message> {
         // Si la réponse contient NACK à true, on n'affiche que cette info
         fn is_nack(context: &Context, id_infos: &[IdInfo]) -> bool {
             if id_infos.contains(&IdInfo::Nack) {
@@ -188,69 +392,153 @@ impl AppView {
             false
         }
 
-        let id_infos = self.dyn_message.id_infos_response();
+        let id_infos = self.relevant_id_infos(self.dyn_message.id_infos_response());
 
         let mut col = Column::new();
 
         if id_infos.is_empty() {
-            let txt = Text::new("(Pas d'information)");
+            let txt = Text::new(tr("(Pas d'information)"));
             col = col.push(txt);
         } else if is_nack(&self.context, &id_infos) {
-            let txt = Text::new("RÉPONSE : NACK !!!");
+            let txt = Text::new(tr("RÉPONSE : NACK !!!"));
             col = col.push(txt);
-        } else if !self.st2150.last_error.is_empty() {
-            let txt = format!("ERREUR : {} !!!", self.st2150.last_error);
+        } else if let Some(e) = &self.st2150.option_last_error {
+            let txt = tr(&format!("ERREUR : {e} !!!"));
             let txt = Text::new(txt);
             col = col.push(txt);
         } else {
-            for id_info in id_infos {
-                let w = show_infos::show_info(&self.context, id_info);
+            for id_info in &id_infos {
+                let w = show_infos::show_info(&self.context, *id_info);
                 col = col.push(w);
             }
         }
 
+        // Export de l'historique de toutes les informations de la réponse
+        if !id_infos.is_empty() {
+            let file_name = format!(
+                "sim_ie_historique_message_{:02}.csv",
+                self.dyn_message.message_num()
+            );
+            let btn = Button::new(Text::new(tr("Exporter l'historique (CSV)")))
+                .on_press(Message::ExportHistoryCsv(id_infos, file_name))
+                .style(theme::Button::Secondary);
+            col = col.push(container(btn).padding([10, 0]));
+        }
+
+        col.into()
+    }
+
+    /// Historique des valeurs d'une information (des plus récentes aux plus anciennes)
+    pub fn view_history(&self, id_info: IdInfo) -> Element<'_, Message> {
+        let history = self.context.get_info_history(id_info);
+
+        let mut col = Column::new().spacing(5);
+        col = col.push(Text::new(tr(&format!(
+            "Historique : {} ({} valeurs)",
+            self.context.get_info_label(id_info),
+            history.len()
+        ))));
+
+        let mut col_entries = Column::new();
+        for history_entry in history.iter().rev().take(HISTORY_VIEW_MAX_LEN) {
+            col_entries = col_entries.push(
+                row![
+                    Text::new(horodatage_to_string(history_entry.origine.horodatage)).width(150),
+                    Text::new(format!("#{}", history_entry.origine.num_vacation)).width(40),
+                    Text::new(history_entry.origine.provenance.to_string()).width(130),
+                    Text::new(
+                        self.context
+                            .t_value_to_string(id_info, &history_entry.t_value)
+                            + &history_entry
+                                .option_unite
+                                .map(|unite| format!(" {unite}"))
+                                .unwrap_or_default()
+                    ),
+                ]
+                .spacing(5),
+            );
+        }
+        col = col.push(scrollable(col_entries).height(Length::Fill));
+
+        let file_name = format!("sim_ie_historique_{id_info:?}.csv");
+        col = col.push(
+            row![
+                Button::new(Text::new(tr("Exporter (CSV)")))
+                    .on_press(Message::ExportHistoryCsv(vec![id_info], file_name))
+                    .style(theme::Button::Secondary),
+                Button::new(Text::new(tr("Fermer")))
+                    .on_press(Message::CloseHistory)
+                    .style(theme::Button::Secondary),
+            ]
+            .spacing(10),
+        );
+        if !self.history_status.is_empty() {
+            col = col.push(Text::new(&self.history_status));
+        }
+
         col.into()
     }
 
     /// Zone avec bouton action selon le contexte
-    pub fn view_do_vacation(&self) -> Element<Message> {
-        let mut row = Row::new();
+    /// (ou toutes les anomalies des informations de la requête si elle est impossible)
+    /// Les messages prérequis à exécuter avant sont proposés (sauf s'ils sont automatiques)
+    pub fn view_do_vacation(&self) -> Element<'_, Message> {
+        let message_num = self.dyn_message.message_num();
+        let mut col = Column::new().spacing(5);
 
-        /* Disponibilité ? */
-        match ST2150::message_availability(&self.context, self.dyn_message.message_num()) {
-            Ok(()) => {
-                // Bouton pour exécuter cette commande
-                let txt_do_it = format!(
-                    "Run Message {:02} ({}) sur le port {}",
-                    self.dyn_message.message_num(),
-                    self.dyn_message.message_str(),
-                    self.st2150.port.name,
-                );
-                let txt_do_it: Text = Text::new(txt_do_it);
-                let btn_do_it = Button::new(txt_do_it)
-                    .on_press(Message::DoMessageVacation(self.dyn_message.message_num()));
-                row = row.push(btn_do_it);
-            }
-            Err(e) => {
-                // Info de cette commande (indisponible)
-                let txt_info = format!(
-                    "Message '{:02}' sur le port {} : {}",
-                    self.dyn_message.message_num(),
-                    self.st2150.port.name,
-                    e
+        /* Prérequis ? */
+        if !self.st2150.auto_prerequis {
+            let mut row_prerequis = Row::new().spacing(10);
+            for prerequis_num in ST2150::message_prerequis(&self.context, message_num) {
+                let txt_prerequis = tr(&format!("Run Message {prerequis_num:02} avant"));
+                row_prerequis = row_prerequis.push(
+                    Button::new(Text::new(txt_prerequis))
+                        .on_press(Message::DoMessageVacation(prerequis_num))
+                        .style(theme::Button::Secondary),
                 );
-                let txt_info: Text = Text::new(txt_info);
-                row = row.push(txt_info);
             }
-        };
+            col = col.push(row_prerequis);
+        }
 
-        row.into()
+        /* Disponibilité ? */
+        let diagnostic = ST2150::message_diagnostic(&self.context, message_num);
+        if diagnostic.is_empty() {
+            // Bouton pour exécuter cette commande
+            let txt_do_it = tr(&format!(
+                "Run Message {:02} ({}) sur le port {}",
+                message_num,
+                tr(self.dyn_message.message_str()),
+                self.st2150.port.name,
+            ));
+            let txt_do_it: Text = Text::new(txt_do_it);
+            let btn_do_it =
+                Button::new(txt_do_it).on_press(Message::DoMessageVacation(message_num));
+            return col.push(btn_do_it).into();
+        }
+
+        // Info de cette commande (indisponible) et anomalies
+        let txt_info = tr(&format!(
+            "Message '{:02}' sur le port {} : {}",
+            message_num,
+            self.st2150.port.name,
+            tr("Requête impossible"),
+        ));
+        col = col.push(Text::new(txt_info));
+        for diagnostic_info in diagnostic {
+            col = col.push(Text::new(format!(
+                "- {} : {}",
+                self.context.get_info_label(diagnostic_info.id_info),
+                diagnostic_info.anomalie
+            )));
+        }
+
+        col.into()
     }
 
     /// Zone avec sélection des éditions de la ST2150 à afficher
-    pub fn view_edition_st2150(&self) -> Element<Message> {
+    pub fn view_edition_st2150(&self) -> Element<'_, Message> {
         row![
-            Text::new("Editions ST2150 : "),
+            Text::new(tr("Editions ST2150 : ")),
             checkbox(
                 "A",
                 self.is_edition_st2150_visible(Edition2150::A),
@@ -281,36 +569,311 @@ impl AppView {
         .into()
     }
 
-    /// Zone avec les traces / erreur de la dernière vacation
-    pub fn view_vacation(&self) -> Element<Message> {
-        let col = Column::new();
+    /// Zone avec sélection du mode de décodage des réponses (strict ou tolérant)
+    pub fn view_decoding_mode(&self) -> Element<'_, Message> {
+        checkbox(
+            tr("Décodage tolérant"),
+            self.st2150.decoding_mode == DecodingMode::Lenient,
+            |value| {
+                Message::SelectionDecodingMode(if value {
+                    DecodingMode::Lenient
+                } else {
+                    DecodingMode::Strict
+                })
+            },
+        )
+        .into()
+    }
+
+    /// Zone avec l'option d'exécution automatique des messages prérequis
+    pub fn view_auto_prerequis(&self) -> Element<'_, Message> {
+        checkbox(
+            tr("Prérequis automatiques"),
+            self.st2150.auto_prerequis,
+            Message::SelectionAutoPrerequis,
+        )
+        .into()
+    }
+
+    /// Zone avec la sélection du jeu de caractères des champs chaînes de caractères
+    pub fn view_code_page(&self) -> Element<'_, Message> {
+        row![
+            Text::new(tr("Caractères : ")),
+            pick_list(
+                &CODE_PAGES[..],
+                Some(self.st2150.code_page),
+                Message::SelectionCodePage
+            ),
+        ]
+        .into()
+    }
+
+    /// Zone avec la sélection de la langue de l'outil
+    pub fn view_langue(&self) -> Element<'_, Message> {
+        row![
+            Text::new(tr("Langue : ")),
+            pick_list(&LANGUES[..], Some(langue()), Message::SelectionLangue),
+        ]
+        .into()
+    }
+
+    /// Zone avec le fichier session et les actions de sauvegarde et de restauration
+    pub fn view_session(&self) -> Element<'_, Message> {
+        row![
+            Text::new(tr("Session : ")),
+            TextInput::new(&tr("Fichier session"), &self.session_path)
+                .width(250)
+                .on_input(Message::InputSessionPath),
+            Button::new(Text::new(tr("Charger")))
+                .on_press(Message::LoadSession)
+                .style(theme::Button::Secondary),
+            Button::new(Text::new(tr("Sauver")))
+                .on_press(Message::SaveSession)
+                .style(theme::Button::Secondary),
+            Text::new(&self.session_status),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Zone avec le profil du véhicule et sa mise à jour depuis la réponse au message 11
+    pub fn view_profil_vehicule(&self) -> Element<'_, Message> {
+        let profil_vehicule = self.context.get_profil_vehicule();
+        row![
+            Text::new(tr(&format!(
+                "Véhicule : {} produits, {} compartiments, {} flexibles",
+                profil_vehicule.nb_produits(),
+                profil_vehicule.nb_compartiments(),
+                profil_vehicule.nb_flexibles()
+            ))),
+            Button::new(Text::new(tr("Compartiments depuis message 11")))
+                .on_press(Message::ProfilFromMessage11)
+                .style(theme::Button::Secondary),
+            Text::new(&self.profil_status),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Sauvegarde automatique de la dernière session (pour `--last-session` au démarrage)
+    /// après une vacation, un chargement de session ou de preset et à la fermeture de l'outil
+    /// (pas à chaque saisie), avec l'erreur éventuelle dans la zone session
+    fn save_last_session(&mut self) {
+        if let Err(e) = self.context.save_session(Path::new(LAST_SESSION_FILE_NAME)) {
+            self.session_status = e.to_string();
+        }
+    }
+
+    /// Vacation du journal selon son numéro
+    fn get_trace_vacation(&self, num_vacation: u32) -> Option<&TraceVacation> {
+        self.st2150
+            .vacations
+            .iter()
+            .find(|trace| trace.num_vacation == num_vacation)
+    }
+
+    /// Journal des vacations de la session (des plus récentes aux plus anciennes) et détail
+    /// de la vacation sélectionnée
+    pub fn view_journal(&self) -> Element<'_, Message> {
+        let mut col = Column::new().spacing(5);
+        col = col.push(Text::new(tr(&format!(
+            "Journal des vacations ({})",
+            self.st2150.vacations.len()
+        ))));
+
+        let mut col_entries = Column::new();
+        for trace in self.st2150.vacations.iter().rev() {
+            let entry = row![
+                Text::new(format!("#{}", trace.num_vacation)).width(40),
+                Text::new(horodatage_to_string(trace.horodatage)).width(150),
+                Text::new(format!("{:02}", trace.message_num)).width(20),
+                Text::new(format!("{} ms", trace.duree.as_millis())).width(60),
+                Text::new(trace.compte_rendu()),
+            ]
+            .spacing(5);
+            let style = if self.option_num_vacation == Some(trace.num_vacation) {
+                theme::Button::Primary
+            } else {
+                theme::Button::Text
+            };
+            col_entries = col_entries.push(
+                Button::new(entry)
+                    .padding(0)
+                    .style(style)
+                    .on_press(Message::SelectionVacation(trace.num_vacation)),
+            );
+        }
+        col = col.push(scrollable(col_entries).height(Length::Fill));
+
+        // Détail de la vacation sélectionnée
+        if let Some(trace) = self
+            .option_num_vacation
+            .and_then(|num_vacation| self.get_trace_vacation(num_vacation))
+        {
+            col = col
+                .push(horizontal_rule(10))
+                .push(self.view_trace_vacation(trace));
+        }
+
+        col = col.push(
+            Button::new(Text::new(tr("Fermer")))
+                .on_press(Message::CloseJournal)
+                .style(theme::Button::Secondary),
+        );
+
+        col.into()
+    }
+
+    /// Détail d'une vacation du journal : Valeurs de la requête et de la réponse, trames
+    /// échangées et compte rendu, avec un bouton pour la rejouer
+    fn view_trace_vacation(&self, trace: &TraceVacation) -> Element<'_, Message> {
+        let mut col = Column::new().spacing(5);
+        col = col.push(Text::new(tr(&format!(
+            "Vacation #{} : Message {:02} ({})",
+            trace.num_vacation,
+            trace.message_num,
+            tr(get_dyn_message(trace.message_num).message_str())
+        ))));
+
+        let view_valeurs = |titre: &str, valeur_infos: &[ValeurInfo]| {
+            let mut col = Column::new().push(Text::new(tr(titre)));
+            for valeur_info in valeur_infos {
+                col = col.push(Text::new(format!(
+                    "- {} : {}",
+                    valeur_info.label, valeur_info.valeur
+                )));
+            }
+            col
+        };
+        col = col
+            .push(view_valeurs("Requête :", &trace.request_values))
+            .push(view_valeurs("Réponse :", &trace.response_values));
+
+        // Trames échangées
+        if !trace.req.is_empty() {
+            let label_champ =
+                |index: usize| self.label_champ_trace(index, &trace.request_field_values(index));
+            col = col.push(show_frame::show_frame(
+                &trace.req,
+                &label_champ,
+                &[],
+                JOURNAL_SEGMENTS_LIGNE,
+            ));
+        }
+        if !trace.rep.is_empty() {
+            let positions_en_cause: Vec<usize> = trace
+                .option_error
+                .iter()
+                .chain(&trace.warnings)
+                .flat_map(|e| Frame::positions_en_cause(&trace.rep, e))
+                .collect();
+            let label_champ =
+                |index: usize| self.label_champ_trace(index, &trace.response_field_values(index));
+            col = col.push(show_frame::show_frame(
+                &trace.rep,
+                &label_champ,
+                &positions_en_cause,
+                JOURNAL_SEGMENTS_LIGNE,
+            ));
+        }
+
+        // Compte rendu
+        col = col.push(Text::new(tr(&format!(
+            "Durée {} ms : {}",
+            trace.duree.as_millis(),
+            trace.compte_rendu()
+        ))));
+        for warning in &trace.warnings {
+            col = col.push(Text::new(tr(&format!("Avertissement : {warning}"))));
+        }
+
+        col = col.push(
+            Button::new(Text::new(tr("Rejouer la vacation")))
+                .on_press(Message::RerunVacation(trace.num_vacation)),
+        );
+
+        col.into()
+    }
+
+    /// Zone avec les traces annotées / erreur de la dernière vacation
+    /// Chaque champ des trames est associé aux informations qu'il encode (requête) ou qu'il
+    /// alimente (réponse) et les octets en cause d'une erreur de décodage sont signalés
+    pub fn view_vacation(&self) -> Element<'_, Message> {
+        let mut col = Column::new().spacing(5);
+        // Trace de la dernière vacation, avec les valeurs de chaque champ lors de cette vacation
+        let option_trace = self.st2150.vacations.back();
+
+        // Accès au journal des vacations
+        if !self.show_journal {
+            col = col.push(
+                Button::new(Text::new(tr(&format!(
+                    "Journal des vacations ({})",
+                    self.st2150.vacations.len()
+                ))))
+                .on_press(Message::ShowJournal)
+                .style(theme::Button::Secondary),
+            );
+        }
 
         // Dernière requête
-        let my_str = if self.st2150.last_req.is_empty() {
-            "(Pas de requête)".to_string()
+        if self.st2150.last_req.is_empty() {
+            col = col.push(Text::new(tr("(Pas de requête)")));
         } else {
-            format!("Requête : {:?}", self.st2150.last_req)
-        };
-        let txt: Text = Text::new(my_str.to_string());
-        let col = col.push(txt);
+            let label_champ = |index: usize| {
+                let field_values = option_trace
+                    .map(|trace| trace.request_field_values(index))
+                    .unwrap_or_default();
+                self.label_champ_trace(index, &field_values)
+            };
+            col = col
+                .push(Text::new(tr("Requête :")))
+                .push(show_frame::show_frame(
+                    &self.st2150.last_req,
+                    &label_champ,
+                    &[],
+                    TRACE_SEGMENTS_LIGNE,
+                ));
+        }
 
         // Dernière réponse
-        let my_str = if self.st2150.last_rep.is_empty() {
-            "(Pas de réponse)".to_string()
+        if self.st2150.last_rep.is_empty() {
+            col = col.push(Text::new(tr("(Pas de réponse)")));
         } else {
-            format!("Réponse : {:?}", self.st2150.last_rep)
-        };
-        let txt: Text = Text::new(my_str.to_string());
-        let col = col.push(txt);
+            let label_champ = |index: usize| {
+                let field_values = option_trace
+                    .map(|trace| trace.response_field_values(index))
+                    .unwrap_or_default();
+                self.label_champ_trace(index, &field_values)
+            };
+            let positions_en_cause: Vec<usize> = self
+                .st2150
+                .option_last_error
+                .iter()
+                .chain(&self.st2150.last_warnings)
+                .flat_map(|e| Frame::positions_en_cause(&self.st2150.last_rep, e))
+                .collect();
+            col = col
+                .push(Text::new(tr("Réponse :")))
+                .push(show_frame::show_frame(
+                    &self.st2150.last_rep,
+                    &label_champ,
+                    &positions_en_cause,
+                    TRACE_SEGMENTS_LIGNE,
+                ));
+        }
 
         // Dernière erreur
-        let my_str = if self.st2150.last_error.is_empty() {
-            "(Pas d'erreur)".to_string()
-        } else {
-            format!("/!\\ ERREUR : {} /!\\", self.st2150.last_error)
+        let my_str = match &self.st2150.option_last_error {
+            None => tr("(Pas d'erreur)"),
+            Some(e) => tr(&format!("/!\\ ERREUR : {e} /!\\")),
         };
-        let txt: Text = Text::new(my_str.to_string());
-        let col = col.push(txt);
+        col = col.push(Text::new(my_str));
+
+        // Avertissements (décodage tolérant)
+        for warning in &self.st2150.last_warnings {
+            let txt: Text = Text::new(tr(&format!("Avertissement : {warning}")));
+            col = col.push(txt);
+        }
 
         col.into()
     }
@@ -332,67 +895,293 @@ impl Application for AppView {
         editions_st2150.insert(Edition2150::D, true);
         editions_st2150.insert(Edition2150::E, true);
 
+        // Presets du répertoire courant (optionnels)
+        let presets_path = Path::new(PRESETS_FILE_NAME);
+        let (presets, presets_status) = if presets_path.exists() {
+            match flags.context.load_presets(presets_path) {
+                Ok(presets) => (presets, String::new()),
+                Err(e) => (vec![], e.to_string()),
+            }
+        } else {
+            (vec![], String::new())
+        };
+
+        // Profil du véhicule du répertoire courant (optionnel)
+        let mut context = flags.context;
+        let profil_path = Path::new(PROFIL_VEHICULE_FILE_NAME);
+        let profil_status = if profil_path.exists() {
+            match ProfilVehicule::load(profil_path) {
+                Ok(profil_vehicule) => {
+                    context.set_profil_vehicule(profil_vehicule);
+                    String::new()
+                }
+                Err(e) => e.to_string(),
+            }
+        } else {
+            String::new()
+        };
+
         // Objet AppView pour l'IHM
-        (
-            AppView {
-                st2150: flags.st2150,
-                context: Context::default(),
-                dyn_message: Box::<Message00>::default(), // Message00 par défaut
-                editions_st2150,
-            },
-            Command::none(),
-        )
+        let mut app_view = AppView {
+            st2150: flags.st2150,
+            context,
+            dyn_message: Box::<Message00>::default(), // Message00 par défaut
+            editions_st2150,
+            session_path: flags.session_path.display().to_string(),
+            session_status: String::new(),
+            invalid_inputs: HashMap::new(),
+            presets,
+            preset_name: String::new(),
+            presets_status,
+            option_history_id_info: None,
+            history_status: String::new(),
+            profil_status,
+            show_journal: false,
+            option_num_vacation: None,
+            preview: Err(ProtocolError::DryRun),
+        };
+        app_view.refresh_preview();
+        (app_view, Command::none())
     }
 
     /// Titre de l'application
     fn title(&self) -> String {
-        format!("Simulateur v{APP_VERSION} - Informatique Embarquée ALMA")
+        tr(&format!(
+            "Simulateur v{APP_VERSION} - Informatique Embarquée ALMA"
+        ))
     }
 
     /// Traitement des messages de l'application
     fn update(&mut self, message: Message) -> Command<Message> {
-        match message {
+        let command = match message {
             Message::SelectionMessageST2150(message_num) => {
                 self.set_current_message_num(message_num);
-                self.st2150.last_req = vec![];
-                self.st2150.last_rep = vec![];
-                self.st2150.last_error = String::new();
+                self.st2150.clear_last_vacation();
                 Command::none()
             }
             Message::DoMessageVacation(message_num) => {
                 let _ = self
                     .st2150
                     .do_message_vacation(&mut self.context, message_num);
+                // Un message prérequis ne change pas le message sélectionné
+                self.context
+                    .set_message_courant(Some(self.dyn_message.message_num()));
+                self.save_last_session();
                 Command::none()
             }
             Message::InputInfo(input, id_info) => {
-                input_infos::callback_input_info(&mut self.context, &input, id_info);
+                match input_infos::callback_input_info(&mut self.context, &input, id_info) {
+                    Ok(()) => {
+                        self.invalid_inputs.remove(&id_info);
+                    }
+                    Err(invalid_input) => {
+                        self.invalid_inputs.insert(id_info, invalid_input);
+                    }
+                }
                 Command::none()
             }
             Message::SelectionEditionST2150(edition, value) => {
                 self.set_edition_st2150_visible(edition, value);
                 Command::none()
             }
-        }
+            Message::SelectionDecodingMode(decoding_mode) => {
+                self.st2150.decoding_mode = decoding_mode;
+                Command::none()
+            }
+            Message::SelectionAutoPrerequis(auto_prerequis) => {
+                self.st2150.auto_prerequis = auto_prerequis;
+                Command::none()
+            }
+            Message::SelectionCodePage(code_page) => {
+                self.st2150.code_page = code_page;
+                Command::none()
+            }
+            Message::InputSessionPath(session_path) => {
+                self.session_path = session_path;
+                Command::none()
+            }
+            Message::LoadSession => {
+                self.session_status = match self.context.load_session(Path::new(&self.session_path))
+                {
+                    Ok(()) => {
+                        self.invalid_inputs.clear();
+                        tr(&format!("Session '{}' chargée", self.session_path))
+                    }
+                    Err(e) => e.to_string(),
+                };
+                self.save_last_session();
+                Command::none()
+            }
+            Message::SaveSession => {
+                self.session_status = match self.context.save_session(Path::new(&self.session_path))
+                {
+                    Ok(()) => tr(&format!("Session '{}' sauvegardée", self.session_path)),
+                    Err(e) => e.to_string(),
+                };
+                Command::none()
+            }
+            Message::ApplyPreset(index) => {
+                if let Some(preset) = self.presets.get(index) {
+                    self.presets_status = match self.context.apply_preset(preset) {
+                        Ok(()) => tr(&format!("Preset '{}' appliqué", preset.name)),
+                        Err(e) => tr(&format!("Preset '{}' : {e}", preset.name)),
+                    };
+                    self.invalid_inputs.clear();
+                    self.save_last_session();
+                }
+                Command::none()
+            }
+            Message::InputPresetName(preset_name) => {
+                self.preset_name = preset_name;
+                Command::none()
+            }
+            Message::SavePreset => {
+                let preset_name = self.preset_name.trim();
+                if preset_name.is_empty() {
+                    self.presets_status = tr("Nom du preset à renseigner");
+                } else {
+                    // Un preset du même nom est remplacé
+                    let preset = self.context.to_preset(
+                        preset_name,
+                        &[self.dyn_message.message_num()],
+                        &self.dyn_message.id_infos_request(),
+                    );
+                    self.presets.retain(|p| p.name != preset.name);
+                    self.presets.push(preset);
+                    self.presets_status =
+                        match save_presets(&self.presets, Path::new(PRESETS_FILE_NAME)) {
+                            Ok(()) => tr(&format!("Preset '{preset_name}' sauvegardé")),
+                            Err(e) => e.to_string(),
+                        };
+                    self.preset_name = String::new();
+                }
+                Command::none()
+            }
+            Message::ShowHistory(id_info) => {
+                self.option_history_id_info = Some(id_info);
+                self.history_status = String::new();
+                Command::none()
+            }
+            Message::CloseHistory => {
+                self.option_history_id_info = None;
+                Command::none()
+            }
+            Message::ExportHistoryCsv(id_infos, file_name) => {
+                self.history_status = match self
+                    .context
+                    .save_history_csv(&id_infos, Path::new(&file_name))
+                {
+                    Ok(()) => tr(&format!("Historique exporté dans '{file_name}'")),
+                    Err(e) => tr(&format!(
+                        "Erreur d'export de l'historique '{file_name}' : {e}"
+                    )),
+                };
+                Command::none()
+            }
+            Message::ProfilFromMessage11 => {
+                self.profil_status = match self.context.profil_vehicule_from_message11() {
+                    Some(profil_vehicule) => {
+                        self.context.set_profil_vehicule(profil_vehicule);
+                        match profil_vehicule.save(Path::new(PROFIL_VEHICULE_FILE_NAME)) {
+                            Ok(()) => tr(&format!(
+                                "Profil sauvegardé dans '{PROFIL_VEHICULE_FILE_NAME}'"
+                            )),
+                            Err(e) => e.to_string(),
+                        }
+                    }
+                    None => tr("Nombre de compartiments inconnu (message 11 à exécuter)"),
+                };
+                Command::none()
+            }
+            Message::SelectionLangue(langue) => {
+                set_langue(langue);
+                Command::none()
+            }
+            Message::ShowJournal => {
+                self.show_journal = true;
+                Command::none()
+            }
+            Message::CloseJournal => {
+                self.show_journal = false;
+                Command::none()
+            }
+            Message::SelectionVacation(num_vacation) => {
+                self.option_num_vacation = Some(num_vacation);
+                Command::none()
+            }
+            Message::RerunVacation(num_vacation) => {
+                if let Some(trace) = self.get_trace_vacation(num_vacation).cloned() {
+                    self.set_current_message_num(trace.message_num);
+                    let _ = self.st2150.rerun_vacation(&mut self.context, &trace);
+                    // Un message prérequis ne change pas le message sélectionné
+                    self.context.set_message_courant(Some(trace.message_num));
+                    self.invalid_inputs.clear();
+                    self.save_last_session();
+                }
+                Command::none()
+            }
+            Message::CloseRequested => {
+                // La fenêtre est fermée : L'erreur éventuelle est affichée sur la console
+                if let Err(e) = self.context.save_session(Path::new(LAST_SESSION_FILE_NAME)) {
+                    eprintln!("{e}");
+                }
+                window::close()
+            }
+        };
+        self.refresh_preview();
+        command
+    }
+
+    /// Abonnement à la demande de fermeture de la fenêtre
+    fn subscription(&self) -> Subscription<Message> {
+        subscription::events_with(|event, _status| match event {
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        })
     }
 
     /// Mise à jour affichage de l'application
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
+        let mut row_message = row![
+            // Sélection du message courant
+            self.body_message_selection(),
+            // Partie 'requête' du message courant
+            vertical_rule(10),
+            self.view_request(),
+            // Partie 'réponse' du message courant
+            vertical_rule(10),
+            self.view_response(),
+        ];
+        // Historique d'une information de la réponse
+        if let Some(id_info) = self.option_history_id_info {
+            row_message = row_message
+                .push(vertical_rule(10))
+                .push(self.view_history(id_info));
+        }
+        // Journal des vacations de la session
+        if self.show_journal {
+            row_message = row_message
+                .push(vertical_rule(10))
+                .push(self.view_journal());
+        }
+
         column![
-            container(row![
-                // Sélection du message courant
-                self.body_message_selection(),
-                // Partie 'requête' du message courant
-                vertical_rule(10),
-                self.view_request(),
-                // Partie 'réponse' du message courant
-                vertical_rule(10),
-                self.view_response(),
-            ])
-            .height(Length::Fill),
+            container(row_message).height(Length::Fill),
             // Status/Vacation selon action
             horizontal_rule(10),
-            row![self.view_do_vacation(), self.view_edition_st2150(),].spacing(10),
+            row![
+                self.view_do_vacation(),
+                self.view_edition_st2150(),
+                self.view_decoding_mode(),
+                self.view_auto_prerequis(),
+                self.view_code_page(),
+                self.view_langue(),
+            ]
+            .spacing(10),
+            // Sauvegarde et restauration de la session
+            horizontal_rule(10),
+            self.view_session(),
+            self.view_profil_vehicule(),
             // Trace dernières requête/réponse/erreur
             horizontal_rule(10),
             self.view_vacation(),
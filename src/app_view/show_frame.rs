@@ -0,0 +1,76 @@
+//! Helpers pour l'affichage annoté d'une trame (octets en hexadécimal et en ASCII)
+
+use iced::widget::{Column, Row, Text};
+use iced::{theme, Color, Font};
+
+use super::{Element, Message};
+use sim_ie::langue::tr;
+use sim_ie::st2150::frame::{Frame, NatureSegment, Segment};
+
+/// Espacement entre les segments affichés
+const SEGMENT_SPACING: u16 = 10;
+
+/// Espacement entre les octets d'un segment
+const OCTET_SPACING: u16 = 4;
+
+/// Couleur des octets en cause dans une erreur de décodage
+const BAD_OCTET_COLOR: Color = Color::from_rgb(0.8, 0.0, 0.0);
+
+/// Libellé d'un segment (`label_champ` donne le libellé d'un champ selon son index)
+fn label_segment(segment: &Segment, label_champ: &dyn Fn(usize) -> String) -> String {
+    match segment.nature {
+        NatureSegment::Stx => "STX".to_string(),
+        NatureSegment::NumeroMessage => tr("Message"),
+        NatureSegment::Separateur => "SEP".to_string(),
+        NatureSegment::Champ(index) => label_champ(index),
+        NatureSegment::Checksum => "LRC".to_string(),
+        NatureSegment::Etx => "ETX".to_string(),
+    }
+}
+
+/// Visualisation IHM d'un octet : En hexadécimal et en ASCII ('.' si non imprimable)
+fn show_octet(octet: u8, is_en_cause: bool) -> Element<'static, Message> {
+    let ascii = if octet.is_ascii_graphic() || octet == b' ' {
+        octet as char
+    } else {
+        '.'
+    };
+    let mut txt_hexa = Text::new(format!("{octet:02X}")).font(Font::MONOSPACE);
+    let mut txt_ascii = Text::new(ascii.to_string()).font(Font::MONOSPACE);
+    if is_en_cause {
+        txt_hexa = txt_hexa.style(theme::Text::Color(BAD_OCTET_COLOR));
+        txt_ascii = txt_ascii.style(theme::Text::Color(BAD_OCTET_COLOR));
+    }
+    Column::new().push(txt_hexa).push(txt_ascii).into()
+}
+
+/// Visualisation IHM d'une trame : Pour chaque segment (STX, numéro du message, SEP, champs,
+/// LRC et ETX), ses octets en hexadécimal et en ASCII, puis son libellé
+/// Les octets aux `positions_en_cause` (voir `Frame::positions_en_cause`) sont en rouge et les
+/// segments sont affichés par lignes de `nb_segments_ligne`
+pub fn show_frame(
+    buffer: &[u8],
+    label_champ: &dyn Fn(usize) -> String,
+    positions_en_cause: &[usize],
+    nb_segments_ligne: usize,
+) -> Element<'static, Message> {
+    let segments = Frame::segments(buffer);
+    let mut col = Column::new().spacing(5);
+    for ligne in segments.chunks(nb_segments_ligne.max(1)) {
+        let mut row = Row::new().spacing(SEGMENT_SPACING);
+        for segment in ligne {
+            let mut row_octets = Row::new().spacing(OCTET_SPACING);
+            for (index, octet) in segment.octets.iter().enumerate() {
+                let is_en_cause = positions_en_cause.contains(&(segment.position + index));
+                row_octets = row_octets.push(show_octet(*octet, is_en_cause));
+            }
+            row = row.push(
+                Column::new()
+                    .push(row_octets)
+                    .push(Text::new(label_segment(segment, label_champ))),
+            );
+        }
+        col = col.push(row);
+    }
+    col.into()
+}
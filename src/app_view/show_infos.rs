@@ -1,20 +1,76 @@
 //! Helpers pour l'affichage des informations du contexte
 
-use iced::widget::Text;
+use iced::widget::tooltip::Position;
+use iced::widget::{Button, Text, Tooltip};
+use iced::{theme, Color};
 
 use super::{Element, Message};
-use crate::context;
-use context::{Context, IdInfo};
+use context::{Context, FormatInfo, IdInfo};
+use sim_ie::context;
+use sim_ie::langue::tr;
+use sim_ie::st2150::vacations::ValeurInfo;
 
 /// Affichage d'un champ non défini
 const STR_INFO_NONE: &str = "???";
 
+/// Couleur (atténuée) pour une valeur reçue lors d'une vacation précédente
+const STALE_INFO_COLOR: Color = Color::from_rgb(0.6, 0.6, 0.6);
+
+/// Libellé et valeur (avec son unité) du champ `IdInfo`
+pub fn info_to_string(context: &Context, id_info: IdInfo) -> String {
+    let mut value = context.get_info_to_string_with_unite(id_info, STR_INFO_NONE);
+    if context.get_info_format(id_info) == FormatInfo::Bool {
+        value = tr(&value);
+    }
+    format!("{} : {value}", context.get_info_label(id_info))
+}
+
+/// Libellé et valeur (avec son unité) d'une information d'une vacation du journal, tels
+/// qu'affichés lors de la vacation (`option_valeur_info` à None si l'information n'avait pas de
+/// valeur : Son libellé est alors le libellé actuel)
+pub fn valeur_info_to_string(
+    context: &Context,
+    id_info: IdInfo,
+    option_valeur_info: Option<&ValeurInfo>,
+) -> String {
+    match option_valeur_info {
+        Some(valeur_info) => format!("{} : {}", valeur_info.label, valeur_info.valeur),
+        None => format!("{} : {STR_INFO_NONE}", context.get_info_label(id_info)),
+    }
+}
+
 /// Visualisation IHM de la valeur du champ `IdInfo`
+/// L'origine de la valeur est affichée au survol et une valeur ancienne est atténuée
+/// Un clic sur la valeur affiche l'historique de ses valeurs
 pub fn show_info(context: &Context, id_info: IdInfo) -> Element<'static, Message> {
-    let txt = format!(
-        "{} : {}",
-        context.get_info_label(id_info),
-        context.get_info_to_string(id_info, STR_INFO_NONE)
-    );
-    Text::new(txt).into()
+    let mut txt = Text::new(info_to_string(context, id_info));
+    if context.is_info_stale(id_info) {
+        txt = txt.style(theme::Text::Color(STALE_INFO_COLOR));
+    }
+    let btn = Button::new(txt)
+        .padding(0)
+        .style(theme::Button::Text)
+        .on_press(Message::ShowHistory(id_info));
+    with_origine_tooltip(context, id_info, btn)
+}
+
+/// Ajout de l'origine de la valeur du champ `IdInfo` au survol d'un élément IHM
+/// (pas de survol si le champ n'est pas défini)
+pub fn with_origine_tooltip<'a>(
+    context: &Context,
+    id_info: IdInfo,
+    element: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    match context.get_option_info_origine(id_info) {
+        None => element.into(),
+        Some(origine) => {
+            let mut txt_origine = origine.to_string();
+            if context.is_info_stale(id_info) {
+                txt_origine.push_str(&tr(" (vacation précédente)"));
+            }
+            Tooltip::new(element, txt_origine, Position::FollowCursor)
+                .style(theme::Container::Box)
+                .into()
+        }
+    }
 }
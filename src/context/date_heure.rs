@@ -0,0 +1,427 @@
+//! Dates et heures calendaires des informations du contexte
+//!
+//! La ST2150 encode les dates et les heures en nombres 'packés' (`AAMMJJ`, `HHMM`, `HHMMSS` et
+//! `AAMMJJHHMMSS`). Les structures de ce module valident les mois, les jours (années
+//! bissextiles comprises), les heures, les minutes et les secondes.
+//!
+//! L'année sur 2 chiffres `AA` de la ST2150 est complétée avec le siècle selon un pivot :
+//! `AA` de `80` à `99` pour 1980 à 1999 et de `00` à `79` pour 2000 à 2079.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Pivot pour le siècle d'une année sur 2 chiffres (`AA` >= pivot pour le 20ème siècle)
+pub const PIVOT_SIECLE: u16 = 80;
+
+/// Première année possible sur 2 chiffres
+const AN_MIN: u16 = 1900 + PIVOT_SIECLE;
+
+/// Dernière année possible sur 2 chiffres
+const AN_MAX: u16 = 2000 + PIVOT_SIECLE - 1;
+
+/// Date calendaire valide
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    an: u16,
+    mois: u8,
+    jour: u8,
+}
+
+impl Date {
+    /// Constructeur d'une date valide
+    /// # Errors
+    /// Raison si la date n'existe pas ou si l'année n'est pas codable sur 2 chiffres
+    pub fn new(an: u16, mois: u8, jour: u8) -> Result<Self, String> {
+        if !(AN_MIN..=AN_MAX).contains(&an) {
+            return Err(format!("Année de {AN_MIN} à {AN_MAX}"));
+        }
+        let nb_jours_mois = match mois {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if is_bissextile(an) => 29,
+            2 => 28,
+            _ => return Err("Mois de 01 à 12".to_string()),
+        };
+        if !(1..=nb_jours_mois).contains(&jour) {
+            return Err(format!("Jour de 01 à {nb_jours_mois}"));
+        }
+        Ok(Self { an, mois, jour })
+    }
+
+    /// Date depuis son encodage ST2150 `AAMMJJ`
+    /// # Errors
+    /// Raison si la date n'existe pas
+    pub fn from_aammjj(aammjj: u32) -> Result<Self, String> {
+        if aammjj > 99_99_99 {
+            return Err("Date AAMMJJ attendue".to_string());
+        }
+        let aa = u16::try_from(aammjj / 1_00_00).unwrap();
+        let an = if aa >= PIVOT_SIECLE {
+            1900 + aa
+        } else {
+            2000 + aa
+        };
+        let mois = u8::try_from((aammjj / 1_00) % 1_00).unwrap();
+        let jour = u8::try_from(aammjj % 1_00).unwrap();
+        Self::new(an, mois, jour)
+    }
+
+    /// Encodage ST2150 `AAMMJJ` de la date
+    pub fn to_aammjj(self) -> u32 {
+        u32::from(self.an % 100) * 1_00_00 + u32::from(self.mois) * 1_00 + u32::from(self.jour)
+    }
+
+    /// Année (sur 4 chiffres)
+    pub fn an(self) -> u16 {
+        self.an
+    }
+
+    /// Mois (1 à 12)
+    pub fn mois(self) -> u8 {
+        self.mois
+    }
+
+    /// Jour du mois (1 à 31)
+    pub fn jour(self) -> u8 {
+        self.jour
+    }
+}
+
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}/{:02}/{:04}", self.jour, self.mois, self.an)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    /// Date au format `JJ/MM/AAAA` ou `AAMMJJ`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        const ERR_FORMAT: &str = "Date JJ/MM/AAAA ou AAMMJJ attendue";
+        let input = input.trim();
+        if let [jour, mois, an] = input.split('/').collect::<Vec<_>>()[..] {
+            match (
+                parse_digits(jour, 1..=2),
+                parse_digits(mois, 1..=2),
+                parse_digits(an, 4..=4),
+            ) {
+                (Some(jour), Some(mois), Some(an)) => Self::new(
+                    u16::try_from(an).unwrap(),
+                    u8::try_from(mois).unwrap(),
+                    u8::try_from(jour).unwrap(),
+                ),
+                _ => Err(ERR_FORMAT.to_string()),
+            }
+        } else {
+            match parse_digits(input, 6..=6) {
+                Some(aammjj) => Self::from_aammjj(u32::try_from(aammjj).unwrap()),
+                None => Err(ERR_FORMAT.to_string()),
+            }
+        }
+    }
+}
+
+/// Heure valide de la journée (à la seconde près)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Heure {
+    heure: u8,
+    minute: u8,
+    seconde: u8,
+}
+
+impl Heure {
+    /// Constructeur d'une heure valide
+    /// # Errors
+    /// Raison si l'heure n'existe pas
+    pub fn new(heure: u8, minute: u8, seconde: u8) -> Result<Self, String> {
+        if heure > 23 {
+            return Err("Heure de 00 à 23".to_string());
+        }
+        if minute > 59 {
+            return Err("Minute de 00 à 59".to_string());
+        }
+        if seconde > 59 {
+            return Err("Seconde de 00 à 59".to_string());
+        }
+        Ok(Self {
+            heure,
+            minute,
+            seconde,
+        })
+    }
+
+    /// Heure depuis son encodage ST2150 `HHMM`
+    /// # Errors
+    /// Raison si l'heure n'existe pas
+    pub fn from_hhmm(hhmm: u16) -> Result<Self, String> {
+        Self::from_hhmmss(u32::from(hhmm) * 1_00)
+    }
+
+    /// Heure depuis son encodage ST2150 `HHMMSS`
+    /// # Errors
+    /// Raison si l'heure n'existe pas
+    pub fn from_hhmmss(hhmmss: u32) -> Result<Self, String> {
+        if hhmmss > 99_99_99 {
+            return Err("Heure de 00 à 23".to_string());
+        }
+        Self::new(
+            u8::try_from(hhmmss / 1_00_00).unwrap(),
+            u8::try_from((hhmmss / 1_00) % 1_00).unwrap(),
+            u8::try_from(hhmmss % 1_00).unwrap(),
+        )
+    }
+
+    /// Encodage ST2150 `HHMM` de l'heure (sans les secondes)
+    pub fn to_hhmm(self) -> u16 {
+        u16::from(self.heure) * 1_00 + u16::from(self.minute)
+    }
+
+    /// Encodage ST2150 `HHMMSS` de l'heure
+    pub fn to_hhmmss(self) -> u32 {
+        u32::from(self.heure) * 1_00_00 + u32::from(self.minute) * 1_00 + u32::from(self.seconde)
+    }
+
+    /// Même heure sans les secondes
+    #[must_use]
+    pub fn without_seconde(self) -> Self {
+        Self { seconde: 0, ..self }
+    }
+
+    /// Heure (0 à 23)
+    pub fn heure(self) -> u8 {
+        self.heure
+    }
+
+    /// Minute (0 à 59)
+    pub fn minute(self) -> u8 {
+        self.minute
+    }
+
+    /// Seconde (0 à 59)
+    pub fn seconde(self) -> u8 {
+        self.seconde
+    }
+
+    /// Représentation `HH:MM` de l'heure (sans les secondes)
+    pub fn to_string_hhmm(self) -> String {
+        format!("{:02}:{:02}", self.heure, self.minute)
+    }
+}
+
+impl Display for Heure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}",
+            self.heure, self.minute, self.seconde
+        )
+    }
+}
+
+impl FromStr for Heure {
+    type Err = String;
+
+    /// Heure au format `HH:MM`, `HH:MM:SS`, `HHMM` ou `HHMMSS`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        const ERR_FORMAT: &str = "Heure HH:MM, HH:MM:SS, HHMM ou HHMMSS attendue";
+        let input = input.trim();
+        let parts: Vec<_> = input.split(':').collect();
+        let (heure, minute, seconde) = match parts[..] {
+            [hhmmss] if hhmmss.len() == 4 || hhmmss.len() == 6 => {
+                let Some(hhmmss) = parse_digits(hhmmss, 4..=6) else {
+                    return Err(ERR_FORMAT.to_string());
+                };
+                if input.len() == 4 {
+                    (hhmmss / 1_00, hhmmss % 1_00, 0)
+                } else {
+                    (hhmmss / 1_00_00, (hhmmss / 1_00) % 1_00, hhmmss % 1_00)
+                }
+            }
+            [heure, minute] => match (parse_digits(heure, 1..=2), parse_digits(minute, 2..=2)) {
+                (Some(heure), Some(minute)) => (heure, minute, 0),
+                _ => return Err(ERR_FORMAT.to_string()),
+            },
+            [heure, minute, seconde] => match (
+                parse_digits(heure, 1..=2),
+                parse_digits(minute, 2..=2),
+                parse_digits(seconde, 2..=2),
+            ) {
+                (Some(heure), Some(minute), Some(seconde)) => (heure, minute, seconde),
+                _ => return Err(ERR_FORMAT.to_string()),
+            },
+            _ => return Err(ERR_FORMAT.to_string()),
+        };
+        Self::new(
+            u8::try_from(heure).unwrap(),
+            u8::try_from(minute).unwrap(),
+            u8::try_from(seconde).unwrap(),
+        )
+    }
+}
+
+/// Date et heure valides
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateHeure {
+    /// Date
+    pub date: Date,
+
+    /// Heure (avec les secondes)
+    pub heure: Heure,
+}
+
+impl DateHeure {
+    /// Date et heure depuis leur encodage ST2150 `AAMMJJHHMMSS`
+    /// # Errors
+    /// Raison si la date ou l'heure n'existe pas
+    pub fn from_aammjjhhmmss(aammjjhhmmss: u64) -> Result<Self, String> {
+        let Ok(aammjj) = u32::try_from(aammjjhhmmss / 1_00_00_00) else {
+            return Err("Date AAMMJJ attendue".to_string());
+        };
+        let hhmmss = u32::try_from(aammjjhhmmss % 1_00_00_00).unwrap();
+        Ok(Self {
+            date: Date::from_aammjj(aammjj)?,
+            heure: Heure::from_hhmmss(hhmmss)?,
+        })
+    }
+
+    /// Encodage ST2150 `AAMMJJHHMMSS` de la date et de l'heure
+    pub fn to_aammjjhhmmss(self) -> u64 {
+        u64::from(self.date.to_aammjj()) * 1_00_00_00 + u64::from(self.heure.to_hhmmss())
+    }
+}
+
+impl Display for DateHeure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.date, self.heure)
+    }
+}
+
+impl FromStr for DateHeure {
+    type Err = String;
+
+    /// Date et heure au format `JJ/MM/AAAA HH:MM:SS` ou `AAMMJJHHMMSS`
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        const ERR_FORMAT: &str = "Date et heure JJ/MM/AAAA HH:MM:SS ou AAMMJJHHMMSS attendues";
+        let input = input.trim();
+        if let Some((date, heure)) = input.split_once(' ') {
+            Ok(Self {
+                date: date.parse()?,
+                heure: heure.parse()?,
+            })
+        } else {
+            match parse_digits(input, 12..=12) {
+                Some(aammjjhhmmss) => Self::from_aammjjhhmmss(aammjjhhmmss),
+                None => Err(ERR_FORMAT.to_string()),
+            }
+        }
+    }
+}
+
+/// Année bissextile ?
+fn is_bissextile(an: u16) -> bool {
+    an.is_multiple_of(4) && (!an.is_multiple_of(100) || an.is_multiple_of(400))
+}
+
+/// Helper pour décoder un nombre avec un nombre de chiffres décimaux dans un intervalle
+fn parse_digits(input: &str, nb_digits: std::ops::RangeInclusive<usize>) -> Option<u64> {
+    if nb_digits.contains(&input.len()) && input.bytes().all(|c| c.is_ascii_digit()) {
+        input.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date() {
+        // Siècle selon le pivot
+        let date = Date::from_aammjj(99_12_31).unwrap();
+        assert_eq!((date.an(), date.mois(), date.jour()), (1999, 12, 31));
+        assert_eq!(date.to_aammjj(), 99_12_31);
+        assert_eq!(date.to_string(), "31/12/1999");
+        assert_eq!(Date::from_aammjj(1_02_03).unwrap().an(), 2001);
+        assert_eq!(Date::from_aammjj(79_01_01).unwrap().an(), 2079);
+        assert_eq!(Date::from_aammjj(80_01_01).unwrap().an(), 1980);
+
+        // Années bissextiles
+        assert!(Date::from_aammjj(24_02_29).is_ok());
+        assert_eq!(
+            Date::from_aammjj(23_02_29),
+            Err("Jour de 01 à 28".to_string())
+        );
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert!(Date::new(2100, 2, 28).is_err());
+
+        // Dates impossibles
+        assert_eq!(
+            Date::from_aammjj(99_13_99),
+            Err("Mois de 01 à 12".to_string())
+        );
+        assert!(Date::from_aammjj(99_04_31).is_err());
+        assert!(Date::from_aammjj(99_04_00).is_err());
+        assert!(Date::from_aammjj(1_00_00_00).is_err());
+
+        // Saisies
+        assert_eq!("31/12/1999".parse(), Ok(date));
+        assert_eq!("991231".parse(), Ok(date));
+        assert_eq!("1/2/2024".parse::<Date>(), Date::new(2024, 2, 1));
+        assert!("31-12-1999".parse::<Date>().is_err());
+        assert!("9912".parse::<Date>().is_err());
+        assert!("31/12/99".parse::<Date>().is_err());
+
+        // Ordre chronologique
+        assert!(Date::from_aammjj(99_12_31).unwrap() < Date::from_aammjj(1_01).unwrap());
+    }
+
+    #[test]
+    fn test_heure() {
+        let heure = Heure::from_hhmmss(23_59_59).unwrap();
+        assert_eq!(
+            (heure.heure(), heure.minute(), heure.seconde()),
+            (23, 59, 59)
+        );
+        assert_eq!(heure.to_hhmmss(), 23_59_59);
+        assert_eq!(heure.to_hhmm(), 23_59);
+        assert_eq!(heure.to_string(), "23:59:59");
+        assert_eq!(heure.to_string_hhmm(), "23:59");
+        assert_eq!(heure.without_seconde(), Heure::from_hhmm(23_59).unwrap());
+
+        // Heures impossibles
+        assert_eq!(Heure::from_hhmm(25_75), Err("Heure de 00 à 23".to_string()));
+        assert_eq!(
+            Heure::from_hhmm(12_60),
+            Err("Minute de 00 à 59".to_string())
+        );
+        assert_eq!(
+            Heure::from_hhmmss(12_00_60),
+            Err("Seconde de 00 à 59".to_string())
+        );
+
+        // Saisies
+        assert_eq!("23:59:59".parse(), Ok(heure));
+        assert_eq!("235959".parse(), Ok(heure));
+        assert_eq!("2359".parse(), Ok(heure.without_seconde()));
+        assert_eq!("8:05".parse::<Heure>(), Heure::new(8, 5, 0));
+        assert!("12h30".parse::<Heure>().is_err());
+        assert!("123".parse::<Heure>().is_err());
+        assert!("12:3".parse::<Heure>().is_err());
+    }
+
+    #[test]
+    fn test_date_heure() {
+        let date_heure = DateHeure::from_aammjjhhmmss(99_12_31_23_59_59).unwrap();
+        assert_eq!(date_heure.to_aammjjhhmmss(), 99_12_31_23_59_59);
+        assert_eq!(date_heure.to_string(), "31/12/1999 23:59:59");
+        assert_eq!("31/12/1999 23:59:59".parse(), Ok(date_heure));
+        assert_eq!("991231235959".parse(), Ok(date_heure));
+
+        assert!(DateHeure::from_aammjjhhmmss(99_13_99_23_59_59).is_err());
+        assert!(DateHeure::from_aammjjhhmmss(99_12_31_25_00_00).is_err());
+        assert!(DateHeure::from_aammjjhhmmss(u64::MAX).is_err());
+        assert!("31/12/1999".parse::<DateHeure>().is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Règles de déduction d'informations du contexte à partir d'autres informations
+//!
+//! Chaque règle déclare les informations 'sources' qui la déclenchent et le calcul des
+//! informations 'déduites'. Lors de chaque mise à jour d'une information du contexte, toutes
+//! les règles dont elle est une source sont appliquées (voir `callback_info_on_change`).
+//!
+//! Pour ajouter une information déduite, il suffit d'ajouter une règle dans `DERIVATION_RULES`
+
+use super::{Context, IdInfo, TValue, NB_COMPARTIMENTS};
+
+/// Information déduite et sa valeur calculée (None si elle ne peut pas être calculée)
+pub(super) type InfoDeduite = (IdInfo, Option<TValue>);
+
+/// Règle de déduction d'informations du contexte
+pub(super) struct DerivationRule {
+    /// Indique si une information est une source de cette règle
+    pub(super) is_source: fn(IdInfo) -> bool,
+
+    /// Calcul des valeurs de toutes les informations déduites (depuis le contexte déjà mis à
+    /// jour). Une information déduite dont la valeur ne peut pas être calculée est à `None` :
+    /// Elle est alors remise à `None` dans le contexte
+    pub(super) derive: fn(&Context) -> Vec<InfoDeduite>,
+}
+
+/// Table de toutes les règles de déduction d'informations du contexte
+pub(super) const DERIVATION_RULES: &[DerivationRule] = &[
+    // HeureHHMMSS -> HeureHHMM
+    DerivationRule {
+        is_source: |id_info| id_info == IdInfo::HeureHHMMSS,
+        derive: |context| {
+            vec![(
+                IdInfo::HeureHHMM,
+                context
+                    .get_option_info_heure_hhmmss(IdInfo::HeureHHMMSS)
+                    .map(TValue::HeureHHMM),
+            )]
+        },
+    },
+    // DateAAMMJJHeureHHMMSS -> DateAAMMJJ, HeureHHMMSS (-> HeureHHMM)
+    DerivationRule {
+        is_source: |id_info| id_info == IdInfo::DateAAMMJJHeureHHMMSS,
+        derive: |context| {
+            let option_date_heure =
+                context.get_option_info_date_heure(IdInfo::DateAAMMJJHeureHHMMSS);
+            vec![
+                (
+                    IdInfo::DateAAMMJJ,
+                    option_date_heure.map(|date_heure| TValue::Date(date_heure.date)),
+                ),
+                (
+                    IdInfo::HeureHHMMSS,
+                    option_date_heure.map(|date_heure| TValue::HeureHHMMSS(date_heure.heure)),
+                ),
+            ]
+        },
+    },
+    // QuantiteCompartiment(1..=NB_COMPARTIMENTS) -> QuantiteTotaleCompartiments
+    // (somme des quantités des compartiments définies)
+    DerivationRule {
+        is_source: |id_info| matches!(id_info, IdInfo::QuantiteCompartiment(_)),
+        derive: |context| {
+            let quantite_totale = (1..=NB_COMPARTIMENTS)
+                .filter_map(|compart_num| {
+                    context.get_option_info_u32(IdInfo::QuantiteCompartiment(compart_num))
+                })
+                .sum();
+            vec![(
+                IdInfo::QuantiteTotaleCompartiments,
+                Some(TValue::U32(quantite_totale)),
+            )]
+        },
+    },
+    // CodeProduit, LibelleTableProduits(..) -> LibelleCodeProduit
+    // (libellé du code produit dans la table des produits)
+    DerivationRule {
+        is_source: |id_info| {
+            matches!(
+                id_info,
+                IdInfo::CodeProduit | IdInfo::LibelleTableProduits(_)
+            )
+        },
+        derive: |context| {
+            let option_libelle =
+                context
+                    .get_option_info_u8(IdInfo::CodeProduit)
+                    .and_then(|code_produit| {
+                        context.get_option_info_string(IdInfo::LibelleTableProduits(usize::from(
+                            code_produit,
+                        )))
+                    });
+            vec![(
+                IdInfo::LibelleCodeProduit,
+                option_libelle.map(TValue::String),
+            )]
+        },
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quantite_totale_compartiments() {
+        let mut context = Context::default();
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::QuantiteTotaleCompartiments),
+            None
+        );
+
+        context.set_info_u32(IdInfo::QuantiteCompartiment(1), 1000);
+        context.set_info_u32(IdInfo::QuantiteCompartiment(3), 2500);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::QuantiteTotaleCompartiments),
+            Some(3500)
+        );
+
+        // Un compartiment qui n'est plus défini n'est plus compté
+        assert_eq!(
+            context.set_info_from_string(IdInfo::QuantiteCompartiment(1), ""),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::QuantiteTotaleCompartiments),
+            Some(2500)
+        );
+    }
+
+    #[test]
+    fn test_libelle_code_produit() {
+        let mut context = Context::default();
+
+        // Libellé inconnu tant que la table des produits n'est pas définie
+        context.set_info_u8(IdInfo::CodeProduit, 2);
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleCodeProduit),
+            None
+        );
+
+        // Mise à jour de la table des produits puis du code produit
+        context.set_info_string(IdInfo::LibelleTableProduits(1), "SP95");
+        context.set_info_string(IdInfo::LibelleTableProduits(2), "GAZOLE");
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleCodeProduit),
+            Some("GAZOLE".to_string())
+        );
+        context.set_info_u8(IdInfo::CodeProduit, 1);
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleCodeProduit),
+            Some("SP95".to_string())
+        );
+
+        // Code produit absent de la table des produits : Libellé inconnu
+        context.set_info_u8(IdInfo::CodeProduit, 2);
+        context.set_info_u8(IdInfo::CodeProduit, 5);
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleCodeProduit),
+            None
+        );
+    }
+}
@@ -0,0 +1,577 @@
+//! Table statique des descripteurs des informations du contexte
+//!
+//! Le descripteur d'une information regroupe ses propriétés invariables : Libellé, format,
+//! valeurs min/max, regex de saisie, grandeur physique, précision et formats spécifiques à
+//! certains messages. Le contexte ne stocke que les valeurs des informations (voir `Info`),
+//! ce qui rend la création d'un contexte peu coûteuse.
+//!
+//! Une information indexée (par exemple `QuantiteCompartiment(n)`) a un seul descripteur pour
+//! tous ses index : `{}` dans son libellé est remplacé par l'index.
+//!
+//! Pour ajouter une information, il suffit d'ajouter son descripteur dans `DESCRIPTEURS`
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use super::variante::VarianteInfo;
+use super::{FormatInfo, Grandeur, IdInfo, TValue};
+use super::{NB_COMPARTIMENTS, NB_FLEXIBLES, NB_PRODUITS};
+
+/// Nombre de caractères pour un libellé produit
+const LIBELLE_PRODUIT_WIDTH: usize = 10;
+
+/// Nombre de caractères pour un libellé produit court (messages 32 et 33 de l'édition A)
+const LIBELLE_PRODUIT_COURT_WIDTH: usize = 5;
+
+/// Valeurs max. des codes produits, des numéros de compartiment et des numéros de flexible
+/// (toujours < 256)
+#[allow(clippy::cast_possible_truncation)]
+const MAX_CODE_PRODUIT: TValue = TValue::U8(NB_PRODUITS as u8);
+#[allow(clippy::cast_possible_truncation)]
+const MAX_NUMERO_COMPARTIMENT: TValue = TValue::U8(NB_COMPARTIMENTS as u8);
+#[allow(clippy::cast_possible_truncation)]
+const MAX_NUMERO_FLEXIBLE: TValue = TValue::U8(NB_FLEXIBLES as u8);
+
+/// Propriétés invariables d'une information du contexte
+#[derive(Debug)]
+pub(super) struct Descripteur {
+    /// Information décrite (index 0 pour une information indexée)
+    pub(super) id_info: IdInfo,
+
+    /// Index max. d'une information indexée (de 0 à cet index, None si non indexée)
+    pub(super) option_index_max: Option<usize>,
+
+    /// Libellé présenté pour l'information (en français, clé de sa traduction, voir `tr`)
+    /// `{}` est remplacé par l'index d'une information indexée
+    pub(super) label: &'static str,
+
+    /// Format choisi pour l'information
+    pub(super) format_info: FormatInfo,
+
+    /// Regex optionnelle pour valider la valeur de l'information (from_string)
+    pub(super) option_regex: Option<&'static str>,
+
+    /// Valeur min optionnelle (pour les nombres)
+    pub(super) option_min_t_value: Option<TValue>,
+
+    /// Valeur max optionnelle (pour les nombres)
+    pub(super) option_max_t_value: Option<TValue>,
+
+    /// Grandeur physique optionnelle (l'unité en dépend, voir `get_info_unite`)
+    pub(super) option_grandeur: Option<Grandeur>,
+
+    /// Nombre de décimales affichées (pour les `F32`)
+    pub(super) precision: usize,
+
+    /// Formats et libellés spécifiques à certains messages (voir `get_message_courant`)
+    pub(super) variantes: &'static [VarianteInfo],
+}
+
+/// Descripteur par défaut (propriétés à définir précisément pour chaque information)
+const DESCRIPTEUR: Descripteur = Descripteur {
+    id_info: IdInfo::Ack,
+    option_index_max: None,
+    label: "Non défini",
+    format_info: FormatInfo::Bool,
+    option_regex: None,
+    option_min_t_value: None,
+    option_max_t_value: None,
+    option_grandeur: None,
+    precision: 1,
+    variantes: &[],
+};
+
+/// Table des descripteurs de toutes les informations du contexte
+static DESCRIPTEURS: &[Descripteur] = &[
+    Descripteur {
+        id_info: IdInfo::Ack,
+        label: "Acquit message",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::Nack,
+        label: "Refus message",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::EnMesurage,
+        label: "En mesurage",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeDefaut,
+        label: "En Code défaut",
+        format_info: FormatInfo::U8,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::ArretIntermediaire,
+        label: "Arrêt intermédiaire",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::ForcagePetitDebit,
+        label: "Forçage petit débit",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::ModeConnecte,
+        label: "Mode connecté",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::Totalisateur,
+        label: "Totalisateur",
+        format_info: FormatInfo::U32,
+        option_grandeur: Some(Grandeur::Quantite),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::DebitInstant,
+        label: "Débit instantané",
+        format_info: FormatInfo::F32,
+        option_grandeur: Some(Grandeur::Debit),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::QuantitePrincipale,
+        label: "Quantité",
+        format_info: FormatInfo::U32,
+        option_grandeur: Some(Grandeur::Quantite),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::QuantiteSecondaire,
+        label: "Quantité secondaire",
+        format_info: FormatInfo::U32,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::TemperatureInstant,
+        label: "Quantité Température instantanée",
+        format_info: FormatInfo::F32,
+        option_grandeur: Some(Grandeur::Temperature),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::TemperatureMoyen,
+        label: "Température moyenne",
+        format_info: FormatInfo::F32,
+        option_grandeur: Some(Grandeur::Temperature),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::Predetermination,
+        label: "Prédétermination",
+        format_info: FormatInfo::U32,
+        option_max_t_value: Some(TValue::U32(99999)),
+        option_grandeur: Some(Grandeur::Quantite),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduit,
+        label: "Code produit",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::IndexSansRaz,
+        label: "Index sans remise à zéro",
+        format_info: FormatInfo::U16,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::IndexJournalier,
+        label: "Index journalier",
+        format_info: FormatInfo::U16,
+        option_max_t_value: Some(TValue::U16(999)),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::Quantieme,
+        label: "Quantième",
+        format_info: FormatInfo::U16,
+        option_max_t_value: Some(TValue::U16(366)),
+        option_min_t_value: Some(TValue::U16(1)),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::HeureHHMMDebut,
+        label: "Heure de début",
+        format_info: FormatInfo::HeureHHMM,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::HeureHHMMFin,
+        label: "Heure de fin",
+        format_info: FormatInfo::HeureHHMM,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::IdentificationTag,
+        label: "Identification TAG",
+        format_info: FormatInfo::String(100),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::ReferenceEtImmatriculation,
+        label: "Référence et immatriculation",
+        format_info: FormatInfo::String(15),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::VersionLogiciel,
+        label: "Version du logiciel",
+        format_info: FormatInfo::String(10),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::DateAAMMJJHeureHHMMSS,
+        label: "Date et Heure",
+        format_info: FormatInfo::DateHeure,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::TypeCompteur,
+        label: "Type de compteur (0:Vm, 1:Vb, 2:Masse)",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(TValue::U8(2)),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NbMesuragesQuantieme,
+        label: "Nombre de mesurages pour un quantième",
+        format_info: FormatInfo::U16,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::LibelleProduit,
+        label: "Libellé produit",
+        format_info: FormatInfo::String(LIBELLE_PRODUIT_WIDTH),
+        variantes: &[VarianteInfo::new(
+            &[32],
+            FormatInfo::String(LIBELLE_PRODUIT_COURT_WIDTH),
+            "Libellé produit (court)",
+        )],
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NbFractionnements,
+        label: "Nombre de fractionnements",
+        format_info: FormatInfo::U16,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::LibelleTableProduits(0),
+        option_index_max: Some(NB_PRODUITS),
+        label: "Libellé table produit #{}",
+        format_info: FormatInfo::String(LIBELLE_PRODUIT_WIDTH),
+        variantes: &[VarianteInfo::new(
+            &[33],
+            FormatInfo::String(LIBELLE_PRODUIT_COURT_WIDTH),
+            "Libellé table produit #{} (court)",
+        )],
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::LibelleCodeProduit,
+        label: "Libellé du code produit",
+        format_info: FormatInfo::String(LIBELLE_PRODUIT_WIDTH),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::IndexFractionnement,
+        label: "Index fractionnement",
+        format_info: FormatInfo::U16,
+        option_max_t_value: Some(TValue::U16(999)),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::TypeDistribution,
+        label: "(A)nticipation purge, li(B)ération, (C)hargement, pré(D)é, (L)ibre, (P)urge, (T)ransfert, (V)idange",
+        format_info: FormatInfo::Char,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::DateAAMMJJ,
+        label: "Date",
+        format_info: FormatInfo::Date,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::HeureHHMMSS,
+        label: "Heure (HH:MM:SS)",
+        format_info: FormatInfo::HeureHHMMSS,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::HeureHHMM,
+        label: "Heure (HH:MM)",
+        format_info: FormatInfo::HeureHHMM,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NbJEvents,
+        label: "Nombre d'événements",
+        format_info: FormatInfo::U16,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::DataJEvent,
+        label: "Données techniques événement",
+        format_info: FormatInfo::String(12),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::LibelleJEvent,
+        label: "Libellé événement",
+        format_info: FormatInfo::String(40),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitCompartiment(0),
+        option_index_max: Some(NB_COMPARTIMENTS),
+        label: "Code produit cpt #{}",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::QuantiteCompartiment(0),
+        option_index_max: Some(NB_COMPARTIMENTS),
+        label: "Quantité cpt #{}",
+        format_info: FormatInfo::U32,
+        option_max_t_value: Some(TValue::U32(99999)),
+        option_grandeur: Some(Grandeur::Quantite),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::QuantiteTotaleCompartiments,
+        label: "Quantité totale cpts",
+        format_info: FormatInfo::U32,
+        option_grandeur: Some(Grandeur::Quantite),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NombreCompartiments,
+        label: "Nombre de compartiments",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_NUMERO_COMPARTIMENT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitCollecteur,
+        label: "Code produit dans le collecteur",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitPartieCommune,
+        label: "Code produit dans la partie commune",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitFlexible1,
+        label: "Code produit dans le flexible #1",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitFlexible2,
+        label: "Code produit dans le flexible #2",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeErreurMouvementProduit,
+        label: "Code erreur (1:Non supporté, 2:En opération)",
+        format_info: FormatInfo::U8,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::CodeProduitFinal,
+        label: "Code produit final",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_CODE_PRODUIT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NumeroCompartiment,
+        label: "No de compartiment (ou T pour remorque)",
+        format_info: FormatInfo::U8OrT,
+        option_max_t_value: Some(MAX_NUMERO_COMPARTIMENT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::PresenceRemorque,
+        label: "Présence d'une remorque",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NumeroCompartimentFinal,
+        label: "No de compartiment final (ou T)",
+        format_info: FormatInfo::U8OrT,
+        option_max_t_value: Some(MAX_NUMERO_COMPARTIMENT),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::OrdreCompartiments,
+        label: "Ordre des compartiments",
+        format_info: FormatInfo::String(9),
+        option_regex: Some(r"^\d{1,9}$"),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NumeroFlexible,
+        label: "Numéro de flexible",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_NUMERO_FLEXIBLE),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::NumeroFlexibleFinal,
+        label: "Numéro de flexible final",
+        format_info: FormatInfo::U8,
+        option_max_t_value: Some(MAX_NUMERO_FLEXIBLE),
+        ..DESCRIPTEUR
+    },
+    Descripteur {
+        id_info: IdInfo::FinirFlexibleVide,
+        label: "Finir flexible vide",
+        format_info: FormatInfo::Bool,
+        ..DESCRIPTEUR
+    },
+];
+
+/// Information indexée ramenée à l'index 0 (pour sa recherche dans `DESCRIPTEURS`) et son index
+fn id_info_index(id_info: IdInfo) -> (IdInfo, Option<usize>) {
+    match id_info {
+        IdInfo::LibelleTableProduits(index) => (IdInfo::LibelleTableProduits(0), Some(index)),
+        IdInfo::CodeProduitCompartiment(index) => (IdInfo::CodeProduitCompartiment(0), Some(index)),
+        IdInfo::QuantiteCompartiment(index) => (IdInfo::QuantiteCompartiment(0), Some(index)),
+        _ => (id_info, None),
+    }
+}
+
+/// Descripteur d'une information du contexte
+/// (None pour une information inconnue, par exemple un index hors limites)
+/// Les descripteurs sont indexés par information une seule fois, à la première utilisation
+pub(super) fn get_descripteur(id_info: IdInfo) -> Option<&'static Descripteur> {
+    static DESCRIPTEURS_INDEXES: OnceLock<HashMap<IdInfo, &'static Descripteur>> = OnceLock::new();
+    let descripteurs = DESCRIPTEURS_INDEXES.get_or_init(|| {
+        DESCRIPTEURS
+            .iter()
+            .map(|descripteur| (descripteur.id_info, descripteur))
+            .collect()
+    });
+    let (id_info_0, option_index) = id_info_index(id_info);
+    descripteurs.get(&id_info_0).copied().filter(|descripteur| {
+        option_index.unwrap_or_default() <= descripteur.option_index_max.unwrap_or_default()
+    })
+}
+
+/// Libellé d'une information (avec son index pour une information indexée)
+/// à partir du libellé de son descripteur (sans allocation pour une information non indexée)
+pub(super) fn label_with_index(label: &'static str, id_info: IdInfo) -> Cow<'static, str> {
+    match id_info_index(id_info) {
+        (_, Some(index)) => Cow::Owned(label.replace("{}", &index.to_string())),
+        (_, None) => Cow::Borrowed(label),
+    }
+}
+
+/// Toutes les informations du contexte (avec tous les index des informations indexées)
+pub(super) fn all_id_infos() -> impl Iterator<Item = IdInfo> {
+    DESCRIPTEURS
+        .iter()
+        .flat_map(|descripteur| match descripteur.option_index_max {
+            None => vec![descripteur.id_info],
+            Some(index_max) => (0..=index_max)
+                .map(|index| match descripteur.id_info {
+                    IdInfo::LibelleTableProduits(_) => IdInfo::LibelleTableProduits(index),
+                    IdInfo::CodeProduitCompartiment(_) => IdInfo::CodeProduitCompartiment(index),
+                    IdInfo::QuantiteCompartiment(_) => IdInfo::QuantiteCompartiment(index),
+                    id_info => id_info,
+                })
+                .collect(),
+        })
+}
+
+/// Regex d'un descripteur (les regex de la table sont compilées une seule fois, à la première
+/// utilisation)
+/// # panics
+/// panic! si la regex n'est pas dans la table ou est incorrecte
+pub(super) fn get_regex(pattern: &'static str) -> &'static Regex {
+    static REGEXES: OnceLock<HashMap<&'static str, Regex>> = OnceLock::new();
+    let regexes = REGEXES.get_or_init(|| {
+        DESCRIPTEURS
+            .iter()
+            .filter_map(|descripteur| descripteur.option_regex)
+            .map(|pattern| (pattern, Regex::new(pattern).unwrap()))
+            .collect()
+    });
+    &regexes[pattern]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descripteurs() {
+        // Pas d'information en double dans la table
+        for (index, descripteur) in DESCRIPTEURS.iter().enumerate() {
+            assert!(
+                DESCRIPTEURS[index + 1..]
+                    .iter()
+                    .all(|autre| autre.id_info != descripteur.id_info),
+                "{:?} en double",
+                descripteur.id_info
+            );
+        }
+
+        // Informations indexées
+        assert!(get_descripteur(IdInfo::QuantiteCompartiment(NB_COMPARTIMENTS)).is_some());
+        assert!(get_descripteur(IdInfo::QuantiteCompartiment(NB_COMPARTIMENTS + 1)).is_none());
+        assert_eq!(
+            label_with_index("Quantité cpt #{}", IdInfo::QuantiteCompartiment(3)),
+            "Quantité cpt #3"
+        );
+        assert!(matches!(
+            label_with_index("Totalisateur", IdInfo::Totalisateur),
+            Cow::Borrowed("Totalisateur")
+        ));
+
+        // Chaque descripteur est retrouvé à partir de son information
+        for descripteur in DESCRIPTEURS {
+            assert!(std::ptr::eq(
+                get_descripteur(descripteur.id_info).unwrap(),
+                descripteur
+            ));
+        }
+        assert_eq!(
+            all_id_infos()
+                .filter(|id_info| matches!(id_info, IdInfo::LibelleTableProduits(_)))
+                .count(),
+            NB_PRODUITS + 1
+        );
+
+        // Regex compilées
+        assert!(get_regex(r"^\d{1,9}$").is_match("123"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Historique des valeurs prises par chaque information du contexte
+//!
+//! Chaque mise à jour d'une information est mémorisée avec son origine (horodatage, provenance
+//! et numéro de vacation), dans la limite des `HISTORY_MAX_LEN` dernières valeurs.
+//!
+//! L'historique d'une ou plusieurs informations peut être exporté au format CSV (séparateur `;`)
+//! pour une exploitation dans un tableur, par exemple le débit, la quantité et la température
+//! relevés pendant une livraison :
+//!
+//! ```text
+//! Horodatage (UTC);Vacation;Provenance;Information;Valeur;Unité
+//! 2024-01-01 10:00:00.000;1;Réponse message 10;Débit instantané;350.0;m³/h
+//! ```
+
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{Context, IdInfo, Origine, TValue, Unite};
+use crate::langue::tr;
+
+/// Nombre max de valeurs mémorisées dans l'historique de chaque information
+pub const HISTORY_MAX_LEN: usize = 1000;
+
+/// Séparateur des colonnes d'un export CSV
+const CSV_SEPARATOR: char = ';';
+
+/// Entête d'un export CSV
+const CSV_HEADER: &str = "Horodatage (UTC);Vacation;Provenance;Information;Valeur;Unité";
+
+/// Valeur prise par une information du contexte
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    /// Valeur de l'information
+    pub t_value: TValue,
+
+    /// Origine de cette valeur
+    pub origine: Origine,
+
+    /// Unité de cette valeur (None pour une information sans unité)
+    pub option_unite: Option<Unite>,
+}
+
+/// Historique des valeurs d'une information (de la plus ancienne à la plus récente)
+pub type History = VecDeque<HistoryEntry>;
+
+impl Context {
+    /// Mémorise la valeur actuelle d'une information dans son historique
+    /// (sans effet si l'information n'est pas définie)
+    pub(super) fn push_info_history(&mut self, id_info: IdInfo) {
+        let option_unite = self.get_info_unite(id_info);
+        let inner_info = self.get_mut_inner_info(id_info);
+        if let (Some(t_value), Some(origine)) =
+            (&inner_info.option_t_value, &inner_info.option_origine)
+        {
+            let history_entry = HistoryEntry {
+                t_value: t_value.clone(),
+                origine: origine.clone(),
+                option_unite,
+            };
+            inner_info.history.push_back(history_entry);
+            if inner_info.history.len() > HISTORY_MAX_LEN {
+                if let Some(history_entry) = inner_info.history.pop_front() {
+                    self.save_history_retiree(id_info, history_entry);
+                }
+            }
+        }
+    }
+
+    /// Historique des valeurs d'une information du contexte
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn get_info_history(&self, id_info: IdInfo) -> &History {
+        &self.get_inner_info(id_info).history
+    }
+
+    /// Efface l'historique de toutes les informations du contexte
+    pub fn clear_all_histories(&mut self) {
+        for inner_info in self.hash_id_infos.values_mut() {
+            inner_info.history.clear();
+        }
+    }
+
+    /// Export CSV de l'historique d'une ou plusieurs informations du contexte
+    /// (une ligne par valeur, triées par horodatage, dans la langue courante de l'outil)
+    /// # panics
+    /// panic! si un `IdInfo` n'est pas reconnu
+    pub fn history_to_csv(&self, id_infos: &[IdInfo]) -> String {
+        let mut entries: Vec<(IdInfo, &HistoryEntry)> = id_infos
+            .iter()
+            .flat_map(|id_info| {
+                self.get_info_history(*id_info)
+                    .iter()
+                    .map(|history_entry| (*id_info, history_entry))
+            })
+            .collect();
+        entries.sort_by_key(|(_, history_entry)| history_entry.origine.horodatage);
+
+        let mut csv = tr(CSV_HEADER);
+        csv.push('\n');
+        for (id_info, history_entry) in entries {
+            let columns = [
+                horodatage_to_string(history_entry.origine.horodatage),
+                history_entry.origine.num_vacation.to_string(),
+                history_entry.origine.provenance.to_string(),
+                self.get_info_label(id_info).into_owned(),
+                self.t_value_to_string(id_info, &history_entry.t_value),
+                history_entry
+                    .option_unite
+                    .map(|unite| unite.to_string())
+                    .unwrap_or_default(),
+            ];
+            let columns: Vec<String> = columns.iter().map(|column| csv_escape(column)).collect();
+            csv.push_str(&columns.join(&CSV_SEPARATOR.to_string()));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Export CSV de l'historique d'une ou plusieurs informations du contexte dans un fichier
+    /// # Errors
+    /// Erreur si le fichier ne peut pas être écrit
+    pub fn save_history_csv(&self, id_infos: &[IdInfo], path: &Path) -> std::io::Result<()> {
+        fs::write(path, self.history_to_csv(id_infos))
+    }
+}
+
+/// Colonne CSV entre guillemets si elle contient un séparateur, un guillemet ou un saut de ligne
+fn csv_escape(column: &str) -> String {
+    if column.contains([CSV_SEPARATOR, '"', '\n']) {
+        format!("\"{}\"", column.replace('"', "\"\""))
+    } else {
+        column.to_string()
+    }
+}
+
+/// Représentation 'textuelle' d'un horodatage (UTC) : `AAAA-MM-JJ HH:MM:SS.mmm`
+pub fn horodatage_to_string(horodatage: SystemTime) -> String {
+    let duration = horodatage.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secondes = duration.as_secs();
+    let (an, mois, jour) = civil_from_days(secondes / 86_400);
+    format!(
+        "{an:04}-{mois:02}-{jour:02} {:02}:{:02}:{:02}.{:03}",
+        secondes / 3600 % 24,
+        secondes / 60 % 60,
+        secondes % 60,
+        duration.subsec_millis()
+    )
+}
+
+/// Date (an, mois, jour) d'un nombre de jours depuis le 01/01/1970
+/// (algorithme 'civil_from_days' de Howard Hinnant)
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let jour = doy - (153 * mp + 2) / 5 + 1;
+    let mois = if mp < 10 { mp + 3 } else { mp - 9 };
+    let an = yoe + era * 400 + u64::from(mois <= 2);
+    (an, mois, jour)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Provenance;
+    use std::time::Duration;
+
+    #[test]
+    fn test_horodatage_to_string() {
+        assert_eq!(horodatage_to_string(UNIX_EPOCH), "1970-01-01 00:00:00.000");
+        assert_eq!(
+            horodatage_to_string(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)),
+            "2024-02-29 12:34:56.789"
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let mut context = Context::default();
+        assert!(context.get_info_history(IdInfo::DebitInstant).is_empty());
+
+        // Chaque mise à jour est mémorisée, même avec la même valeur
+        for (num_vacation, debit) in [(1, 350.0), (2, 350.0), (3, 420.5)] {
+            context.begin_vacation();
+            context.with_provenance(Provenance::Message(10), |context| {
+                context.set_info_f32(IdInfo::DebitInstant, debit);
+                context.set_info_u32(IdInfo::QuantitePrincipale, num_vacation * 100);
+            });
+        }
+        context.set_info_string(IdInfo::LibelleProduit, "SP95;E10");
+
+        let history = context.get_info_history(IdInfo::DebitInstant);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].t_value, TValue::F32(420.5));
+        assert_eq!(history[2].origine.provenance, Provenance::Message(10));
+        assert_eq!(history[2].origine.num_vacation, 3);
+
+        // Une information qui n'est plus définie garde son historique
+        assert_eq!(
+            context.set_info_from_string(IdInfo::DebitInstant, ""),
+            Ok(())
+        );
+        assert_eq!(context.get_info_history(IdInfo::DebitInstant).len(), 3);
+
+        // Export CSV
+        let csv = context.history_to_csv(&[
+            IdInfo::DebitInstant,
+            IdInfo::QuantitePrincipale,
+            IdInfo::LibelleProduit,
+        ]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + 3 + 3 + 1);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(";1;Réponse message 10;Débit instantané;350.0;m³/h"));
+        assert!(lines[2].ends_with(";1;Réponse message 10;Quantité;100;L"));
+        assert!(lines[7].ends_with(";3;Saisie manuelle;Libellé produit;\"SP95;E10\";"));
+
+        // Historique borné
+        for quantite in 0..u32::try_from(HISTORY_MAX_LEN).unwrap() {
+            context.set_info_u32(IdInfo::QuantitePrincipale, quantite);
+        }
+        let history = context.get_info_history(IdInfo::QuantitePrincipale);
+        assert_eq!(history.len(), HISTORY_MAX_LEN);
+        assert_eq!(history[0].t_value, TValue::U32(0));
+
+        context.clear_all_histories();
+        assert!(context
+            .get_info_history(IdInfo::QuantitePrincipale)
+            .is_empty());
+    }
+}
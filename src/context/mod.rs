@@ -1,15 +1,41 @@
 //! Informations 'atomiques' échangées par le protocole ALMA IE - ST2150
 
-use regex::Regex;
-
+use std::borrow::Cow;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::num::{IntErrorKind, ParseIntError};
+
+use crate::langue::tr_static;
+
+mod date_heure;
+mod derivation;
+mod descripteur;
+mod history;
+mod observer;
+mod origine;
+mod preset;
+mod profil_vehicule;
+mod session;
+mod transaction;
+mod unite;
+mod variante;
+pub use date_heure::{Date, DateHeure, Heure, PIVOT_SIECLE};
+use derivation::DERIVATION_RULES;
+use descripteur::{get_descripteur, get_regex, label_with_index, Descripteur};
+pub use history::{horodatage_to_string, History, HistoryEntry, HISTORY_MAX_LEN};
+use observer::Subscription;
+pub use observer::{InfoChange, Observer, SubscriptionId};
+pub use origine::{Origine, Provenance};
+pub use preset::{presets_to_string, save_presets, Preset, PRESETS_FILE_NAME};
+pub use profil_vehicule::{ProfilVehicule, PROFIL_VEHICULE_FILE_NAME};
+pub use session::{SessionError, LAST_SESSION_FILE_NAME};
+use transaction::InfosAvantTransaction;
+pub use unite::{Grandeur, Unite};
 
 /// Nombre max de produits
 pub const NB_PRODUITS: usize = 16;
 
-/// Nombre de caractères pour un libellé produit
-const LIBELLE_PRODUIT_WIDTH: usize = 10;
-
 /// Nombre max de compartiments
 pub const NB_COMPARTIMENTS: usize = 9;
 
@@ -20,136 +46,343 @@ pub const NB_FLEXIBLES: usize = 3;
 #[allow(clippy::struct_field_names)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FormatInfo {
+    /// Booléen (oui/non)
     Bool,
+
+    /// Un caractère
     Char,
+
+    /// Entier sur 8 bits
     U8,
+
+    /// Entier sur 8 bits ou la lettre `T` (voir `U8OrT`)
     U8OrT,
+
+    /// Entier sur 16 bits
     U16,
+
+    /// Entier sur 32 bits
     U32,
+
+    /// Entier sur 64 bits
     U64,
+
+    /// Nombre décimal
     F32,
+
+    /// Chaîne de caractères (nombre de caractères dans les trames)
     String(usize),
+
+    /// Date (voir `Date`)
+    Date,
+
+    /// Heure sans les secondes
+    HeureHHMM,
+
+    /// Heure avec les secondes
+    HeureHHMMSS,
+
+    /// Date et heure (voir `DateHeure`)
+    DateHeure,
 }
 
 /// Énumération des informations du contexte<br>
 /// Cette énumération permet aux modules externes de désigner une information du contexte.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum IdInfo {
+    /// Acquit (ACK) du message
     Ack,
+
+    /// Refus (NACK) du message
     Nack,
+
+    /// Mesurage en cours
     EnMesurage,
+
+    /// Calculateur en code défaut
     CodeDefaut,
+
+    /// Arrêt intermédiaire du mesurage
     ArretIntermediaire,
+
+    /// Forçage en petit débit
     ForcagePetitDebit,
+
+    /// Mode connecté du calculateur
     ModeConnecte,
+
+    /// Totalisateur (non remis à zéro)
     Totalisateur,
+
+    /// Débit instantané
     DebitInstant,
+
+    /// Quantité mesurée (dans l'unité du type de compteur)
     QuantitePrincipale,
+
+    /// Quantité secondaire (par exemple à 15 °C)
     QuantiteSecondaire,
+
+    /// Température instantanée
     TemperatureInstant,
+
+    /// Température moyenne du mesurage
     TemperatureMoyen,
+
+    /// Quantité prédéterminée
     Predetermination,
+
+    /// Code produit (1 à `NB_PRODUITS`)
     CodeProduit,
+
+    /// Index des mesurages sans remise à zéro
     IndexSansRaz,
+
+    /// Index (numéro d'ordre) du mesurage dans la journée
     IndexJournalier,
+
+    /// Quantième (jour de l'année)
     Quantieme,
+
+    /// Heure de début du mesurage
     HeureHHMMDebut,
+
+    /// Heure de fin du mesurage
     HeureHHMMFin,
+
+    /// Identification TAG
     IdentificationTag,
+
+    /// Référence et immatriculation du calculateur
     ReferenceEtImmatriculation,
+
+    /// Version du logiciel du calculateur
     VersionLogiciel,
+
+    /// Date et heure du calculateur
     DateAAMMJJHeureHHMMSS,
+
+    /// Type de compteur (0 : Vm, 1 : Vb, 2 : Masse)
     TypeCompteur,
+
+    /// Nombre de mesurages pour un quantième
     NbMesuragesQuantieme,
+
+    /// Libellé produit d'un mesurage
     LibelleProduit,
+
+    /// Nombre de fractionnements d'un mesurage
     NbFractionnements,
+
+    /// Libellé d'un produit de la table des produits (numéro du produit)
     LibelleTableProduits(usize),
+
+    /// Libellé du code produit (déduit de la table des produits)
+    LibelleCodeProduit,
+
+    /// Index d'un fractionnement d'un mesurage
     IndexFractionnement,
+
+    /// Type de distribution d'un fractionnement
     TypeDistribution,
+
+    /// Date d'un événement
     DateAAMMJJ,
+
+    /// Heure (HH:MM:SS)
     HeureHHMMSS,
+
+    /// Heure (HH:MM)
     HeureHHMM,
+
+    /// Nombre d'événements du journal
     NbJEvents,
+
+    /// Données techniques d'un événement du journal
     DataJEvent,
+
+    /// Libellé d'un événement du journal
     LibelleJEvent,
+
+    /// Code produit d'un compartiment (numéro du compartiment)
     CodeProduitCompartiment(usize),
+
+    /// Quantité d'un compartiment (numéro du compartiment)
     QuantiteCompartiment(usize),
+
+    /// Quantité totale des compartiments
+    QuantiteTotaleCompartiments,
+
+    /// Nombre de compartiments du véhicule
     NombreCompartiments,
+
+    /// Présence d'une remorque
     PresenceRemorque,
+
+    /// Code produit dans le collecteur
     CodeProduitCollecteur,
+
+    /// Code produit dans la partie commune
     CodeProduitPartieCommune,
+
+    /// Code produit dans le flexible #1
     CodeProduitFlexible1,
+
+    /// Code produit dans le flexible #2
     CodeProduitFlexible2,
+
+    /// Code erreur d'un mouvement de produit (1 : Non supporté, 2 : En opération)
     CodeErreurMouvementProduit,
+
+    /// Code produit final d'un mouvement de produit
     CodeProduitFinal,
+
+    /// Numéro de compartiment (ou `T` pour la remorque)
     NumeroCompartiment,
+
+    /// Numéro de compartiment final (ou `T` pour la remorque)
     NumeroCompartimentFinal,
+
+    /// Ordre des compartiments pour un mouvement multi-compartiments
     OrdreCompartiments,
+
+    /// Numéro de flexible
     NumeroFlexible,
+
+    /// Numéro de flexible final
     NumeroFlexibleFinal,
+
+    /// Finir avec le flexible vide
     FinirFlexibleVide,
 }
 
 /// Container pour une information `U8OrT`: Peut être `U8` (numéro de compartiment) ou la lettre `T` (remorque)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum U8OrT {
+    /// Numéro (de compartiment)
     U8(u8),
+
+    /// Lettre `T` (remorque)
     T,
 }
 
 /// Container des différents types de valeurs possibles pour une information du contexte
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TValue {
+    /// Valeur d'une information `FormatInfo::Bool`
     Bool(bool),
+
+    /// Valeur d'une information `FormatInfo::Char`
     Char(char),
+
+    /// Valeur d'une information `FormatInfo::U8`
     U8(u8),
+
+    /// Valeur d'une information `FormatInfo::U8OrT`
     U8OrT(U8OrT),
+
+    /// Valeur d'une information `FormatInfo::U16`
     U16(u16),
+
+    /// Valeur d'une information `FormatInfo::U32`
     U32(u32),
+
+    /// Valeur d'une information `FormatInfo::U64`
     U64(u64),
+
+    /// Valeur d'une information `FormatInfo::F32`
     F32(f32),
+
+    /// Valeur d'une information `FormatInfo::String`
     String(String),
-}
 
-/// Propriétés associées à chaque information de contexte
-#[derive(Clone, Debug)]
-struct Info {
-    /// Libellé présenté pour l'information
-    label: String,
+    /// Valeur d'une information `FormatInfo::Date`
+    Date(Date),
 
-    /// Format choisi pour l'information.
-    #[allow(clippy::struct_field_names)]
-    format_info: FormatInfo,
+    /// Valeur d'une information `FormatInfo::HeureHHMM`
+    HeureHHMM(Heure),
 
-    /// Valeur de l'information dans le format choisi
-    /// En cohérence avec la propriété `format_info`
-    /// Par défaut, aucune valeur n'est définie (None)
-    option_t_value: Option<TValue>,
+    /// Valeur d'une information `FormatInfo::HeureHHMMSS`
+    HeureHHMMSS(Heure),
 
-    /// Regex optionnelle pour valider la valeur de l'information (from_string)
-    option_regex: Option<Regex>,
+    /// Valeur d'une information `FormatInfo::DateHeure`
+    DateHeure(DateHeure),
+}
 
-    /// Valeur max optionnelle (pour les nombres)
-    option_max_t_value: Option<TValue>,
+impl Display for TValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TValue::Bool(value) => {
+                if *value {
+                    write!(f, "Oui")
+                } else {
+                    write!(f, "Non")
+                }
+            }
+            TValue::Char(value) => write!(f, "{value}"),
+            TValue::U8(value) => write!(f, "{value}"),
+            TValue::U8OrT(u8_or_t) => match u8_or_t {
+                U8OrT::U8(value) => write!(f, "{value}"),
+                U8OrT::T => write!(f, "T"),
+            },
+            TValue::U16(value) => write!(f, "{value}"),
+            TValue::U32(value) => write!(f, "{value}"),
+            TValue::U64(value) => write!(f, "{value}"),
+            TValue::F32(value) => write!(f, "{value:.1}"),
+            TValue::String(value) => write!(f, "{}", value.trim_end()),
+            TValue::Date(value) => write!(f, "{value}"),
+            TValue::HeureHHMM(value) => write!(f, "{}", value.to_string_hhmm()),
+            TValue::HeureHHMMSS(value) => write!(f, "{value}"),
+            TValue::DateHeure(value) => write!(f, "{value}"),
+        }
+    }
 }
 
-impl Default for Info {
-    fn default() -> Self {
-        Self {
-            label: "Non défini".to_string(), // Par défaut, à définir précisément
-            format_info: FormatInfo::Bool,   // Par défaut, à définir précisément
-            option_t_value: None,
-            option_regex: None,
-            option_max_t_value: None,
+impl TValue {
+    /// Comparaison de 2 valeurs de même format (None si les formats sont différents)
+    /// Une valeur `U8OrT::U8` se compare avec une valeur `U8` (cas des valeurs min/max)
+    fn partial_cmp_t_value(&self, other: &TValue) -> Option<Ordering> {
+        match (self, other) {
+            (TValue::Char(a), TValue::Char(b)) => a.partial_cmp(b),
+            (TValue::U8(a) | TValue::U8OrT(U8OrT::U8(a)), TValue::U8(b)) => a.partial_cmp(b),
+            (TValue::U16(a), TValue::U16(b)) => a.partial_cmp(b),
+            (TValue::U32(a), TValue::U32(b)) => a.partial_cmp(b),
+            (TValue::U64(a), TValue::U64(b)) => a.partial_cmp(b),
+            (TValue::F32(a), TValue::F32(b)) => a.partial_cmp(b),
+            (TValue::String(a), TValue::String(b)) => a.partial_cmp(b),
+            _ => None,
         }
     }
 }
 
+/// Valeur, origine et historique d'une information du contexte
+/// Les propriétés invariables de l'information sont dans son descripteur (voir `Descripteur`)
+#[derive(Clone, Debug, Default)]
+struct Info {
+    /// Valeur de l'information dans le format de son descripteur
+    /// Par défaut, aucune valeur n'est définie (None)
+    option_t_value: Option<TValue>,
+
+    /// Origine de la dernière mise à jour de la valeur (None si aucune valeur n'est définie)
+    option_origine: Option<Origine>,
+
+    /// Historique des dernières valeurs de l'information (voir `HISTORY_MAX_LEN`)
+    history: History,
+}
+
+/// Information jamais mise à jour (sans valeur ni historique)
+static INFO_VIDE: Info = Info {
+    option_t_value: None,
+    option_origine: None,
+    history: History::new(),
+};
+
 /// Container pour toutes les informations du contexte<br>
-/// C'est l'implémentation de `Default` qui créé toutes les informations
-/// atomiques du contexte
+/// Les informations sont décrites par la table `DESCRIPTEURS` et leurs valeurs ne sont
+/// mémorisées qu'à leur première mise à jour
+#[derive(Clone, Default)]
 pub struct Context {
-    /// Table des informations du contexte `IdInfo` -> `Info`
+    /// Table des informations mises à jour du contexte `IdInfo` -> `Info`
     hash_id_infos: HashMap<IdInfo, Info>,
 
     /// Liste des `IdInfo` en cours de traitement par la fonction de `callback` invoquée
@@ -158,488 +391,78 @@ pub struct Context {
     /// informations sont traitées par ce `callback`.
     /// Typiquement `HeureHHM` -> `HeureHHMMSS` -> `HeureHHM` -> `HeureHHMMSS`...
     set_id_infos_on_change: HashSet<IdInfo>,
-}
 
-impl Default for Context {
-    #[allow(clippy::too_many_lines)]
-    fn default() -> Self {
-        let mut hash_id_infos: HashMap<_, _> = HashMap::new();
-
-        // Construction du contexte par défaut
-        hash_id_infos.insert(
-            IdInfo::Ack,
-            Info {
-                label: "Acquit message".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::Nack,
-            Info {
-                label: "Refus message".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::EnMesurage,
-            Info {
-                label: "En mesurage".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeDefaut,
-            Info {
-                label: "En Code défaut".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::ArretIntermediaire,
-            Info {
-                label: "Arrêt intermédiaire".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::ForcagePetitDebit,
-            Info {
-                label: "Forçage petit débit".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::ModeConnecte,
-            Info {
-                label: "Mode connecté".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::Totalisateur,
-            Info {
-                label: "Totalisateur".to_string(),
-                format_info: FormatInfo::U32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::DebitInstant,
-            Info {
-                label: "Débit instantané".to_string(),
-                format_info: FormatInfo::F32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::QuantitePrincipale,
-            Info {
-                label: "Quantité".to_string(),
-                format_info: FormatInfo::U32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::QuantiteSecondaire,
-            Info {
-                label: "Quantité secondaire".to_string(),
-                format_info: FormatInfo::U32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::TemperatureInstant,
-            Info {
-                label: "Quantité Température instantanée".to_string(),
-                format_info: FormatInfo::F32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::TemperatureMoyen,
-            Info {
-                label: "Température moyenne".to_string(),
-                format_info: FormatInfo::F32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::Predetermination,
-            Info {
-                label: "Prédétermination".to_string(),
-                format_info: FormatInfo::U32,
-                option_max_t_value: Some(TValue::U32(99999)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduit,
-            Info {
-                label: "Code produit".to_string(),
-                format_info: FormatInfo::U8,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_PRODUITS).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::IndexSansRaz,
-            Info {
-                label: "Index sans remise à zéro".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::IndexJournalier,
-            Info {
-                label: "Index journalier".to_string(),
-                format_info: FormatInfo::U16,
-                option_max_t_value: Some(TValue::U16(999)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::Quantieme,
-            Info {
-                label: "Quantième".to_string(),
-                format_info: FormatInfo::U16,
-                option_max_t_value: Some(TValue::U16(366)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::HeureHHMMDebut,
-            Info {
-                label: "Heure de début (HHMM)".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::HeureHHMMFin,
-            Info {
-                label: "Heure de fin (HHMM)".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::IdentificationTag,
-            Info {
-                label: "Identification TAG".to_string(),
-                format_info: FormatInfo::String(100),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::ReferenceEtImmatriculation,
-            Info {
-                label: "Référence et immatriculation".to_string(),
-                format_info: FormatInfo::String(15),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::VersionLogiciel,
-            Info {
-                label: "Version du logiciel".to_string(),
-                format_info: FormatInfo::String(10),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::DateAAMMJJHeureHHMMSS,
-            Info {
-                label: "Date et Heure (AAMMJJHHMMSS)".to_string(),
-                format_info: FormatInfo::U64,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::TypeCompteur,
-            Info {
-                label: "Type de compteur (0:Vm, 1:Vb, 2:Masse)".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NbMesuragesQuantieme,
-            Info {
-                label: "Nombre de mesurages pour un quantième".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::LibelleProduit,
-            Info {
-                label: "Libellé produit".to_string(),
-                format_info: FormatInfo::String(LIBELLE_PRODUIT_WIDTH),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NbFractionnements,
-            Info {
-                label: "Nombre de fractionnements".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        for prod_num in 0..=NB_PRODUITS {
-            hash_id_infos.insert(
-                IdInfo::LibelleTableProduits(prod_num),
-                Info {
-                    label: format!("Libellé table produit #{prod_num}"),
-                    format_info: FormatInfo::String(LIBELLE_PRODUIT_WIDTH),
-                    ..Default::default()
-                },
-            );
-        }
-        hash_id_infos.insert(
-            IdInfo::IndexFractionnement,
-            Info {
-                label: "Index fractionnement".to_string(),
-                format_info: FormatInfo::U16,
-                option_max_t_value: Some(TValue::U16(999)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::TypeDistribution,
-            Info {
-                label: "(A)nticipation purge, li(B)ération, (C)hargement, pré(D)é, (L)ibre, (P)urge, (T)ransfert, (V)idange".to_string(),
-                format_info: FormatInfo::Char,
-                ..Default::default()
+    /// Provenance des mises à jour en cours (voir `with_provenance`)
+    provenance: Provenance,
 
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::DateAAMMJJ,
-            Info {
-                label: "Date (AAMMJJ)".to_string(),
-                format_info: FormatInfo::U32,
-                option_max_t_value: Some(TValue::U32(99_12_31)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::HeureHHMMSS,
-            Info {
-                label: "Heure (HHMMSS)".to_string(),
-                format_info: FormatInfo::U32,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::HeureHHMM,
-            Info {
-                label: "Heure (HHMM)".to_string(),
-                format_info: FormatInfo::U16,
-                option_max_t_value: Some(TValue::U16(2359)),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NbJEvents,
-            Info {
-                label: "Nombre d'événements".to_string(),
-                format_info: FormatInfo::U16,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::DataJEvent,
-            Info {
-                label: "Données techniques événement".to_string(),
-                format_info: FormatInfo::String(12),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::LibelleJEvent,
-            Info {
-                label: "Libellé événement".to_string(),
-                format_info: FormatInfo::String(40),
-                ..Default::default()
-            },
-        );
-        for compart_num in 0..=NB_COMPARTIMENTS {
-            hash_id_infos.insert(
-                IdInfo::CodeProduitCompartiment(compart_num),
-                Info {
-                    label: format!("Code produit cpt #{compart_num}"),
-                    format_info: FormatInfo::U8,
-                    option_max_t_value: Some(TValue::U8(u8::try_from(NB_PRODUITS).unwrap())),
-                    ..Default::default()
-                },
-            );
-            hash_id_infos.insert(
-                IdInfo::QuantiteCompartiment(compart_num),
-                Info {
-                    label: format!("Quantité cpt #{compart_num}"),
-                    format_info: FormatInfo::U32,
-                    option_max_t_value: Some(TValue::U32(99999)),
-                    ..Default::default()
-                },
-            );
-        }
-        hash_id_infos.insert(
-            IdInfo::NombreCompartiments,
-            Info {
-                label: "Nombre de compartiments".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduitCollecteur,
-            Info {
-                label: "Code produit dans le collecteur".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduitPartieCommune,
-            Info {
-                label: "Code produit dans la partie commune".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduitFlexible1,
-            Info {
-                label: "Code produit dans le flexible #1".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduitFlexible2,
-            Info {
-                label: "Code produit dans le flexible #2".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeErreurMouvementProduit,
-            Info {
-                label: "Code erreur (1:Non supporté, 2:En opération)".to_string(),
-                format_info: FormatInfo::U8,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::CodeProduitFinal,
-            Info {
-                label: "Code produit final".to_string(),
-                format_info: FormatInfo::U8,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_PRODUITS).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NumeroCompartiment,
-            Info {
-                label: "No de compartiment (ou T pour remorque)".to_string(),
-                format_info: FormatInfo::U8OrT,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_COMPARTIMENTS).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::PresenceRemorque,
-            Info {
-                label: "Présence d'une remorque".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NumeroCompartimentFinal,
-            Info {
-                label: "No de compartiment final (ou T)".to_string(),
-                format_info: FormatInfo::U8OrT,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_COMPARTIMENTS).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::OrdreCompartiments,
-            Info {
-                label: "Ordre des compartiments".to_string(),
-                format_info: FormatInfo::String(9),
-                option_regex: Some(Regex::new(r"^\d{1,9}$").unwrap()),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NumeroFlexible,
-            Info {
-                label: "Numéro de flexible".to_string(),
-                format_info: FormatInfo::U8,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_FLEXIBLES).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::NumeroFlexibleFinal,
-            Info {
-                label: "Numéro de flexible final".to_string(),
-                format_info: FormatInfo::U8,
-                option_max_t_value: Some(TValue::U8(u8::try_from(NB_FLEXIBLES).unwrap())),
-                ..Default::default()
-            },
-        );
-        hash_id_infos.insert(
-            IdInfo::FinirFlexibleVide,
-            Info {
-                label: "Finir flexible vide".to_string(),
-                format_info: FormatInfo::Bool,
-                ..Default::default()
-            },
-        );
+    /// Numéro de la dernière vacation (voir `begin_vacation`)
+    num_vacation: u32,
 
-        Self {
-            hash_id_infos,
-            set_id_infos_on_change: HashSet::new(),
-        }
-    }
+    /// Numéro de la dernière mise à jour d'une information (voir `Origine::num_mise_a_jour`)
+    num_mise_a_jour: u64,
+
+    /// Abonnements aux changements du contexte (voir `subscribe`)
+    subscriptions: Vec<Subscription>,
+
+    /// Identifiant du dernier abonnement
+    last_subscription_id: usize,
+
+    /// Changements à notifier à la fin d'une mise à jour 'atomique' en cours
+    /// (None si aucune mise à jour 'atomique' n'est en cours)
+    option_deferred_changes: Option<Vec<InfoChange>>,
+
+    /// États des informations avant leur modification par la mise à jour 'atomique' en cours
+    /// (None si aucune mise à jour 'atomique' n'est en cours, voir `transaction`)
+    option_infos_avant_transaction: Option<InfosAvantTransaction>,
+
+    /// Profil du véhicule (voir `set_profil_vehicule`)
+    profil_vehicule: ProfilVehicule,
+
+    /// Message courant pour le format et le libellé des informations
+    /// (voir `set_message_courant`)
+    option_message_courant: Option<u8>,
 }
 
 impl Context {
     /* ----------- */
-    /** GÉNÉRIQUE **/
+    /*  GÉNÉRIQUE  */
     /* ----------- */
 
-    /// Getter générique (non mutable) d'une information du contexte (tout format)
+    /// Descripteur d'une information du contexte
     /// # panics
     /// panic! si l'`IdInfo` n'est pas reconnu
-    fn get_inner_info(&self, id_info: IdInfo) -> &Info {
-        match self.hash_id_infos.get(&id_info) {
-            Some(inner_info) => inner_info,
+    fn get_descripteur(id_info: IdInfo) -> &'static Descripteur {
+        match get_descripteur(id_info) {
+            Some(descripteur) => descripteur,
             None => panic!("IdInfo {id_info:?} inconnue"),
         }
     }
 
+    /// Getter générique (non mutable) d'une information du contexte (tout format)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    fn get_inner_info(&self, id_info: IdInfo) -> &Info {
+        Self::get_descripteur(id_info);
+        self.hash_id_infos.get(&id_info).unwrap_or(&INFO_VIDE)
+    }
+
     /// Getter générique (mutable) d'une information du contexte (tout format)
+    /// L'information est créée à son premier accès mutable
     /// # panics
     /// panic! si l'`IdInfo` n'est pas reconnu
     fn get_mut_inner_info(&mut self, id_info: IdInfo) -> &mut Info {
-        match self.hash_id_infos.get_mut(&id_info) {
-            Some(inner_info) => inner_info,
-            None => panic!("IdInfo {id_info:?} inconnue"),
+        Self::get_descripteur(id_info);
+        self.save_info_avant_transaction(id_info);
+        self.hash_id_infos.entry(id_info).or_default()
+    }
+
+    /// Contrôle du format d'une information du contexte
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    /// panic! si l'`IdInfo` n'est pas du format attendu
+    fn check_info_format(id_info: IdInfo, format_info: FormatInfo) {
+        if Self::get_descripteur(id_info).format_info != format_info {
+            panic!("IdInfo {id_info:?} n'est pas {format_info:?}")
         }
     }
 
@@ -648,16 +471,8 @@ impl Context {
     /// panic! si l'`IdInfo` n'est pas reconnu
     /// panic! si l'`IdInfo` n'est pas du format attendu
     fn get_inner_info_with_format(&self, id_info: IdInfo, format_info: FormatInfo) -> &Info {
-        match self.hash_id_infos.get(&id_info) {
-            Some(inner_info) => {
-                if inner_info.format_info == format_info {
-                    inner_info
-                } else {
-                    panic!("IdInfo {id_info:?} n'est pas {format_info:?}")
-                }
-            }
-            None => panic!("IdInfo {id_info:?} inconnue"),
-        }
+        Self::check_info_format(id_info, format_info);
+        self.get_inner_info(id_info)
     }
 
     /// Getter générique (mutable) d'une information du contexte d'un format spécifique
@@ -669,75 +484,166 @@ impl Context {
         id_info: IdInfo,
         format_info: FormatInfo,
     ) -> &mut Info {
-        match self.hash_id_infos.get_mut(&id_info) {
-            Some(inner_info) => {
-                if inner_info.format_info == format_info {
-                    inner_info
-                } else {
-                    panic!("IdInfo {id_info:?} n'est pas {format_info:?}")
-                }
-            }
-            None => panic!("IdInfo {id_info:?} inconnue"),
-        }
+        Self::check_info_format(id_info, format_info);
+        self.get_mut_inner_info(id_info)
     }
 
-    /// Libellé d'une information du contexte
+    /// Libellé d'une information du contexte (dans la langue courante de l'outil)
+    /// Le libellé peut être spécifique au message courant (voir `get_message_courant`)
     /// # panics
     /// panic! si l'`IdInfo` n'est pas reconnu
-    pub fn get_info_label(&self, id_info: IdInfo) -> String {
-        let inner_info = self.get_inner_info(id_info);
-        inner_info.label.clone()
+    pub fn get_info_label(&self, id_info: IdInfo) -> Cow<'static, str> {
+        let label = match self.get_variante_info(id_info) {
+            Some(variante) => variante.label,
+            None => Self::get_descripteur(id_info).label,
+        };
+        label_with_index(tr_static(label), id_info)
     }
 
     /// Format d'une information du contexte
+    /// Le format peut être spécifique au message courant (voir `get_message_courant`)
     /// # panics
     /// panic! si l'`IdInfo` n'est pas reconnu
     pub fn get_info_format(&self, id_info: IdInfo) -> FormatInfo {
-        let inner_info = self.get_inner_info(id_info);
-        inner_info.format_info
+        match self.get_variante_info(id_info) {
+            Some(variante) => variante.format_info,
+            None => Self::get_descripteur(id_info).format_info,
+        }
+    }
+
+    /// Valeur max. d'une information du contexte (None si aucune)
+    /// La valeur max. peut dépendre du profil du véhicule (voir `set_profil_vehicule`)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    fn get_info_max_t_value(&self, id_info: IdInfo) -> Option<TValue> {
+        match self.profil_vehicule.get_option_max(id_info) {
+            Some(max) => Some(TValue::U8(max)),
+            None => Self::get_descripteur(id_info).option_max_t_value.clone(),
+        }
+    }
+
+    /// Getter générique de la valeur d'une information du contexte (tout format)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn get_option_info_t_value(&self, id_info: IdInfo) -> Option<TValue> {
+        self.get_inner_info(id_info).option_t_value.clone()
+    }
+
+    /// Contrôle du domaine de valeurs d'une information du contexte (valeurs min/max)
+    /// Les dates et les heures sont toujours valides (voir `Date`, `Heure` et `DateHeure`)
+    /// # Errors
+    /// Libellé du domaine de valeurs de l'information si la valeur n'est pas dans ce domaine
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn check_info_domain(&self, id_info: IdInfo, t_value: &TValue) -> Result<(), String> {
+        let option_min_t_value = &Self::get_descripteur(id_info).option_min_t_value;
+        let option_max_t_value = &self.get_info_max_t_value(id_info);
+
+        // Valeurs min/max
+        let is_lower = |option_t_value: &Option<TValue>| {
+            option_t_value
+                .as_ref()
+                .is_some_and(|limit| t_value.partial_cmp_t_value(limit) == Some(Ordering::Less))
+        };
+        let is_greater = |option_t_value: &Option<TValue>| {
+            option_t_value
+                .as_ref()
+                .is_some_and(|limit| t_value.partial_cmp_t_value(limit) == Some(Ordering::Greater))
+        };
+        if is_lower(option_min_t_value) || is_greater(option_max_t_value) {
+            return Err(match (option_min_t_value, option_max_t_value) {
+                (Some(min), Some(max)) => format!("Valeur entre {min} et {max}"),
+                (Some(min), None) => format!("Valeur min. {min}"),
+                (None, Some(max)) => format!("Valeur max. {max}"),
+                (None, None) => unreachable!(),
+            });
+        }
+
+        Ok(())
     }
 
-    /// Action en callback pour une information du contexte
+    /// Remet des informations du contexte à `None` (non définies)
+    /// Les informations déduites de ces informations (voir `DERIVATION_RULES`) sont recalculées,
+    /// ou remises à `None` si elles ne peuvent plus être calculées (voir
+    /// `do_callback_info_on_change`)
+    /// # panics
+    /// panic! si un `IdInfo` n'est pas reconnu
+    pub fn clear_infos(&mut self, id_infos: &[IdInfo]) {
+        for id_info in id_infos {
+            let inner_info = self.get_mut_inner_info(*id_info);
+            let old_t_value = inner_info.option_t_value.take();
+            self.callback_info_on_change(*id_info, old_t_value);
+        }
+    }
+
+    /// Setter générique d'une information du contexte (selon le format de la valeur)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    /// panic! si l'`IdInfo` n'est pas du format de la valeur
+    pub fn set_info_t_value(&mut self, id_info: IdInfo, t_value: TValue) {
+        match t_value {
+            TValue::Bool(value) => self.set_info_bool(id_info, value),
+            TValue::Char(value) => self.set_info_char(id_info, value),
+            TValue::U8(value) => self.set_info_u8(id_info, value),
+            TValue::U8OrT(value) => self.set_info_u8_or_t(id_info, value),
+            TValue::U16(value) => self.set_info_u16(id_info, value),
+            TValue::U32(value) => self.set_info_u32(id_info, value),
+            TValue::U64(value) => self.set_info_u64(id_info, value),
+            TValue::F32(value) => self.set_info_f32(id_info, value),
+            TValue::String(value) => self.set_info_string(id_info, &value),
+            TValue::Date(value) => self.set_info_date(id_info, value),
+            TValue::HeureHHMM(value) => self.set_info_heure_hhmm(id_info, value),
+            TValue::HeureHHMMSS(value) => self.set_info_heure_hhmmss(id_info, value),
+            TValue::DateHeure(value) => self.set_info_date_heure(id_info, value),
+        }
+    }
+
+    /// Action en callback pour une information du contexte : Application de toutes les règles
+    /// de déduction dont cette information est une source (voir `DERIVATION_RULES`)
+    /// Une information déduite qui ne peut plus être calculée est remise à `None`
     /// L'accès à cette fonction est protégé contre une ré-entrance récursive
-    fn do_callback_info_on_change(&mut self, id_info: IdInfo, t_value: &TValue) {
-        match id_info {
-            // HeureHHMMSS => HeureHHMM
-            IdInfo::HeureHHMMSS => {
-                if let TValue::U32(heure_minute_seconde) = t_value {
-                    let heure_minute = u16::try_from(*heure_minute_seconde / 100).unwrap();
-                    self.set_info_u16(IdInfo::HeureHHMM, heure_minute);
-                }
-            }
-            // DateAAMMJJHeureHHMMSS -> DateAAMMJJ, HeureHHMMSS -> HeureHHMM
-            IdInfo::DateAAMMJJHeureHHMMSS => {
-                if let TValue::U64(an_mois_jour_heure_minute_seconde) = t_value {
-                    let an_mois_jour = an_mois_jour_heure_minute_seconde / 1_00_00_00;
-                    let heure_minute_seconde = an_mois_jour_heure_minute_seconde % 1_00_00_00;
-                    let an_mois_jour = u32::try_from(an_mois_jour).unwrap();
-                    let heure_minute_seconde = u32::try_from(heure_minute_seconde).unwrap();
-                    self.set_info_u32(IdInfo::DateAAMMJJ, an_mois_jour);
-                    self.set_info_u32(IdInfo::HeureHHMMSS, heure_minute_seconde);
+    fn do_callback_info_on_change(&mut self, id_info: IdInfo) {
+        for rule in DERIVATION_RULES
+            .iter()
+            .filter(|rule| (rule.is_source)(id_info))
+        {
+            for (id_info_derivee, option_t_value) in (rule.derive)(self) {
+                match option_t_value {
+                    Some(t_value) => self.set_info_t_value(id_info_derivee, t_value),
+                    // Information déduite qui ne peut plus être calculée
+                    None if self
+                        .get_inner_info(id_info_derivee)
+                        .option_t_value
+                        .is_some() =>
+                    {
+                        self.clear_infos(&[id_info_derivee]);
+                    }
+                    None => (),
                 }
             }
-
-            // Pas d'action en callback pour toutes les autres informations
-            _ => (),
         }
     }
 
     /// Callback lors d'une mise à jour d'une information du contexte
-    fn callback_info_on_change(&mut self, id_info: IdInfo, t_value: &TValue) {
+    /// (mémorise également l'origine et l'historique de cette mise à jour et notifie les abonnés)
+    fn callback_info_on_change(&mut self, id_info: IdInfo, old_t_value: Option<TValue>) {
+        let origine = self.new_origine();
+        let inner_info = self.get_mut_inner_info(id_info);
+        inner_info.option_origine = inner_info.option_t_value.as_ref().map(|_| origine);
+        self.push_info_history(id_info);
+        self.notify_info_change(id_info, old_t_value);
+
         // Protection contre ré-entrance par récursion
         if self.set_id_infos_on_change.contains(&id_info) {
             return;
         }
         self.set_id_infos_on_change.insert(id_info);
-        self.do_callback_info_on_change(id_info, t_value);
+        self.do_callback_info_on_change(id_info);
         self.set_id_infos_on_change.remove(&id_info);
     }
 
     /* --------------------- */
-    /** STRING INPUT/OUTPUT **/
+    /*  STRING INPUT/OUTPUT  */
     /* --------------------- */
 
     /// Getter de la représentation 'textuelle' d'une information du contexte
@@ -745,101 +651,80 @@ impl Context {
         let inner_info = self.get_inner_info(id_info);
         match &inner_info.option_t_value {
             None => output_none.to_string(),
-            Some(t_value) => match t_value {
-                TValue::Bool(value) => {
-                    if *value {
-                        "Oui".to_string()
-                    } else {
-                        "Non".to_string()
-                    }
-                }
-                TValue::Char(value) => format!("{value}"),
-                TValue::U8(value) => format!("{value}"),
-                TValue::U8OrT(u8_or_t) => match u8_or_t {
-                    U8OrT::U8(value) => format!("{value}"),
-                    U8OrT::T => "T".to_string(),
-                },
-                TValue::U16(value) => format!("{value}"),
-                TValue::U32(value) => format!("{value}"),
-                TValue::U64(value) => format!("{value}"),
-                TValue::F32(value) => format!("{value:.1}"),
-                TValue::String(value) => value.trim_end().to_string(),
-            },
+            Some(t_value) => self.t_value_to_string(id_info, t_value),
         }
     }
 
     /// Setter d'une information du contexte depuis une représentation 'textuelle'
     /// Une `input.is_empty()` ré-initialise l'information à `None` (non définie)
-    pub fn set_info_from_string(&mut self, id_info: IdInfo, input: &str) {
-        let inner_info = self.get_mut_inner_info(id_info);
+    /// Un nombre peut être suivi de son unité (voir `get_info_unite`)
+    /// # Errors
+    /// Raison du refus si l'input ne respecte pas la regex, l'unité, le format ou le domaine de
+    /// valeurs de l'information (l'information n'est alors pas modifiée)
+    pub fn set_info_from_string(&mut self, id_info: IdInfo, input: &str) -> Result<(), String> {
         if input.is_empty() {
-            inner_info.option_t_value = None;
-        } else {
-            // L'input n'est pas vide
-            if let Some(regex) = &inner_info.option_regex {
-                // Une Regex est associée à l'information
-                if !regex.is_match(input) {
-                    return; // Ne matche pas la Regex, on ne fait rien
-                }
+            let inner_info = self.get_mut_inner_info(id_info);
+            let old_t_value = inner_info.option_t_value.take();
+            self.callback_info_on_change(id_info, old_t_value);
+            return Ok(());
+        }
+        let input = self.strip_unite(id_info, input)?;
+        let input = input.as_str();
+        let format_info = self.get_info_format(id_info);
+
+        // L'input n'est pas vide
+        if let Some(pattern) = Self::get_descripteur(id_info).option_regex {
+            // Une Regex est associée à l'information
+            if !get_regex(pattern).is_match(input) {
+                return Err("Format incorrect".to_string());
             }
-            match inner_info.format_info {
-                FormatInfo::Bool => {
-                    let value = ['o', 'O', '1'].contains(&input.chars().next().unwrap());
-                    self.set_info_bool(id_info, value);
-                }
-                FormatInfo::Char => {
-                    let value = input.chars().next().unwrap();
-                    self.set_info_char(id_info, value);
-                }
-                FormatInfo::U8 => {
-                    if let Ok(value) = input.parse::<u8>() {
-                        self.set_info_u8(id_info, value);
-                    }
-                }
-                FormatInfo::U8OrT => {
-                    if let Ok(value) = input.parse::<u8>() {
-                        self.set_info_u8_or_t(id_info, U8OrT::U8(value));
-                    } else if ["T", "t"].contains(&input) {
-                        self.set_info_u8_or_t(id_info, U8OrT::T);
-                    }
-                }
-                FormatInfo::U16 => {
-                    if let Ok(value) = input.parse::<u16>() {
-                        self.set_info_u16(id_info, value);
-                    }
-                }
-                FormatInfo::U32 => {
-                    if let Ok(value) = input.parse::<u32>() {
-                        self.set_info_u32(id_info, value);
-                    }
-                }
-                FormatInfo::U64 => {
-                    if let Ok(value) = input.parse::<u64>() {
-                        self.set_info_u64(id_info, value);
-                    }
-                }
-                FormatInfo::F32 => {
-                    if let Ok(value) = input.parse::<f32>() {
-                        self.set_info_f32(id_info, value);
-                    }
+        }
+        let t_value = match format_info {
+            FormatInfo::Bool => match input.chars().next() {
+                Some('o' | 'O' | 'y' | 'Y' | '1') => TValue::Bool(true),
+                Some('n' | 'N' | '0') => TValue::Bool(false),
+                _ => return Err("'Oui' ou 'Non' attendu".to_string()),
+            },
+            FormatInfo::Char => {
+                let mut chars = input.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(value), None) => TValue::Char(value),
+                    _ => return Err("Un seul caractère attendu".to_string()),
                 }
-                FormatInfo::String(width) => {
-                    let input = input.trim_end();
-                    let value = if input.len() > width {
-                        // Tronque si trop long
-                        // /!\ format! ne le fait pas...
-                        input[..width].to_string()
-                    } else {
-                        input.to_string()
-                    };
-                    self.set_info_string(id_info, &value);
+            }
+            FormatInfo::U8 => TValue::U8(parse_integer(input)?),
+            FormatInfo::U8OrT => {
+                if ["T", "t"].contains(&input) {
+                    TValue::U8OrT(U8OrT::T)
+                } else {
+                    TValue::U8OrT(U8OrT::U8(
+                        parse_integer(input).map_err(|_| "Numéro ou 'T' attendu".to_string())?,
+                    ))
                 }
             }
-        }
+            FormatInfo::U16 => TValue::U16(parse_integer(input)?),
+            FormatInfo::U32 => TValue::U32(parse_integer(input)?),
+            FormatInfo::U64 => TValue::U64(parse_integer(input)?),
+            FormatInfo::F32 => match input.parse::<f32>() {
+                Ok(value) if value.is_finite() => TValue::F32(value),
+                _ => return Err("Nombre attendu".to_string()),
+            },
+            // Conservé en entier : tronqué seulement dans les trames et pour l'affichage
+            // (voir `get_info_string_width`)
+            FormatInfo::String(_) => TValue::String(input.trim_end().to_string()),
+            FormatInfo::Date => TValue::Date(input.parse()?),
+            FormatInfo::HeureHHMM => TValue::HeureHHMM(input.parse::<Heure>()?.without_seconde()),
+            FormatInfo::HeureHHMMSS => TValue::HeureHHMMSS(input.parse()?),
+            FormatInfo::DateHeure => TValue::DateHeure(input.parse()?),
+        };
+
+        self.check_info_domain(id_info, &t_value)?;
+        self.set_info_t_value(id_info, t_value);
+        Ok(())
     }
 
     /* ------ */
-    /** BOOL **/
+    /*  BOOL  */
     /* ------ */
 
     /// Getter d'une information de type `bool`
@@ -857,13 +742,12 @@ impl Context {
     /// Setter d'une information de type `bool`
     pub fn set_info_bool(&mut self, id_info: IdInfo, value: bool) {
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::Bool);
-        let t_value = TValue::Bool(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::Bool(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* -------*/
-    /** CHAR **/
+    /*  CHAR  */
     /* -------*/
 
     /// Getter d'une information de type `char`
@@ -880,19 +764,19 @@ impl Context {
 
     /// Setter d'une information de type `char`
     pub fn set_info_char(&mut self, id_info: IdInfo, value: char) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::Char);
-        if let Some(TValue::Char(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::Char(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::Char(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::Char(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* -----*/
-    /** U8 **/
+    /*  U8  */
     /* -----*/
 
     /// Getter d'une information de type `u8`
@@ -909,19 +793,19 @@ impl Context {
 
     /// Setter d'une information de type `u8`
     pub fn set_info_u8(&mut self, id_info: IdInfo, value: u8) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::U8);
-        if let Some(TValue::U8(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::U8(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::U8(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::U8(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ----------*/
-    /** `U8OrT` **/
+    /*  `U8OrT`  */
     /* ----------*/
 
     /// Getter d'une information de type `u8_or_t`
@@ -938,21 +822,21 @@ impl Context {
 
     /// Setter d'une information de type `u8_or_t`
     pub fn set_info_u8_or_t(&mut self, id_info: IdInfo, value: U8OrT) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::U8OrT);
-        if let Some(TValue::U8(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::U8(max_value)) = option_max_t_value {
             if let U8OrT::U8(value_u8) = value {
                 if value_u8 > max_value {
                     return;
                 }
             }
         }
-        let t_value = TValue::U8OrT(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::U8OrT(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ------*/
-    /** U16 **/
+    /*  U16  */
     /* ------*/
 
     /// Getter d'une information de type `u16`
@@ -969,19 +853,19 @@ impl Context {
 
     /// Setter d'une information de type `u16`
     pub fn set_info_u16(&mut self, id_info: IdInfo, value: u16) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::U16);
-        if let Some(TValue::U16(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::U16(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::U16(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::U16(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ------*/
-    /** U32 **/
+    /*  U32  */
     /* ------*/
 
     /// Getter d'une information de type `u32`
@@ -998,19 +882,19 @@ impl Context {
 
     /// Setter d'une information de type `u32`
     pub fn set_info_u32(&mut self, id_info: IdInfo, value: u32) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::U32);
-        if let Some(TValue::U32(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::U32(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::U32(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::U32(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ------*/
-    /** U64 **/
+    /*  U64  */
     /* ------*/
 
     /// Getter d'une information de type `u64`
@@ -1027,19 +911,19 @@ impl Context {
 
     /// Setter d'une information de type `u64`
     pub fn set_info_u64(&mut self, id_info: IdInfo, value: u64) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::U64);
-        if let Some(TValue::U64(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::U64(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::U64(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::U64(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ------*/
-    /** F32 **/
+    /*  F32  */
     /* ------*/
 
     /// Getter d'une information de type `f32`
@@ -1056,19 +940,115 @@ impl Context {
 
     /// Setter d'une information de type `f32`
     pub fn set_info_f32(&mut self, id_info: IdInfo, value: f32) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::F32);
-        if let Some(TValue::F32(max_value)) = inner_info.option_max_t_value {
+        if let Some(TValue::F32(max_value)) = option_max_t_value {
             if value > max_value {
                 return;
             }
         }
-        let t_value = TValue::F32(value);
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info.option_t_value.replace(TValue::F32(value));
+        self.callback_info_on_change(id_info, old_t_value);
+    }
+
+    /* -------*/
+    /*  DATE  */
+    /* -------*/
+
+    /// Getter d'une information de type `Date`
+    pub fn get_option_info_date(&self, id_info: IdInfo) -> Option<Date> {
+        let inner_info = self.get_inner_info_with_format(id_info, FormatInfo::Date);
+        match &inner_info.option_t_value {
+            None => None,
+            Some(t_value) => match t_value {
+                TValue::Date(value) => Some(*value),
+                _ => panic!("{id_info:?} n'est pas une Date"),
+            },
+        }
+    }
+
+    /// Setter d'une information de type `Date`
+    pub fn set_info_date(&mut self, id_info: IdInfo, value: Date) {
+        let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::Date);
+        let old_t_value = inner_info.option_t_value.replace(TValue::Date(value));
+        self.callback_info_on_change(id_info, old_t_value);
+    }
+
+    /* ------------*/
+    /*  HEURE HHMM */
+    /* ------------*/
+
+    /// Getter d'une information de type `Heure` à la minute près
+    pub fn get_option_info_heure_hhmm(&self, id_info: IdInfo) -> Option<Heure> {
+        let inner_info = self.get_inner_info_with_format(id_info, FormatInfo::HeureHHMM);
+        match &inner_info.option_t_value {
+            None => None,
+            Some(t_value) => match t_value {
+                TValue::HeureHHMM(value) => Some(*value),
+                _ => panic!("{id_info:?} n'est pas une Heure HHMM"),
+            },
+        }
+    }
+
+    /// Setter d'une information de type `Heure` à la minute près (les secondes sont ignorées)
+    pub fn set_info_heure_hhmm(&mut self, id_info: IdInfo, value: Heure) {
+        let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::HeureHHMM);
+        let old_t_value = inner_info
+            .option_t_value
+            .replace(TValue::HeureHHMM(value.without_seconde()));
+        self.callback_info_on_change(id_info, old_t_value);
+    }
+
+    /* --------------*/
+    /*  HEURE HHMMSS */
+    /* --------------*/
+
+    /// Getter d'une information de type `Heure` à la seconde près
+    pub fn get_option_info_heure_hhmmss(&self, id_info: IdInfo) -> Option<Heure> {
+        let inner_info = self.get_inner_info_with_format(id_info, FormatInfo::HeureHHMMSS);
+        match &inner_info.option_t_value {
+            None => None,
+            Some(t_value) => match t_value {
+                TValue::HeureHHMMSS(value) => Some(*value),
+                _ => panic!("{id_info:?} n'est pas une Heure HHMMSS"),
+            },
+        }
+    }
+
+    /// Setter d'une information de type `Heure` à la seconde près
+    pub fn set_info_heure_hhmmss(&mut self, id_info: IdInfo, value: Heure) {
+        let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::HeureHHMMSS);
+        let old_t_value = inner_info
+            .option_t_value
+            .replace(TValue::HeureHHMMSS(value));
+        self.callback_info_on_change(id_info, old_t_value);
+    }
+
+    /* ------------*/
+    /*  DATE HEURE */
+    /* ------------*/
+
+    /// Getter d'une information de type `DateHeure`
+    pub fn get_option_info_date_heure(&self, id_info: IdInfo) -> Option<DateHeure> {
+        let inner_info = self.get_inner_info_with_format(id_info, FormatInfo::DateHeure);
+        match &inner_info.option_t_value {
+            None => None,
+            Some(t_value) => match t_value {
+                TValue::DateHeure(value) => Some(*value),
+                _ => panic!("{id_info:?} n'est pas une DateHeure"),
+            },
+        }
+    }
+
+    /// Setter d'une information de type `DateHeure`
+    pub fn set_info_date_heure(&mut self, id_info: IdInfo, value: DateHeure) {
+        let inner_info = self.get_mut_inner_info_with_format(id_info, FormatInfo::DateHeure);
+        let old_t_value = inner_info.option_t_value.replace(TValue::DateHeure(value));
+        self.callback_info_on_change(id_info, old_t_value);
     }
 
     /* ---------*/
-    /** String **/
+    /*  String  */
     /* ---------*/
 
     /// Getter d'une information de type `string`
@@ -1084,25 +1064,41 @@ impl Context {
     }
 
     /// Setter d'une information de type `string`
+    /// (conservé en entier, tronqué seulement dans les trames et pour l'affichage selon le nombre
+    /// de caractères de l'information pour le message courant, voir `get_info_string_width`)
     pub fn set_info_string(&mut self, id_info: IdInfo, value: &str) {
+        let option_max_t_value = self.get_info_max_t_value(id_info);
         let inner_info = self.get_mut_inner_info(id_info);
-        let value = if let FormatInfo::String(width) = inner_info.format_info {
-            if value.len() > width {
-                &value[0..width]
-            } else {
-                value
-            }
-        } else {
-            value
-        };
-        if let Some(TValue::String(max_value)) = &inner_info.option_max_t_value {
+        if let Some(TValue::String(max_value)) = &option_max_t_value {
             if value > &max_value[..] {
                 return;
             }
         }
-        let t_value = TValue::String(value.to_string());
-        inner_info.option_t_value = Some(t_value.clone());
-        self.callback_info_on_change(id_info, &t_value);
+        let old_t_value = inner_info
+            .option_t_value
+            .replace(TValue::String(value.to_string()));
+        self.callback_info_on_change(id_info, old_t_value);
+    }
+}
+
+/// Helper pour décoder un nombre entier saisi par l'utilisateur
+fn parse_integer<T>(input: &str) -> Result<T, String>
+where
+    T: std::str::FromStr<Err = ParseIntError>,
+{
+    input.parse::<T>().map_err(|e| match e.kind() {
+        IntErrorKind::PosOverflow => "Nombre trop grand".to_string(),
+        _ => "Nombre entier positif attendu".to_string(),
+    })
+}
+
+/// Helper pour tronquer une chaîne à au plus `width` caractères
+/// (un caractère accentué compte pour un seul caractère, quel que soit le jeu de caractères des
+/// trames, voir `CodePage`)
+fn truncate_str(value: &str, width: usize) -> &str {
+    match value.char_indices().nth(width) {
+        Some((end, _)) => &value[..end],
+        None => value,
     }
 }
 
@@ -1115,7 +1111,7 @@ mod tests {
     // Voir `test_get_set` ci-dessous
     #[allow(clippy::too_many_lines)]
     fn check_id_code(context: &mut Context, id_info: IdInfo) {
-        let option_max_t_value = { context.get_inner_info(id_info).option_max_t_value.clone() };
+        let option_max_t_value = context.get_info_max_t_value(id_info);
         match context.get_info_format(id_info) {
             FormatInfo::Bool => {
                 assert!(context.get_option_info_bool(id_info).is_none());
@@ -1215,6 +1211,38 @@ mod tests {
                     assert_eq!(context.get_option_info_f32(id_info), Some(value));
                 }
             }
+            FormatInfo::Date => {
+                assert!(context.get_option_info_date(id_info).is_none());
+                for aammjj in [99_12_31, 24_02_29, 1_01] {
+                    let value = Date::from_aammjj(aammjj).unwrap();
+                    context.set_info_date(id_info, value);
+                    assert_eq!(context.get_option_info_date(id_info), Some(value));
+                }
+            }
+            FormatInfo::HeureHHMM => {
+                assert!(context.get_option_info_heure_hhmm(id_info).is_none());
+                for hhmm in [0, 12_34, 23_59] {
+                    let value = Heure::from_hhmm(hhmm).unwrap();
+                    context.set_info_heure_hhmm(id_info, value);
+                    assert_eq!(context.get_option_info_heure_hhmm(id_info), Some(value));
+                }
+            }
+            FormatInfo::HeureHHMMSS => {
+                assert!(context.get_option_info_heure_hhmmss(id_info).is_none());
+                for hhmmss in [0, 12_34_56, 23_59_59] {
+                    let value = Heure::from_hhmmss(hhmmss).unwrap();
+                    context.set_info_heure_hhmmss(id_info, value);
+                    assert_eq!(context.get_option_info_heure_hhmmss(id_info), Some(value));
+                }
+            }
+            FormatInfo::DateHeure => {
+                assert!(context.get_option_info_date_heure(id_info).is_none());
+                for aammjjhhmmss in [99_12_31_23_59_59, 24_02_29_12_00_00] {
+                    let value = DateHeure::from_aammjjhhmmss(aammjjhhmmss).unwrap();
+                    context.set_info_date_heure(id_info, value);
+                    assert_eq!(context.get_option_info_date_heure(id_info), Some(value));
+                }
+            }
             FormatInfo::String(_width) => {
                 assert!(context.get_option_info_string(id_info).is_none());
                 for value in ["", "ABC"] {
@@ -1273,6 +1301,8 @@ mod tests {
         check_id_code(&mut context, IdInfo::NbMesuragesQuantieme);
         check_id_code(&mut context, IdInfo::LibelleProduit);
         check_id_code(&mut context, IdInfo::NbFractionnements);
+        // Attention, les informations déduites sont à tester avant leurs sources
+        check_id_code(&mut context, IdInfo::LibelleCodeProduit);
         for prod_num in 0..=NB_PRODUITS {
             check_id_code(&mut context, IdInfo::LibelleTableProduits(prod_num));
         }
@@ -1281,6 +1311,7 @@ mod tests {
         check_id_code(&mut context, IdInfo::NbJEvents);
         check_id_code(&mut context, IdInfo::DataJEvent);
         check_id_code(&mut context, IdInfo::LibelleJEvent);
+        check_id_code(&mut context, IdInfo::QuantiteTotaleCompartiments);
         for compart_num in 0..=NB_COMPARTIMENTS {
             check_id_code(&mut context, IdInfo::CodeProduitCompartiment(compart_num));
             check_id_code(&mut context, IdInfo::QuantiteCompartiment(compart_num));
@@ -1313,6 +1344,40 @@ mod tests {
         let _ = context.get_option_info_bool(IdInfo::TemperatureInstant);
     }
 
+    #[test]
+    fn test_context_lazy_infos() {
+        // Le contexte par défaut ne mémorise aucune information
+        let mut context = Context::default();
+        assert!(context.hash_id_infos.is_empty());
+
+        // Les lectures ne créent pas d'information
+        assert_eq!(
+            context.get_info_label(IdInfo::QuantiteCompartiment(3)),
+            "Quantité cpt #3"
+        );
+        assert!(context
+            .get_option_info_u32(IdInfo::QuantiteCompartiment(3))
+            .is_none());
+        assert!(context.get_info_history(IdInfo::Totalisateur).is_empty());
+        assert!(context.hash_id_infos.is_empty());
+
+        // Une mise à jour crée l'information (et les informations déduites)
+        context.set_info_u32(IdInfo::QuantiteCompartiment(3), 1000);
+        assert!(context
+            .hash_id_infos
+            .contains_key(&IdInfo::QuantiteCompartiment(3)));
+        assert!(!context
+            .hash_id_infos
+            .contains_key(&IdInfo::QuantiteCompartiment(4)));
+    }
+
+    #[test]
+    #[should_panic(expected = "IdInfo QuantiteCompartiment(10) inconnue")]
+    fn test_context_index_panic() {
+        let context = Context::default();
+        let _ = context.get_option_info_u32(IdInfo::QuantiteCompartiment(NB_COMPARTIMENTS + 1));
+    }
+
     #[test]
     fn test_context_string_bool() {
         let mut context = Context::default();
@@ -1324,11 +1389,11 @@ mod tests {
         assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(true));
         assert_eq!(context.get_info_to_string(IdInfo::Ack, "None"), "Oui");
 
-        context.set_info_from_string(IdInfo::Ack, "Non");
+        assert_eq!(context.set_info_from_string(IdInfo::Ack, "Non"), Ok(()));
         assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(false));
         assert_eq!(context.get_info_to_string(IdInfo::Ack, "None"), "Non");
 
-        context.set_info_from_string(IdInfo::Ack, "");
+        assert_eq!(context.set_info_from_string(IdInfo::Ack, ""), Ok(()));
         assert_eq!(context.get_option_info_bool(IdInfo::Ack), None);
     }
 
@@ -1344,7 +1409,10 @@ mod tests {
             "None"
         );
 
-        context.set_info_from_string(IdInfo::TypeDistribution, "X");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TypeDistribution, "X"),
+            Ok(())
+        );
         assert_eq!(
             context.get_option_info_char(IdInfo::TypeDistribution),
             Some('X')
@@ -1354,7 +1422,10 @@ mod tests {
             "X"
         );
 
-        context.set_info_from_string(IdInfo::TypeDistribution, "");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TypeDistribution, ""),
+            Ok(())
+        );
         assert_eq!(context.get_option_info_char(IdInfo::TypeDistribution), None);
     }
 
@@ -1368,11 +1439,17 @@ mod tests {
             "None"
         );
 
-        context.set_info_from_string(IdInfo::CodeProduit, "3");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::CodeProduit, "3"),
+            Ok(())
+        );
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), Some(3));
         assert_eq!(context.get_info_to_string(IdInfo::CodeProduit, "None"), "3");
 
-        context.set_info_from_string(IdInfo::CodeProduit, "");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::CodeProduit, ""),
+            Ok(())
+        );
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), None);
     }
 
@@ -1380,21 +1457,30 @@ mod tests {
     fn test_context_string_u16() {
         let mut context = Context::default();
 
-        assert!(context.get_option_info_u16(IdInfo::HeureHHMM).is_none());
+        assert!(context.get_option_info_u16(IdInfo::IndexSansRaz).is_none());
         assert_eq!(
-            context.get_info_to_string(IdInfo::HeureHHMM, "None"),
+            context.get_info_to_string(IdInfo::IndexSansRaz, "None"),
             "None"
         );
 
-        context.set_info_from_string(IdInfo::HeureHHMM, "1234");
-        assert_eq!(context.get_option_info_u16(IdInfo::HeureHHMM), Some(1234));
         assert_eq!(
-            context.get_info_to_string(IdInfo::HeureHHMM, "None"),
+            context.set_info_from_string(IdInfo::IndexSansRaz, "1234"),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_option_info_u16(IdInfo::IndexSansRaz),
+            Some(1234)
+        );
+        assert_eq!(
+            context.get_info_to_string(IdInfo::IndexSansRaz, "None"),
             "1234"
         );
 
-        context.set_info_from_string(IdInfo::HeureHHMM, "");
-        assert_eq!(context.get_option_info_u16(IdInfo::HeureHHMM), None);
+        assert_eq!(
+            context.set_info_from_string(IdInfo::IndexSansRaz, ""),
+            Ok(())
+        );
+        assert_eq!(context.get_option_info_u16(IdInfo::IndexSansRaz), None);
     }
 
     #[test]
@@ -1409,7 +1495,10 @@ mod tests {
             "None"
         );
 
-        context.set_info_from_string(IdInfo::QuantitePrincipale, "12345");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::QuantitePrincipale, "12345"),
+            Ok(())
+        );
         assert_eq!(
             context.get_option_info_u32(IdInfo::QuantitePrincipale),
             Some(12345)
@@ -1419,38 +1508,12 @@ mod tests {
             "12345"
         );
 
-        context.set_info_from_string(IdInfo::QuantitePrincipale, "");
-        assert_eq!(
-            context.get_option_info_u32(IdInfo::QuantitePrincipale),
-            None
-        );
-    }
-
-    #[test]
-    fn test_context_string_u64() {
-        let mut context = Context::default();
-
-        assert!(context
-            .get_option_info_u64(IdInfo::DateAAMMJJHeureHHMMSS)
-            .is_none());
-        assert_eq!(
-            context.get_info_to_string(IdInfo::DateAAMMJJHeureHHMMSS, "None"),
-            "None"
-        );
-
-        context.set_info_from_string(IdInfo::DateAAMMJJHeureHHMMSS, "991231235959");
-        assert_eq!(
-            context.get_option_info_u64(IdInfo::DateAAMMJJHeureHHMMSS),
-            Some(99_12_31_23_59_59)
-        );
         assert_eq!(
-            context.get_info_to_string(IdInfo::DateAAMMJJHeureHHMMSS, "None"),
-            "991231235959"
+            context.set_info_from_string(IdInfo::QuantitePrincipale, ""),
+            Ok(())
         );
-
-        context.set_info_from_string(IdInfo::DateAAMMJJHeureHHMMSS, "");
         assert_eq!(
-            context.get_option_info_u64(IdInfo::DateAAMMJJHeureHHMMSS),
+            context.get_option_info_u32(IdInfo::QuantitePrincipale),
             None
         );
     }
@@ -1467,7 +1530,10 @@ mod tests {
             "None"
         );
 
-        context.set_info_from_string(IdInfo::TemperatureInstant, "-12.3");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TemperatureInstant, "-12.3"),
+            Ok(())
+        );
         assert_eq!(
             context.get_option_info_f32(IdInfo::TemperatureInstant),
             Some(-12.3)
@@ -1477,7 +1543,10 @@ mod tests {
             "-12.3"
         );
 
-        context.set_info_from_string(IdInfo::TemperatureInstant, "");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TemperatureInstant, ""),
+            Ok(())
+        );
         assert_eq!(
             context.get_option_info_f32(IdInfo::TemperatureInstant),
             None
@@ -1496,7 +1565,10 @@ mod tests {
             "None"
         );
 
-        context.set_info_from_string(IdInfo::LibelleProduit, "ABCDE");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::LibelleProduit, "ABCDE"),
+            Ok(())
+        );
         assert_eq!(
             context.get_option_info_string(IdInfo::LibelleProduit),
             Some("ABCDE".to_string())
@@ -1506,8 +1578,187 @@ mod tests {
             "ABCDE"
         );
 
-        context.set_info_from_string(IdInfo::LibelleProduit, "");
+        assert_eq!(
+            context.set_info_from_string(IdInfo::LibelleProduit, ""),
+            Ok(())
+        );
         assert_eq!(context.get_option_info_string(IdInfo::LibelleProduit), None);
+
+        // Troncature en caractères (et non en octets UTF-8)
+        assert_eq!(truncate_str("ABC", 5), "ABC");
+        assert_eq!(truncate_str("ABCDEF", 5), "ABCDE");
+        assert_eq!(truncate_str("ABCDé", 5), "ABCDé");
+        assert_eq!(truncate_str("ABCDéF", 5), "ABCDé");
+    }
+
+    #[test]
+    fn test_set_info_from_string_errors() {
+        let mut context = Context::default();
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "5000"),
+            Ok(())
+        );
+
+        // Valeur max
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "100000"),
+            Err("Valeur max. 99999".to_string())
+        );
+
+        // Nombres incorrects
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "12a"),
+            Err("Nombre entier positif attendu".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "-1"),
+            Err("Nombre entier positif attendu".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::CodeProduit, "256"),
+            Err("Nombre trop grand".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::NumeroCompartiment, "X"),
+            Err("Numéro ou 'T' attendu".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TemperatureInstant, "chaud"),
+            Err("Nombre attendu".to_string())
+        );
+
+        // Booléens et caractères
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Ack, "peut-être"),
+            Err("'Oui' ou 'Non' attendu".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TypeDistribution, "AB"),
+            Err("Un seul caractère attendu".to_string())
+        );
+
+        // Regex
+        assert_eq!(
+            context.set_info_from_string(IdInfo::OrdreCompartiments, "1234567890"),
+            Err("Format incorrect".to_string())
+        );
+
+        // Dates et heures
+        assert_eq!(
+            context.set_info_from_string(IdInfo::HeureHHMM, "1260"),
+            Err("Minute de 00 à 59".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::DateAAMMJJ, "230229"),
+            Err("Jour de 01 à 28".to_string())
+        );
+
+        // La valeur n'est pas modifiée par une saisie refusée
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(5000)
+        );
+    }
+
+    #[test]
+    fn test_check_info_domain() {
+        let context = Context::default();
+
+        // Sans domaine particulier
+        assert_eq!(
+            context.check_info_domain(IdInfo::Totalisateur, &TValue::U32(99_999_999)),
+            Ok(())
+        );
+
+        // Valeurs min/max
+        assert_eq!(
+            context.check_info_domain(IdInfo::Quantieme, &TValue::U16(1)),
+            Ok(())
+        );
+        assert_eq!(
+            context.check_info_domain(IdInfo::Quantieme, &TValue::U16(366)),
+            Ok(())
+        );
+        assert_eq!(
+            context.check_info_domain(IdInfo::Quantieme, &TValue::U16(0)),
+            Err("Valeur entre 1 et 366".to_string())
+        );
+        assert_eq!(
+            context.check_info_domain(IdInfo::Quantieme, &TValue::U16(367)),
+            Err("Valeur entre 1 et 366".to_string())
+        );
+        assert_eq!(
+            context.check_info_domain(IdInfo::TypeCompteur, &TValue::U8(3)),
+            Err("Valeur max. 2".to_string())
+        );
+        assert!(context
+            .check_info_domain(
+                IdInfo::CodeProduitCollecteur,
+                &TValue::U8(u8::try_from(NB_PRODUITS).unwrap() + 1)
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_transaction() {
+        let mut context = Context::default();
+        context.set_info_u32(IdInfo::Totalisateur, 1234);
+
+        // Aucune mise à jour si une erreur survient
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u32(IdInfo::Totalisateur, 5678);
+            context.set_info_from_string(IdInfo::Quantieme, "367")
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            Some(1234)
+        );
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), None);
+
+        // Toutes les mises à jour sinon
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u32(IdInfo::Totalisateur, 5678);
+            context.set_info_from_string(IdInfo::Quantieme, "366")
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            Some(5678)
+        );
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), Some(366));
+
+        context.clear_infos(&[IdInfo::Totalisateur]);
+        assert_eq!(context.get_option_info_u32(IdInfo::Totalisateur), None);
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), Some(366));
+    }
+
+    #[test]
+    fn test_clear_infos_derivees() {
+        let mut context = Context::default();
+        context.set_info_date_heure(
+            IdInfo::DateAAMMJJHeureHHMMSS,
+            DateHeure::from_aammjjhhmmss(240_315_131_415).unwrap(),
+        );
+        context.set_info_u32(IdInfo::QuantiteCompartiment(1), 1000);
+        context.set_info_u32(IdInfo::QuantiteCompartiment(2), 2500);
+
+        // Les informations déduites ne peuvent plus être calculées
+        context.clear_infos(&[IdInfo::DateAAMMJJHeureHHMMSS]);
+        assert!(context.get_option_info_date(IdInfo::DateAAMMJJ).is_none());
+        assert!(context
+            .get_option_info_heure_hhmmss(IdInfo::HeureHHMMSS)
+            .is_none());
+        assert!(context
+            .get_option_info_heure_hhmm(IdInfo::HeureHHMM)
+            .is_none());
+
+        // Les informations déduites sont recalculées
+        context.clear_infos(&[IdInfo::QuantiteCompartiment(1)]);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::QuantiteTotaleCompartiments),
+            Some(2500)
+        );
     }
 
     #[test]
@@ -1516,28 +1767,92 @@ mod tests {
 
         // Par défaut, rien n'est défini
         assert!(context
-            .get_option_info_u64(IdInfo::DateAAMMJJHeureHHMMSS)
+            .get_option_info_date_heure(IdInfo::DateAAMMJJHeureHHMMSS)
+            .is_none());
+        assert!(context.get_option_info_date(IdInfo::DateAAMMJJ).is_none());
+        assert!(context
+            .get_option_info_heure_hhmmss(IdInfo::HeureHHMMSS)
+            .is_none());
+        assert!(context
+            .get_option_info_heure_hhmm(IdInfo::HeureHHMM)
             .is_none());
-        assert!(context.get_option_info_u32(IdInfo::DateAAMMJJ).is_none());
-        assert!(context.get_option_info_u32(IdInfo::HeureHHMMSS).is_none());
-        assert!(context.get_option_info_u16(IdInfo::HeureHHMM).is_none());
 
         // Si on définit l'heure_minute_seconde, heure_minute est maintenant défini
-        context.set_info_u32(IdInfo::HeureHHMMSS, 13_14_15);
-        assert_eq!(context.get_option_info_u16(IdInfo::HeureHHMM), Some(13_14));
+        context.set_info_heure_hhmmss(IdInfo::HeureHHMMSS, Heure::new(13, 14, 15).unwrap());
+        assert_eq!(
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMM),
+            Some(Heure::new(13, 14, 0).unwrap())
+        );
 
         // Si on définit an_mois_jour_heure_minute_seconde, an_mois_jour est défini...
-        context.set_info_u64(IdInfo::DateAAMMJJHeureHHMMSS, 1_02_03_10_11_12);
+        context.set_info_date_heure(
+            IdInfo::DateAAMMJJHeureHHMMSS,
+            DateHeure::from_aammjjhhmmss(1_02_03_10_11_12).unwrap(),
+        );
         assert_eq!(
-            context.get_option_info_u32(IdInfo::DateAAMMJJ),
-            Some(1_02_03)
+            context.get_option_info_date(IdInfo::DateAAMMJJ),
+            Some(Date::new(2001, 2, 3).unwrap())
         );
         // ... ainsi que heure_minute_seconde...
         assert_eq!(
-            context.get_option_info_u32(IdInfo::HeureHHMMSS),
-            Some(10_11_12)
+            context.get_option_info_heure_hhmmss(IdInfo::HeureHHMMSS),
+            Some(Heure::new(10, 11, 12).unwrap())
         );
         // ... ainsi que l'heure sans les secondes
-        assert_eq!(context.get_option_info_u16(IdInfo::HeureHHMM), Some(10_11));
+        assert_eq!(
+            context.get_info_to_string(IdInfo::HeureHHMM, "None"),
+            "10:11"
+        );
+    }
+
+    #[test]
+    fn test_context_string_date_heure() {
+        let mut context = Context::default();
+
+        // Saisie lisible ou au format ST2150
+        for input in ["31/12/1999 23:59:59", "991231235959"] {
+            assert_eq!(
+                context.set_info_from_string(IdInfo::DateAAMMJJHeureHHMMSS, input),
+                Ok(())
+            );
+            assert_eq!(
+                context.get_info_to_string(IdInfo::DateAAMMJJHeureHHMMSS, "None"),
+                "31/12/1999 23:59:59"
+            );
+        }
+        assert_eq!(
+            context.get_info_to_string(IdInfo::DateAAMMJJ, "None"),
+            "31/12/1999"
+        );
+        assert_eq!(
+            context.get_info_to_string(IdInfo::HeureHHMMSS, "None"),
+            "23:59:59"
+        );
+
+        // Les secondes ne sont pas conservées pour une heure HHMM
+        assert_eq!(
+            context.set_info_from_string(IdInfo::HeureHHMMDebut, "12:34:56"),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_info_to_string(IdInfo::HeureHHMMDebut, "None"),
+            "12:34"
+        );
+
+        // Dates et heures impossibles
+        assert_eq!(
+            context.set_info_from_string(IdInfo::DateAAMMJJ, "991399"),
+            Err("Mois de 01 à 12".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::HeureHHMM, "2575"),
+            Err("Heure de 00 à 23".to_string())
+        );
+        assert!(context
+            .set_info_from_string(IdInfo::DateAAMMJJHeureHHMMSS, "31/12/1999")
+            .is_err());
+
+        assert_eq!(context.set_info_from_string(IdInfo::DateAAMMJJ, ""), Ok(()));
+        assert_eq!(context.get_option_info_date(IdInfo::DateAAMMJJ), None);
     }
 }
@@ -0,0 +1,219 @@
+//! Abonnement aux changements de valeur des informations du contexte
+//!
+//! Un module externe (journalisation, tableau de bord, passerelle, etc.) peut s'abonner aux
+//! changements d'une information du contexte ou de toutes les informations. Chaque changement
+//! est notifié avec l'ancienne valeur, la nouvelle valeur et la provenance de la mise à jour.
+//!
+//! * Seuls les changements de valeur sont notifiés (pas une mise à jour avec la même valeur)
+//! * Les informations déduites (voir `DERIVATION_RULES`) sont également notifiées
+//! * Lors d'une mise à jour 'atomique' (voir `transaction`), les changements ne sont notifiés
+//!   qu'une fois toutes les mises à jour reprises dans le contexte
+
+use std::sync::{Arc, Mutex};
+
+use super::{Context, IdInfo, Provenance, TValue};
+
+/// Changement de valeur d'une information du contexte
+#[derive(Clone, Debug, PartialEq)]
+pub struct InfoChange {
+    /// Information modifiée
+    pub id_info: IdInfo,
+
+    /// Valeur avant le changement (None si l'information n'était pas définie)
+    pub old_t_value: Option<TValue>,
+
+    /// Valeur après le changement (None si l'information n'est plus définie)
+    pub new_t_value: Option<TValue>,
+
+    /// Provenance du changement
+    pub provenance: Provenance,
+}
+
+/// Identifiant d'un abonnement (pour se désabonner)
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SubscriptionId(usize);
+
+/// Fonction appelée pour chaque changement notifié à un abonné
+pub type Observer = dyn FnMut(&InfoChange) + Send;
+
+/// Abonnement aux changements du contexte
+#[derive(Clone)]
+pub(super) struct Subscription {
+    /// Identifiant de l'abonnement
+    id: SubscriptionId,
+
+    /// Information concernée (None pour toutes les informations)
+    option_id_info: Option<IdInfo>,
+
+    /// Fonction appelée pour chaque changement
+    /// (partagée avec les copies du contexte)
+    observer: Arc<Mutex<Observer>>,
+}
+
+impl Context {
+    /// Abonnement aux changements d'une information du contexte
+    /// (ou de toutes les informations si `option_id_info` est None)
+    pub fn subscribe(
+        &mut self,
+        option_id_info: Option<IdInfo>,
+        observer: impl FnMut(&InfoChange) + Send + 'static,
+    ) -> SubscriptionId {
+        self.last_subscription_id += 1;
+        let id = SubscriptionId(self.last_subscription_id);
+        self.subscriptions.push(Subscription {
+            id,
+            option_id_info,
+            observer: Arc::new(Mutex::new(observer)),
+        });
+        id
+    }
+
+    /// Désabonnement (sans effet si l'abonnement n'existe plus)
+    pub fn unsubscribe(&mut self, id: SubscriptionId) {
+        self.subscriptions
+            .retain(|subscription| subscription.id != id);
+    }
+
+    /// Notification d'un changement de valeur d'une information du contexte aux abonnés
+    /// (la nouvelle valeur est la valeur actuelle de l'information)
+    pub(super) fn notify_info_change(&mut self, id_info: IdInfo, old_t_value: Option<TValue>) {
+        let new_t_value = self.get_inner_info(id_info).option_t_value.clone();
+        if old_t_value == new_t_value || self.subscriptions.is_empty() {
+            return;
+        }
+        let info_change = InfoChange {
+            id_info,
+            old_t_value,
+            new_t_value,
+            provenance: self.provenance.clone(),
+        };
+        match &mut self.option_deferred_changes {
+            // Mise à jour 'atomique' en cours : Notification différée
+            Some(deferred_changes) => deferred_changes.push(info_change),
+            None => self.do_notify_info_change(&info_change),
+        }
+    }
+
+    /// Notification des changements différés lors d'une mise à jour 'atomique'
+    pub(super) fn notify_deferred_changes(&mut self) {
+        for info_change in self.option_deferred_changes.take().unwrap_or_default() {
+            self.do_notify_info_change(&info_change);
+        }
+    }
+
+    /// Appel des abonnés concernés par un changement
+    fn do_notify_info_change(&self, info_change: &InfoChange) {
+        for subscription in &self.subscriptions {
+            if subscription
+                .option_id_info
+                .is_none_or(|id_info| id_info == info_change.id_info)
+            {
+                if let Ok(mut observer) = subscription.observer.lock() {
+                    observer(info_change);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Abonnement qui mémorise tous les changements reçus
+    fn subscribe_changes(
+        context: &mut Context,
+        option_id_info: Option<IdInfo>,
+    ) -> (SubscriptionId, Arc<Mutex<Vec<InfoChange>>>) {
+        let changes = Arc::new(Mutex::new(vec![]));
+        let changes_observer = changes.clone();
+        let id = context.subscribe(option_id_info, move |info_change| {
+            changes_observer.lock().unwrap().push(info_change.clone());
+        });
+        (id, changes)
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let mut context = Context::default();
+        let (id_all, changes_all) = subscribe_changes(&mut context, None);
+        let (_, changes_heure) = subscribe_changes(&mut context, Some(IdInfo::HeureHHMM));
+
+        // Changement avec les informations déduites
+        context.set_info_u32(IdInfo::Predetermination, 5000);
+        context
+            .with_provenance(Provenance::Message(36), |context| {
+                context.set_info_from_string(IdInfo::HeureHHMMSS, "12:34:56")
+            })
+            .unwrap();
+        assert_eq!(
+            changes_all
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|info_change| info_change.id_info)
+                .collect::<Vec<_>>(),
+            vec![
+                IdInfo::Predetermination,
+                IdInfo::HeureHHMMSS,
+                IdInfo::HeureHHMM
+            ]
+        );
+        assert_eq!(
+            *changes_heure.lock().unwrap(),
+            vec![InfoChange {
+                id_info: IdInfo::HeureHHMM,
+                old_t_value: None,
+                new_t_value: Some(TValue::HeureHHMM("12:34".parse().unwrap())),
+                provenance: Provenance::Message(36),
+            }]
+        );
+
+        // Pas de notification sans changement de valeur
+        context.set_info_u32(IdInfo::Predetermination, 5000);
+        assert_eq!(changes_all.lock().unwrap().len(), 3);
+
+        // Une information qui n'est plus définie
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, ""),
+            Ok(())
+        );
+        assert_eq!(
+            changes_all.lock().unwrap().last().map(|info_change| (
+                info_change.old_t_value.clone(),
+                info_change.new_t_value.clone()
+            )),
+            Some((Some(TValue::U32(5000)), None))
+        );
+
+        // Plus de notification après désabonnement
+        context.unsubscribe(id_all);
+        context.set_info_u32(IdInfo::Predetermination, 1000);
+        assert_eq!(changes_all.lock().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_subscribe_transaction() {
+        let mut context = Context::default();
+        let (_, changes) = subscribe_changes(&mut context, None);
+
+        // Pas de notification pour une mise à jour 'atomique' en échec
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u32(IdInfo::Totalisateur, 1234);
+            assert!(changes.lock().unwrap().is_empty());
+            Err("Échec".to_string())
+        });
+        assert!(result.is_err());
+        assert!(changes.lock().unwrap().is_empty());
+
+        // Notification de tous les changements une fois la mise à jour reprise
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u32(IdInfo::Totalisateur, 1234);
+            context.set_info_u16(IdInfo::Quantieme, 12);
+            assert!(changes.lock().unwrap().is_empty());
+            Ok(())
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(changes.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,221 @@
+//! Provenance et horodatage de la valeur de chaque information du contexte
+//!
+//! Plusieurs messages mettent à jour la même information (par exemple `QuantitePrincipale`
+//! pour les messages 10, 21, 32 et 34). Chaque information mémorise donc l'origine de sa
+//! dernière mise à jour : Le message ou la saisie concernée et l'instant de cette mise à jour.
+//!
+//! Une valeur reçue lors d'une vacation antérieure à la dernière vacation est dite 'ancienne'.
+
+use std::fmt::Display;
+use std::time::{Duration, SystemTime};
+
+use super::{Context, IdInfo};
+use crate::langue::tr;
+
+/// Provenance de la mise à jour d'une information du contexte
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Provenance {
+    /// Saisie manuelle (ou mise à jour par programme)
+    #[default]
+    Saisie,
+
+    /// Restauration d'un fichier session
+    Session,
+
+    /// Application d'un preset (nom du preset)
+    Preset(String),
+
+    /// Réponse à un message (numéro du message)
+    Message(u8),
+
+    /// Rejeu d'une vacation du journal des vacations (numéro de la vacation rejouée)
+    Rejeu(u32),
+}
+
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provenance::Saisie => write!(f, "{}", tr("Saisie manuelle")),
+            Provenance::Session => write!(f, "Session"),
+            Provenance::Preset(name) => write!(f, "Preset '{name}'"),
+            Provenance::Message(message_num) => {
+                write!(f, "{}", tr(&format!("Réponse message {message_num:02}")))
+            }
+            Provenance::Rejeu(num_vacation) => {
+                write!(f, "{}", tr(&format!("Rejeu vacation #{num_vacation}")))
+            }
+        }
+    }
+}
+
+/// Origine de la dernière mise à jour d'une information du contexte
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Origine {
+    /// Provenance de la mise à jour
+    pub provenance: Provenance,
+
+    /// Instant de la mise à jour
+    pub horodatage: SystemTime,
+
+    /// Numéro d'ordre de la mise à jour dans le contexte (strictement croissant, contrairement à
+    /// l'horodatage qui dépend de l'horloge système)
+    pub num_mise_a_jour: u64,
+
+    /// Numéro de la vacation en cours lors de la mise à jour (0 avant la première vacation)
+    pub num_vacation: u32,
+}
+
+impl Origine {
+    /// Ancienneté de la mise à jour (à l'instant présent)
+    pub fn age(&self) -> Duration {
+        self.horodatage.elapsed().unwrap_or_default()
+    }
+}
+
+impl Display for Origine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let txt = format!("{} il y a {}", self.provenance, age_to_string(self.age()));
+        write!(f, "{}", tr(&txt))
+    }
+}
+
+/// Représentation 'textuelle' d'une ancienneté (par exemple `2 min 05 s`)
+fn age_to_string(age: Duration) -> String {
+    let secondes = age.as_secs();
+    match (secondes / 3600, secondes / 60 % 60, secondes % 60) {
+        (0, 0, s) => format!("{s} s"),
+        (0, m, s) => format!("{m} min {s:02} s"),
+        (h, m, _) => format!("{h} h {m:02} min"),
+    }
+}
+
+impl Context {
+    /// Exécute des mises à jour du contexte avec une provenance spécifique
+    /// (la provenance précédente est restaurée ensuite)
+    pub fn with_provenance<R>(
+        &mut self,
+        provenance: Provenance,
+        update: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let previous_provenance = std::mem::replace(&mut self.provenance, provenance);
+        let result = update(self);
+        self.provenance = previous_provenance;
+        result
+    }
+
+    /// Début d'une nouvelle vacation<br>
+    /// Les valeurs reçues lors des vacations précédentes deviennent 'anciennes'
+    pub fn begin_vacation(&mut self) {
+        self.num_vacation += 1;
+    }
+
+    /// Numéro de la dernière vacation (0 avant la première vacation)
+    pub fn num_vacation(&self) -> u32 {
+        self.num_vacation
+    }
+
+    /// Origine de la dernière mise à jour d'une information du contexte
+    /// (None si l'information n'est pas définie)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn get_option_info_origine(&self, id_info: IdInfo) -> Option<&Origine> {
+        self.get_inner_info(id_info).option_origine.as_ref()
+    }
+
+    /// Indique si la valeur d'une information a été reçue lors d'une vacation
+    /// antérieure à la dernière vacation
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn is_info_stale(&self, id_info: IdInfo) -> bool {
+        self.get_option_info_origine(id_info)
+            .is_some_and(|origine| {
+                matches!(origine.provenance, Provenance::Message(_))
+                    && origine.num_vacation < self.num_vacation
+            })
+    }
+
+    /// Origine pour une mise à jour maintenant
+    pub(super) fn new_origine(&mut self) -> Origine {
+        self.num_mise_a_jour += 1;
+        Origine {
+            provenance: self.provenance.clone(),
+            horodatage: SystemTime::now(),
+            num_vacation: self.num_vacation,
+            num_mise_a_jour: self.num_mise_a_jour,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origine() {
+        let mut context = Context::default();
+        assert_eq!(context.get_option_info_origine(IdInfo::HeureHHMMSS), None);
+
+        // Saisie manuelle
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "5000"),
+            Ok(())
+        );
+        let origine = context
+            .get_option_info_origine(IdInfo::Predetermination)
+            .unwrap();
+        assert_eq!(origine.provenance, Provenance::Saisie);
+        assert!(origine.to_string().starts_with("Saisie manuelle il y a "));
+
+        // Réponse à un message, y compris pour les informations déduites
+        context.begin_vacation();
+        context
+            .with_provenance(Provenance::Message(30), |context| {
+                context.set_info_from_string(IdInfo::DateAAMMJJHeureHHMMSS, "240101123456")
+            })
+            .unwrap();
+        for id_info in [IdInfo::DateAAMMJJHeureHHMMSS, IdInfo::HeureHHMM] {
+            let origine = context.get_option_info_origine(id_info).unwrap();
+            assert_eq!(origine.provenance, Provenance::Message(30));
+            assert_eq!(origine.num_vacation, 1);
+            assert!(!context.is_info_stale(id_info));
+        }
+        assert!(
+            context
+                .get_option_info_origine(IdInfo::Predetermination)
+                .unwrap()
+                .num_mise_a_jour
+                < context
+                    .get_option_info_origine(IdInfo::DateAAMMJJHeureHHMMSS)
+                    .unwrap()
+                    .num_mise_a_jour
+        );
+
+        // La provenance est restaurée après la vacation
+        assert_eq!(context.provenance, Provenance::Saisie);
+
+        // Une nouvelle vacation rend 'anciennes' les valeurs reçues précédemment
+        context.begin_vacation();
+        context
+            .with_provenance(Provenance::Message(36), |context| {
+                context.set_info_from_string(IdInfo::HeureHHMMSS, "12:00:00")
+            })
+            .unwrap();
+        assert!(context.is_info_stale(IdInfo::DateAAMMJJHeureHHMMSS));
+        assert!(!context.is_info_stale(IdInfo::HeureHHMM));
+        assert!(!context.is_info_stale(IdInfo::Predetermination));
+
+        // Une information remise à `None` n'a plus d'origine
+        assert_eq!(context.set_info_from_string(IdInfo::HeureHHMM, ""), Ok(()));
+        assert_eq!(context.get_option_info_origine(IdInfo::HeureHHMM), None);
+    }
+
+    #[test]
+    fn test_age_to_string() {
+        assert_eq!(age_to_string(Duration::from_secs(5)), "5 s");
+        assert_eq!(age_to_string(Duration::from_secs(125)), "2 min 05 s");
+        assert_eq!(
+            age_to_string(Duration::from_secs(3 * 3600 + 65)),
+            "3 h 01 min"
+        );
+    }
+}
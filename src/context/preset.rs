@@ -0,0 +1,293 @@
+//! Presets nommés de valeurs pour les requêtes récurrentes
+//!
+//! Un preset est un sous-ensemble nommé d'informations du contexte, par exemple
+//! "Gazole 5000 L cpt 3 flexible 1", applicable d'un clic avant l'exécution des messages
+//! auxquels il est destiné. Les presets sont stockés dans un fichier texte :
+//!
+//! ```text
+//! [Gazole 5000 L cpt 3 flexible 1]
+//! messages = 61, 62
+//! CodeProduit = 1
+//! Predetermination = 5000
+//! NumeroCompartiment = 3
+//! NumeroFlexible = 1
+//! ```
+//!
+//! * Une section `[nom du preset]` par preset
+//! * Une ligne `messages = ...` optionnelle avec les numéros des messages concernés
+//!   (sans cette ligne, le preset est proposé pour tous les messages)
+//! * Puis une ligne `IdInfo = valeur` par information (même format qu'un fichier session)
+
+use std::fs;
+use std::path::Path;
+
+use super::session::is_session_comment;
+use super::{Context, IdInfo, Provenance, SessionError};
+
+/// Nom du fichier des presets (dans le répertoire courant)
+pub const PRESETS_FILE_NAME: &str = "sim_ie_presets.txt";
+
+/// Clé de la ligne avec les numéros des messages concernés par un preset
+const PRESET_MESSAGES_KEY: &str = "messages";
+
+/// Preset nommé de valeurs d'informations du contexte
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Preset {
+    /// Nom du preset
+    pub name: String,
+
+    /// Numéros des messages concernés (tous les messages si vide)
+    pub message_nums: Vec<u8>,
+
+    /// Valeurs 'textuelles' des informations du preset
+    pub values: Vec<(IdInfo, String)>,
+}
+
+impl Preset {
+    /// Indique si ce preset est destiné à un message
+    pub fn is_for_message(&self, message_num: u8) -> bool {
+        self.message_nums.is_empty() || self.message_nums.contains(&message_num)
+    }
+}
+
+/// Représentation 'textuelle' d'une liste de presets (contenu d'un fichier presets)
+pub fn presets_to_string(presets: &[Preset]) -> String {
+    let mut txt = String::new();
+    for (index, preset) in presets.iter().enumerate() {
+        if index > 0 {
+            txt.push('\n');
+        }
+        txt.push_str(&format!("[{}]\n", preset.name));
+        if !preset.message_nums.is_empty() {
+            let message_nums: Vec<String> = preset
+                .message_nums
+                .iter()
+                .map(|message_num| format!("{message_num:02}"))
+                .collect();
+            txt.push_str(&format!(
+                "{PRESET_MESSAGES_KEY} = {}\n",
+                message_nums.join(", ")
+            ));
+        }
+        for (id_info, value) in &preset.values {
+            txt.push_str(&format!("{} = {value}\n", Context::session_name(*id_info)));
+        }
+    }
+    txt
+}
+
+/// Sauvegarde d'une liste de presets dans un fichier
+/// # Errors
+/// `SessionError::Io` si le fichier ne peut pas être écrit
+pub fn save_presets(presets: &[Preset], path: &Path) -> Result<(), SessionError> {
+    fs::write(path, presets_to_string(presets))
+        .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))
+}
+
+impl Context {
+    /// Décodage d'une liste de presets depuis le contenu d'un fichier presets
+    /// # Errors
+    /// `SessionError` pour la première ligne incorrecte
+    pub fn presets_from_str(&self, txt: &str) -> Result<Vec<Preset>, SessionError> {
+        let mut presets: Vec<Preset> = vec![];
+        for (index, line) in txt.lines().enumerate() {
+            let num_ligne = index + 1;
+            if is_session_comment(line) {
+                continue;
+            }
+            let line = line.trim_end();
+
+            // Début d'un nouveau preset
+            if let Some(name) = line
+                .trim_start()
+                .strip_prefix('[')
+                .and_then(|name| name.strip_suffix(']'))
+            {
+                presets.push(Preset {
+                    name: name.trim().to_string(),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            // Une ligne doit toujours être dans un preset
+            let Some(preset) = presets.last_mut() else {
+                return Err(SessionError::BadLine(num_ligne));
+            };
+
+            // Numéros des messages concernés
+            if let Some((PRESET_MESSAGES_KEY, message_nums)) = line
+                .split_once('=')
+                .map(|(key, message_nums)| (key.trim(), message_nums))
+            {
+                for message_num in message_nums.split(',') {
+                    let Ok(message_num) = message_num.trim().parse::<u8>() else {
+                        return Err(SessionError::IllegalValue(
+                            num_ligne,
+                            PRESET_MESSAGES_KEY.to_string(),
+                            message_num.trim().to_string(),
+                            "Numéro de message attendu".to_string(),
+                        ));
+                    };
+                    preset.message_nums.push(message_num);
+                }
+                continue;
+            }
+
+            // Valeur d'une information
+            let (id_info, value) = self.parse_session_line(num_ligne, line)?;
+            preset.values.push((id_info, value.to_string()));
+        }
+        Ok(presets)
+    }
+
+    /// Lecture d'une liste de presets depuis un fichier
+    /// # Errors
+    /// `SessionError` si le fichier ne peut pas être lu ou s'il contient une erreur
+    pub fn load_presets(&self, path: &Path) -> Result<Vec<Preset>, SessionError> {
+        let txt = fs::read_to_string(path)
+            .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))?;
+        self.presets_from_str(&txt)
+    }
+
+    /// Applique les valeurs d'un preset aux informations du contexte
+    /// (les autres informations ne sont pas modifiées)
+    /// Le preset est appliqué entièrement ou pas du tout (voir `transaction`)
+    /// # Errors
+    /// Raison du refus de la première valeur incorrecte du preset (aucune valeur du preset
+    /// n'est alors appliquée)
+    pub fn apply_preset(&mut self, preset: &Preset) -> Result<(), String> {
+        self.transaction(|context| {
+            context.with_provenance(Provenance::Preset(preset.name.clone()), |context| {
+                for (id_info, value) in &preset.values {
+                    context
+                        .set_info_from_string(*id_info, value)
+                        .map_err(|raison| {
+                            format!("{} = {value} : {raison}", Self::session_name(*id_info))
+                        })?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    /// Création d'un preset avec les valeurs actuelles de certaines informations du contexte
+    /// (les informations non définies ne sont pas reprises dans le preset)
+    pub fn to_preset(&self, name: &str, message_nums: &[u8], id_infos: &[IdInfo]) -> Preset {
+        Preset {
+            name: name.to_string(),
+            message_nums: message_nums.to_vec(),
+            values: id_infos
+                .iter()
+                .filter(|id_info| self.get_inner_info(**id_info).option_t_value.is_some())
+                .map(|id_info| (*id_info, self.get_info_to_session_string(*id_info)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: &str = "# Presets de test
+[Gazole 5000 L cpt 3 flexible 1]
+messages = 61, 62
+CodeProduit = 1
+Predetermination = 5000
+NumeroCompartiment = 3
+NumeroFlexible = 1
+
+[Quantième 123]
+Quantieme = 123
+";
+
+    #[test]
+    fn test_presets() {
+        let mut context = Context::default();
+        let presets = context.presets_from_str(PRESETS).unwrap();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "Gazole 5000 L cpt 3 flexible 1");
+        assert_eq!(presets[0].message_nums, vec![61, 62]);
+        assert!(presets[0].is_for_message(62));
+        assert!(!presets[0].is_for_message(10));
+        assert!(presets[1].is_for_message(10));
+
+        // Application d'un preset
+        context.set_info_u32(IdInfo::Totalisateur, 1234);
+        assert_eq!(context.apply_preset(&presets[0]), Ok(()));
+        assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), Some(1));
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(5000)
+        );
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            Some(1234)
+        );
+        assert_eq!(
+            context
+                .get_option_info_origine(IdInfo::CodeProduit)
+                .map(|origine| origine.provenance.clone()),
+            Some(Provenance::Preset(
+                "Gazole 5000 L cpt 3 flexible 1".to_string()
+            ))
+        );
+
+        // Aller-retour dans le format du fichier
+        let txt = presets_to_string(&presets);
+        assert_eq!(context.presets_from_str(&txt), Ok(presets.clone()));
+
+        // Création d'un preset depuis le contexte
+        let preset = context.to_preset(
+            "Nouveau",
+            &[61],
+            &[IdInfo::CodeProduit, IdInfo::NumeroFlexibleFinal],
+        );
+        assert_eq!(preset.values, vec![(IdInfo::CodeProduit, "1".to_string())]);
+
+        // Pas de perte des décimales au-delà de la précision d'affichage
+        context.set_info_f32(IdInfo::TemperatureMoyen, 12.34);
+        let preset = context.to_preset("Température", &[], &[IdInfo::TemperatureMoyen]);
+        assert_eq!(
+            preset.values,
+            vec![(IdInfo::TemperatureMoyen, "12.34".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_presets_errors() {
+        let mut context = Context::default();
+        assert_eq!(
+            context.presets_from_str("CodeProduit = 1\n"),
+            Err(SessionError::BadLine(1))
+        );
+        assert_eq!(
+            context.presets_from_str("[P]\nmessages = 61, X\n"),
+            Err(SessionError::IllegalValue(
+                2,
+                "messages".to_string(),
+                "X".to_string(),
+                "Numéro de message attendu".to_string()
+            ))
+        );
+        assert_eq!(
+            context.presets_from_str("[P]\nInconnue = 1\n"),
+            Err(SessionError::UnknownInfo(2, "Inconnue".to_string()))
+        );
+
+        // Un preset avec une valeur refusée n'est pas appliqué du tout
+        context.set_info_u8(IdInfo::CodeProduit, 2);
+        let preset = Preset {
+            name: "P".to_string(),
+            message_nums: vec![],
+            values: vec![
+                (IdInfo::CodeProduit, "1".to_string()),
+                (IdInfo::Quantieme, "999".to_string()),
+            ],
+        };
+        assert!(context.apply_preset(&preset).is_err());
+        assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), Some(2));
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), None);
+    }
+}
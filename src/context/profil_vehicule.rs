@@ -0,0 +1,309 @@
+//! Profil du véhicule : Nombre de produits, de compartiments et de flexibles
+//!
+//! La ST2150 prévoit au maximum `NB_PRODUITS` produits, `NB_COMPARTIMENTS` compartiments et
+//! `NB_FLEXIBLES` flexibles. Un véhicule réel en a souvent moins (par exemple 5 compartiments
+//! et 2 flexibles). Le profil du véhicule restreint les saisies et les requêtes à ses limites,
+//! et permet à l'IHM de ne pas afficher les compartiments absents.
+//!
+//! Le profil est stocké dans un fichier texte (même format qu'un fichier session) :
+//!
+//! ```text
+//! # Profil véhicule sim_ie
+//! produits = 16
+//! compartiments = 5
+//! flexibles = 2
+//! ```
+
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::session::is_session_comment;
+use super::{Context, IdInfo, SessionError};
+use super::{NB_COMPARTIMENTS, NB_FLEXIBLES, NB_PRODUITS};
+
+/// Nom du fichier du profil véhicule (dans le répertoire courant)
+pub const PROFIL_VEHICULE_FILE_NAME: &str = "sim_ie_profil.txt";
+
+/// Entête d'un fichier profil véhicule
+const PROFIL_VEHICULE_HEADER: &str = "# Profil véhicule sim_ie";
+
+/// Clés des lignes d'un fichier profil véhicule
+const PRODUITS_KEY: &str = "produits";
+const COMPARTIMENTS_KEY: &str = "compartiments";
+const FLEXIBLES_KEY: &str = "flexibles";
+
+/// Profil du véhicule (par défaut, les maxima de la ST2150)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProfilVehicule {
+    nb_produits: u8,
+    nb_compartiments: u8,
+    nb_flexibles: u8,
+}
+
+impl Default for ProfilVehicule {
+    fn default() -> Self {
+        Self {
+            nb_produits: max_u8(NB_PRODUITS),
+            nb_compartiments: max_u8(NB_COMPARTIMENTS),
+            nb_flexibles: max_u8(NB_FLEXIBLES),
+        }
+    }
+}
+
+impl ProfilVehicule {
+    /// Constructeur avec contrôle des limites de la ST2150
+    /// # Errors
+    /// Raison du refus si une des valeurs est hors des limites de la ST2150
+    pub fn new(nb_produits: u8, nb_compartiments: u8, nb_flexibles: u8) -> Result<Self, String> {
+        for (nom, value, max) in [
+            (PRODUITS_KEY, nb_produits, max_u8(NB_PRODUITS)),
+            (
+                COMPARTIMENTS_KEY,
+                nb_compartiments,
+                max_u8(NB_COMPARTIMENTS),
+            ),
+            (FLEXIBLES_KEY, nb_flexibles, max_u8(NB_FLEXIBLES)),
+        ] {
+            if !(1..=max).contains(&value) {
+                return Err(format!("Nombre de {nom} de 1 à {max}"));
+            }
+        }
+        Ok(Self {
+            nb_produits,
+            nb_compartiments,
+            nb_flexibles,
+        })
+    }
+
+    /// Nombre de produits du véhicule
+    pub fn nb_produits(self) -> u8 {
+        self.nb_produits
+    }
+
+    /// Nombre de compartiments du véhicule
+    pub fn nb_compartiments(self) -> u8 {
+        self.nb_compartiments
+    }
+
+    /// Nombre de flexibles du véhicule
+    pub fn nb_flexibles(self) -> u8 {
+        self.nb_flexibles
+    }
+
+    /// Profil avec un autre nombre de compartiments (typiquement reçu par le message 11)
+    /// # Errors
+    /// Raison du refus si le nombre de compartiments est hors des limites de la ST2150
+    pub fn with_nb_compartiments(self, nb_compartiments: u8) -> Result<Self, String> {
+        Self::new(self.nb_produits, nb_compartiments, self.nb_flexibles)
+    }
+
+    /// Indique si une information concerne ce véhicule
+    /// (par exemple, pas de compartiment #7 pour un véhicule à 5 compartiments)
+    pub fn is_info_relevant(self, id_info: IdInfo) -> bool {
+        match id_info {
+            IdInfo::CodeProduitCompartiment(compart_num)
+            | IdInfo::QuantiteCompartiment(compart_num) => {
+                compart_num <= usize::from(self.nb_compartiments)
+            }
+            IdInfo::LibelleTableProduits(prod_num) => prod_num <= usize::from(self.nb_produits),
+            IdInfo::CodeProduitFlexible2 => self.nb_flexibles >= 2,
+            _ => true,
+        }
+    }
+
+    /// Valeur max. d'une information de requête limitée par ce profil
+    /// (None si l'information n'est pas limitée par le profil)
+    pub(super) fn get_option_max(self, id_info: IdInfo) -> Option<u8> {
+        match id_info {
+            IdInfo::CodeProduit | IdInfo::CodeProduitFinal => Some(self.nb_produits),
+            IdInfo::NumeroCompartiment | IdInfo::NumeroCompartimentFinal => {
+                Some(self.nb_compartiments)
+            }
+            IdInfo::NumeroFlexible | IdInfo::NumeroFlexibleFinal => Some(self.nb_flexibles),
+            _ => None,
+        }
+    }
+
+    /// Sauvegarde du profil dans un fichier
+    /// # Errors
+    /// `SessionError::Io` si le fichier ne peut pas être écrit
+    pub fn save(self, path: &Path) -> Result<(), SessionError> {
+        fs::write(path, self.to_string())
+            .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))
+    }
+
+    /// Lecture d'un profil depuis un fichier
+    /// # Errors
+    /// `SessionError` si le fichier ne peut pas être lu ou s'il contient une erreur
+    pub fn load(path: &Path) -> Result<Self, SessionError> {
+        fs::read_to_string(path)
+            .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))?
+            .parse()
+    }
+}
+
+/// Nombre maximum de la ST2150 (toujours < 256)
+fn max_u8(nb_max: usize) -> u8 {
+    u8::try_from(nb_max).unwrap_or(u8::MAX)
+}
+
+impl Display for ProfilVehicule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{PROFIL_VEHICULE_HEADER}")?;
+        writeln!(f, "{PRODUITS_KEY} = {}", self.nb_produits)?;
+        writeln!(f, "{COMPARTIMENTS_KEY} = {}", self.nb_compartiments)?;
+        writeln!(f, "{FLEXIBLES_KEY} = {}", self.nb_flexibles)
+    }
+}
+
+impl FromStr for ProfilVehicule {
+    type Err = SessionError;
+
+    /// Décodage du contenu d'un fichier profil véhicule
+    /// (les valeurs absentes sont celles du profil par défaut)
+    fn from_str(txt: &str) -> Result<Self, Self::Err> {
+        let mut profil = ProfilVehicule::default();
+        for (index, line) in txt.lines().enumerate() {
+            let num_ligne = index + 1;
+            if is_session_comment(line) {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(SessionError::BadLine(num_ligne));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let illegal_value = |raison: String| {
+                SessionError::IllegalValue(num_ligne, key.to_string(), value.to_string(), raison)
+            };
+            let value = value
+                .parse::<u8>()
+                .map_err(|_| illegal_value("Nombre attendu".to_string()))?;
+            let (nb_produits, nb_compartiments, nb_flexibles) = match key {
+                PRODUITS_KEY => (value, profil.nb_compartiments, profil.nb_flexibles),
+                COMPARTIMENTS_KEY => (profil.nb_produits, value, profil.nb_flexibles),
+                FLEXIBLES_KEY => (profil.nb_produits, profil.nb_compartiments, value),
+                _ => return Err(SessionError::UnknownInfo(num_ligne, key.to_string())),
+            };
+            profil = ProfilVehicule::new(nb_produits, nb_compartiments, nb_flexibles)
+                .map_err(illegal_value)?;
+        }
+        Ok(profil)
+    }
+}
+
+impl Context {
+    /// Profil du véhicule
+    pub fn get_profil_vehicule(&self) -> ProfilVehicule {
+        self.profil_vehicule
+    }
+
+    /// Changement du profil du véhicule<br>
+    /// Les saisies des codes produits et des numéros de compartiment ou de flexible des
+    /// requêtes sont alors limitées à ce profil (les valeurs des réponses restent contrôlées
+    /// selon les limites de la ST2150)
+    pub fn set_profil_vehicule(&mut self, profil_vehicule: ProfilVehicule) {
+        self.profil_vehicule = profil_vehicule;
+    }
+
+    /// Profil du véhicule avec le nombre de compartiments reçu par le message 11
+    /// (None si le nombre de compartiments n'est pas défini ou incorrect)
+    pub fn profil_vehicule_from_message11(&self) -> Option<ProfilVehicule> {
+        let nb_compartiments = self.get_option_info_u8(IdInfo::NombreCompartiments)?;
+        self.profil_vehicule
+            .with_nb_compartiments(nb_compartiments)
+            .ok()
+    }
+
+    /// Indique si une information concerne le véhicule du profil actuel
+    pub fn is_info_relevant(&self, id_info: IdInfo) -> bool {
+        self.profil_vehicule.is_info_relevant(id_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profil_vehicule() {
+        let profil = ProfilVehicule::default();
+        assert_eq!(
+            (
+                profil.nb_produits(),
+                profil.nb_compartiments(),
+                profil.nb_flexibles()
+            ),
+            (
+                max_u8(NB_PRODUITS),
+                max_u8(NB_COMPARTIMENTS),
+                max_u8(NB_FLEXIBLES)
+            )
+        );
+        assert!(profil.is_info_relevant(IdInfo::QuantiteCompartiment(9)));
+
+        let profil: ProfilVehicule = "compartiments = 5\nflexibles = 2\n".parse().unwrap();
+        assert_eq!(profil, ProfilVehicule::new(16, 5, 2).unwrap());
+        assert!(profil.is_info_relevant(IdInfo::QuantiteCompartiment(5)));
+        assert!(!profil.is_info_relevant(IdInfo::QuantiteCompartiment(6)));
+        assert!(profil.is_info_relevant(IdInfo::CodeProduitFlexible2));
+
+        // Aller-retour dans le format du fichier
+        assert_eq!(profil.to_string().parse(), Ok(profil));
+
+        // Erreurs
+        assert_eq!(
+            "compartiments = 10".parse::<ProfilVehicule>(),
+            Err(SessionError::IllegalValue(
+                1,
+                "compartiments".to_string(),
+                "10".to_string(),
+                "Nombre de compartiments de 1 à 9".to_string()
+            ))
+        );
+        assert_eq!(
+            "# Profil\nroues = 6".parse::<ProfilVehicule>(),
+            Err(SessionError::UnknownInfo(2, "roues".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_context_profil_vehicule() {
+        let mut context = Context::default();
+        assert_eq!(context.profil_vehicule_from_message11(), None);
+
+        // Saisies limitées au profil du véhicule
+        context.set_profil_vehicule(ProfilVehicule::new(4, 5, 1).unwrap());
+        assert_eq!(
+            context.set_info_from_string(IdInfo::NumeroCompartiment, "6"),
+            Err("Valeur max. 5".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::NumeroCompartiment, "T"),
+            Ok(())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::CodeProduit, "5"),
+            Err("Valeur max. 4".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::NumeroFlexible, "2"),
+            Err("Valeur max. 1".to_string())
+        );
+        assert!(!context.is_info_relevant(IdInfo::CodeProduitFlexible2));
+
+        // Les réponses restent contrôlées selon les limites de la ST2150
+        assert_eq!(
+            context.set_info_from_string(IdInfo::CodeProduitCompartiment(1), "16"),
+            Ok(())
+        );
+
+        // Nombre de compartiments reçu par le message 11
+        context.set_info_u8(IdInfo::NombreCompartiments, 7);
+        assert_eq!(
+            context.profil_vehicule_from_message11(),
+            Some(ProfilVehicule::new(4, 7, 1).unwrap())
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Sauvegarde et restauration de toutes les informations du contexte dans un fichier 'session'
+//!
+//! Le fichier session est un fichier texte lisible et modifiable avec un simple éditeur :
+//!
+//! ```text
+//! # Session sim_ie
+//! CodeProduitCompartiment(3) = 2
+//! Predetermination = 5000
+//! ```
+//!
+//! * Une ligne par information définie du contexte : `IdInfo = valeur`
+//! * La valeur est la représentation 'textuelle' de l'information, sans perte de précision
+//!   (voir `get_info_to_session_string`)
+//! * Les lignes vides et les lignes commençant par `#` sont ignorées
+//! * Une information sans valeur (`IdInfo =`) reste non définie
+
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use super::descripteur::all_id_infos;
+use super::{Context, IdInfo, Provenance, TValue};
+use crate::langue::tr;
+
+/// Nom du fichier de la dernière session (sauvegardé automatiquement par l'IHM)
+pub const LAST_SESSION_FILE_NAME: &str = "sim_ie_last_session.txt";
+
+/// Entête d'un fichier session
+const SESSION_HEADER: &str = "# Session sim_ie (une ligne 'IdInfo = valeur' par information)";
+
+/// Erreurs possibles lors de la sauvegarde ou de la restauration d'une session
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// Erreur d'accès au fichier session (nom_fichier, erreur)
+    Io(String, String),
+
+    /// Ligne sans séparateur '=' (numéro_ligne)
+    BadLine(usize),
+
+    /// Information inconnue du contexte (numéro_ligne, nom_information)
+    UnknownInfo(usize, String),
+
+    /// Valeur refusée pour une information (numéro_ligne, nom_information, valeur, raison)
+    IllegalValue(usize, String, String, String),
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let txt = match self {
+            SessionError::Io(file_name, err) => {
+                format!("Erreur d'accès au fichier session '{file_name}' : {err}")
+            }
+            SessionError::BadLine(num_ligne) => {
+                format!("Ligne {num_ligne} incorrecte ('IdInfo = valeur' attendu)")
+            }
+            SessionError::UnknownInfo(num_ligne, nom) => {
+                format!("Ligne {num_ligne} : Information '{nom}' inconnue")
+            }
+            SessionError::IllegalValue(num_ligne, nom, valeur, raison) => {
+                format!(
+                    "Ligne {num_ligne} : Valeur '{valeur}' incorrecte pour l'information '{nom}' ({raison})"
+                )
+            }
+        };
+        write!(f, "{}", tr(&txt))
+    }
+}
+
+impl Error for SessionError {}
+
+/// Indique si une ligne d'un fichier session est à ignorer (ligne vide ou commentaire)
+pub(super) fn is_session_comment(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+impl Context {
+    /// Nom d'une information dans un fichier session (par exemple `LibelleTableProduits(3)`)
+    pub(super) fn session_name(id_info: IdInfo) -> String {
+        format!("{id_info:?}")
+    }
+
+    /// Représentation 'textuelle' d'une information dans un fichier session ou un preset
+    /// Contrairement à `get_info_to_string`, un nombre à virgule est écrit avec toutes ses
+    /// décimales (et non selon la précision d'affichage de l'information)
+    pub(super) fn get_info_to_session_string(&self, id_info: IdInfo) -> String {
+        match &self.get_inner_info(id_info).option_t_value {
+            Some(TValue::F32(value)) => value.to_string(),
+            _ => self.get_info_to_string(id_info, ""),
+        }
+    }
+
+    /// Remet toutes les informations du contexte à `None` (non définies)
+    pub fn clear_all_infos(&mut self) {
+        let id_infos: Vec<IdInfo> = self.hash_id_infos.keys().copied().collect();
+        self.clear_infos(&id_infos);
+    }
+
+    /// Représentation 'textuelle' de toutes les informations définies du contexte
+    /// (contenu d'un fichier session, trié par nom d'information)
+    pub fn to_session_string(&self) -> String {
+        let mut lines: Vec<String> = self
+            .hash_id_infos
+            .iter()
+            .filter(|(_, inner_info)| inner_info.option_t_value.is_some())
+            .map(|(id_info, _)| {
+                format!(
+                    "{} = {}",
+                    Self::session_name(*id_info),
+                    self.get_info_to_session_string(*id_info)
+                )
+            })
+            .collect();
+        lines.sort();
+
+        let mut session = SESSION_HEADER.to_string();
+        for line in lines {
+            session.push('\n');
+            session.push_str(&line);
+        }
+        session.push('\n');
+        session
+    }
+
+    /// Restauration de toutes les informations du contexte depuis le contenu d'un fichier session
+    /// Les informations absentes de la session sont remises à `None`
+    /// Le contexte n'est pas modifié si la session contient une erreur
+    /// # Errors
+    /// `SessionError` pour la première ligne incorrecte de la session
+    pub fn load_session_str(&mut self, session: &str) -> Result<(), SessionError> {
+        // Décodage de toutes les lignes avant toute modification du contexte
+        let mut lines = vec![];
+        for (index, line) in session.lines().enumerate() {
+            if is_session_comment(line) {
+                continue;
+            }
+            let (id_info, value) = self.parse_session_line(index + 1, line)?;
+            lines.push((index + 1, id_info, value));
+        }
+
+        // Valeurs contrôlées selon le contexte (profil du véhicule compris) et conservées
+        // seulement si toutes les valeurs sont acceptées
+        self.transaction(|context| {
+            context.clear_all_infos();
+            context.with_provenance(Provenance::Session, |context| {
+                for (num_ligne, id_info, value) in lines {
+                    context
+                        .set_info_from_string(id_info, value)
+                        .map_err(|raison| {
+                            SessionError::IllegalValue(
+                                num_ligne,
+                                Self::session_name(id_info),
+                                value.to_string(),
+                                raison,
+                            )
+                        })?;
+                }
+                Ok(())
+            })
+        })
+    }
+
+    /// Décodage et contrôle d'une ligne `IdInfo = valeur` d'un fichier session
+    /// # Errors
+    /// `SessionError` si la ligne est incorrecte
+    pub(super) fn parse_session_line<'a>(
+        &self,
+        num_ligne: usize,
+        line: &'a str,
+    ) -> Result<(IdInfo, &'a str), SessionError> {
+        let line = line.trim_end();
+        let Some((name, value)) = line.split_once('=') else {
+            return Err(SessionError::BadLine(num_ligne));
+        };
+        let name = name.trim();
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        let Some(id_info) = all_id_infos().find(|id_info| Self::session_name(*id_info) == name)
+        else {
+            return Err(SessionError::UnknownInfo(num_ligne, name.to_string()));
+        };
+
+        // Contrôle de la valeur sur un contexte de travail
+        let mut context = Context::default();
+        if let Err(raison) = context.set_info_from_string(id_info, value) {
+            return Err(SessionError::IllegalValue(
+                num_ligne,
+                name.to_string(),
+                value.to_string(),
+                raison,
+            ));
+        }
+
+        Ok((id_info, value))
+    }
+
+    /// Sauvegarde de toutes les informations définies du contexte dans un fichier session
+    /// # Errors
+    /// `SessionError::Io` si le fichier ne peut pas être écrit
+    pub fn save_session(&self, path: &Path) -> Result<(), SessionError> {
+        fs::write(path, self.to_session_string())
+            .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))
+    }
+
+    /// Restauration de toutes les informations du contexte depuis un fichier session
+    /// # Errors
+    /// `SessionError` si le fichier ne peut pas être lu ou s'il contient une erreur
+    pub fn load_session(&mut self, path: &Path) -> Result<(), SessionError> {
+        let session = fs::read_to_string(path)
+            .map_err(|e| SessionError::Io(path.display().to_string(), e.to_string()))?;
+        self.load_session_str(&session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{ProfilVehicule, U8OrT};
+
+    #[test]
+    fn test_session_round_trip() {
+        let mut context = Context::default();
+        context.set_info_bool(IdInfo::FinirFlexibleVide, true);
+        context.set_info_char(IdInfo::TypeDistribution, 'L');
+        context.set_info_u8(IdInfo::CodeProduitCompartiment(3), 2);
+        context.set_info_u8_or_t(IdInfo::NumeroCompartiment, U8OrT::T);
+        context.set_info_u32(IdInfo::Predetermination, 5000);
+        context.set_info_f32(IdInfo::TemperatureMoyen, -12.34);
+        context.set_info_f32(IdInfo::DebitInstant, 123.456);
+        context.set_info_string(IdInfo::LibelleTableProduits(2), "GAZOLE");
+
+        let session = context.to_session_string();
+        assert!(session.starts_with(SESSION_HEADER));
+        assert!(session.contains("\nCodeProduitCompartiment(3) = 2\n"));
+        assert!(session.contains("\nPredetermination = 5000\n"));
+        assert!(session.contains("\nFinirFlexibleVide = Oui\n"));
+
+        // Les informations absentes de la session sont remises à `None`
+        let mut context_restored = Context::default();
+        context_restored.set_info_u32(IdInfo::Totalisateur, 1234);
+        assert_eq!(context_restored.load_session_str(&session), Ok(()));
+        assert_eq!(context_restored.to_session_string(), session);
+        assert_eq!(
+            context_restored.get_option_info_u32(IdInfo::Totalisateur),
+            None
+        );
+        assert_eq!(
+            context_restored.get_option_info_u8_or_t(IdInfo::NumeroCompartiment),
+            Some(U8OrT::T)
+        );
+        assert_eq!(
+            context_restored.get_option_info_string(IdInfo::LibelleTableProduits(2)),
+            Some("GAZOLE".to_string())
+        );
+
+        // Pas de perte des décimales au-delà de la précision d'affichage
+        assert!(session.contains("\nTemperatureMoyen = -12.34\n"));
+        assert_eq!(
+            context_restored.get_option_info_f32(IdInfo::TemperatureMoyen),
+            Some(-12.34)
+        );
+        assert_eq!(
+            context_restored.get_option_info_f32(IdInfo::DebitInstant),
+            Some(123.456)
+        );
+        assert_eq!(
+            context_restored
+                .get_option_info_origine(IdInfo::Predetermination)
+                .map(|origine| origine.provenance.clone()),
+            Some(Provenance::Session)
+        );
+    }
+
+    #[test]
+    fn test_session_errors() {
+        let mut context = Context::default();
+        context.set_info_u32(IdInfo::Predetermination, 5000);
+
+        assert_eq!(
+            context.load_session_str("# Commentaire\n\nPredetermination 100\n"),
+            Err(SessionError::BadLine(3))
+        );
+        assert_eq!(
+            context.load_session_str("Inconnue = 1\n"),
+            Err(SessionError::UnknownInfo(1, "Inconnue".to_string()))
+        );
+        assert_eq!(
+            context.load_session_str("CodeProduit = 1\nPredetermination = abc\n"),
+            Err(SessionError::IllegalValue(
+                2,
+                "Predetermination".to_string(),
+                "abc".to_string(),
+                "Nombre entier positif attendu".to_string()
+            ))
+        );
+
+        // Le contexte n'est pas modifié en cas d'erreur
+        assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), None);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(5000)
+        );
+
+        // Valeur acceptée seule mais refusée par le profil du véhicule du contexte
+        context.set_profil_vehicule(ProfilVehicule::new(4, 5, 1).unwrap());
+        assert_eq!(
+            context.load_session_str("CodeProduit = 1\nNumeroCompartiment = 6\n"),
+            Err(SessionError::IllegalValue(
+                2,
+                "NumeroCompartiment".to_string(),
+                "6".to_string(),
+                "Valeur max. 5".to_string()
+            ))
+        );
+        assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), None);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(5000)
+        );
+
+        // Fichier inexistant
+        assert!(matches!(
+            context.load_session(Path::new("/fichier/inexistant.txt")),
+            Err(SessionError::Io(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_session_file() {
+        let path = std::env::temp_dir().join("sim_ie_test_session.txt");
+
+        let mut context = Context::default();
+        context.set_info_u16(IdInfo::Quantieme, 123);
+        assert_eq!(context.save_session(&path), Ok(()));
+
+        let mut context_restored = Context::default();
+        assert_eq!(context_restored.load_session(&path), Ok(()));
+        assert_eq!(
+            context_restored.get_option_info_u16(IdInfo::Quantieme),
+            Some(123)
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,209 @@
+//! Mise à jour 'atomique' des informations du contexte
+//!
+//! Pendant une mise à jour 'atomique' (voir `transaction`), les informations sont modifiées
+//! directement dans le contexte, mais l'état de chaque information avant sa première
+//! modification est mémorisé. En cas d'erreur, seules ces informations sont restaurées
+//! (valeur, origine et historique), sans copie du reste du contexte.
+//!
+//! Les mises à jour 'atomiques' peuvent être imbriquées : L'échec d'une mise à jour imbriquée
+//! ne restaure que les informations qu'elle a modifiées.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use super::{Context, HistoryEntry, IdInfo, Info, Origine, TValue, INFO_VIDE};
+
+/// État d'une information avant sa première modification lors d'une mise à jour 'atomique'
+#[derive(Clone, Debug)]
+pub(super) struct InfoAvantTransaction {
+    /// Valeur de l'information
+    option_t_value: Option<TValue>,
+
+    /// Origine de la valeur
+    option_origine: Option<Origine>,
+
+    /// Longueur de l'historique
+    history_len: usize,
+
+    /// Entrées les plus anciennes retirées depuis de l'historique (voir `HISTORY_MAX_LEN`),
+    /// dans l'ordre de leur retrait
+    history_retirees: Vec<HistoryEntry>,
+}
+
+impl InfoAvantTransaction {
+    /// État actuel d'une information
+    fn new(inner_info: &Info) -> Self {
+        Self {
+            option_t_value: inner_info.option_t_value.clone(),
+            option_origine: inner_info.option_origine.clone(),
+            history_len: inner_info.history.len(),
+            history_retirees: vec![],
+        }
+    }
+
+    /// Restauration de l'information dans cet état
+    fn restore(self, inner_info: &mut Info) {
+        inner_info.option_t_value = self.option_t_value;
+        inner_info.option_origine = self.option_origine;
+        // Les entrées ajoutées sont à la fin de l'historique et les entrées retirées au début
+        let history_len_conservee = self.history_len.saturating_sub(self.history_retirees.len());
+        inner_info.history.truncate(history_len_conservee);
+        for history_entry in self.history_retirees.into_iter().rev() {
+            inner_info.history.push_front(history_entry);
+        }
+    }
+}
+
+/// États des informations modifiées lors d'une mise à jour 'atomique'
+pub(super) type InfosAvantTransaction = HashMap<IdInfo, InfoAvantTransaction>;
+
+impl Context {
+    /// Mise à jour 'atomique' du contexte<br>
+    /// Les mises à jour ne sont conservées dans le contexte que si toutes réussissent
+    /// (les changements ne sont notifiés aux abonnés qu'à ce moment)
+    /// # Errors
+    /// Erreur retournée par `update` (les informations modifiées sont alors restaurées)
+    pub fn transaction<T, E>(
+        &mut self,
+        update: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let option_infos_avant_englobante = self
+            .option_infos_avant_transaction
+            .replace(InfosAvantTransaction::new());
+        let is_nested_transaction = option_infos_avant_englobante.is_some();
+        let nb_deferred_changes = self
+            .option_deferred_changes
+            .get_or_insert_with(Vec::new)
+            .len();
+
+        let result = update(self);
+
+        let infos_avant = std::mem::replace(
+            &mut self.option_infos_avant_transaction,
+            option_infos_avant_englobante,
+        )
+        .unwrap_or_default();
+        if result.is_err() {
+            for (id_info, info_avant) in infos_avant {
+                if let Some(inner_info) = self.hash_id_infos.get_mut(&id_info) {
+                    info_avant.restore(inner_info);
+                }
+            }
+            match &mut self.option_deferred_changes {
+                Some(deferred_changes) if is_nested_transaction => {
+                    deferred_changes.truncate(nb_deferred_changes);
+                }
+                _ => self.option_deferred_changes = None,
+            }
+        } else if let Some(infos_avant_englobante) = &mut self.option_infos_avant_transaction {
+            // Les informations modifiées le sont aussi pour la mise à jour englobante
+            for (id_info, info_avant) in infos_avant {
+                match infos_avant_englobante.entry(id_info) {
+                    Entry::Occupied(mut entry) => entry
+                        .get_mut()
+                        .history_retirees
+                        .extend(info_avant.history_retirees),
+                    Entry::Vacant(entry) => {
+                        entry.insert(info_avant);
+                    }
+                }
+            }
+        } else {
+            self.notify_deferred_changes();
+        }
+        result
+    }
+
+    /// Mémorise l'état d'une information avant sa première modification lors d'une mise à
+    /// jour 'atomique' en cours (sans effet si aucune mise à jour 'atomique' n'est en cours)
+    pub(super) fn save_info_avant_transaction(&mut self, id_info: IdInfo) {
+        if let Some(infos_avant) = &mut self.option_infos_avant_transaction {
+            if let Entry::Vacant(entry) = infos_avant.entry(id_info) {
+                let inner_info = self.hash_id_infos.get(&id_info).unwrap_or(&INFO_VIDE);
+                entry.insert(InfoAvantTransaction::new(inner_info));
+            }
+        }
+    }
+
+    /// Mémorise une entrée retirée de l'historique d'une information lors d'une mise à jour
+    /// 'atomique' en cours (sans effet si aucune mise à jour 'atomique' n'est en cours)
+    pub(super) fn save_history_retiree(&mut self, id_info: IdInfo, history_entry: HistoryEntry) {
+        if let Some(info_avant) = self
+            .option_infos_avant_transaction
+            .as_mut()
+            .and_then(|infos_avant| infos_avant.get_mut(&id_info))
+        {
+            info_avant.history_retirees.push(history_entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::HISTORY_MAX_LEN;
+
+    #[test]
+    fn test_transaction_history() {
+        let mut context = Context::default();
+        for value in 0..HISTORY_MAX_LEN {
+            context.set_info_u32(IdInfo::Totalisateur, u32::try_from(value).unwrap());
+        }
+        let history_before = context.get_info_history(IdInfo::Totalisateur).clone();
+
+        // L'historique est restauré, y compris ses entrées les plus anciennes
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u32(IdInfo::Totalisateur, 5000);
+            context.set_info_u32(IdInfo::Totalisateur, 6000);
+            assert_eq!(
+                context.get_info_history(IdInfo::Totalisateur).len(),
+                HISTORY_MAX_LEN
+            );
+            Err("Échec".to_string())
+        });
+        assert!(result.is_err());
+        assert_eq!(
+            context.get_info_history(IdInfo::Totalisateur),
+            &history_before
+        );
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            u32::try_from(HISTORY_MAX_LEN - 1).ok()
+        );
+    }
+
+    #[test]
+    fn test_transaction_nested() {
+        let mut context = Context::default();
+        context.set_info_u32(IdInfo::Totalisateur, 1234);
+
+        // Une mise à jour imbriquée en échec ne restaure que ses informations
+        let result: Result<(), String> = context.transaction(|context| {
+            context.set_info_u16(IdInfo::Quantieme, 12);
+            let nested: Result<(), String> = context.transaction(|context| {
+                context.set_info_u16(IdInfo::Quantieme, 13);
+                context.set_info_u32(IdInfo::Totalisateur, 5678);
+                Err("Échec".to_string())
+            });
+            assert!(nested.is_err());
+            assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), Some(12));
+            assert_eq!(
+                context.get_option_info_u32(IdInfo::Totalisateur),
+                Some(1234)
+            );
+
+            // Puis la mise à jour imbriquée réussie est annulée avec la mise à jour englobante
+            context.transaction(|context| {
+                context.set_info_u32(IdInfo::Totalisateur, 5678);
+                Ok::<(), String>(())
+            })?;
+            Err("Échec".to_string())
+        });
+        assert!(result.is_err());
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), None);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            Some(1234)
+        );
+    }
+}
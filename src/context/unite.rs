@@ -0,0 +1,289 @@
+//! Unités des informations du contexte (quantités, débits et températures)
+//!
+//! Une information 'physique' est associée à une grandeur (quantité, débit ou température).
+//! L'unité de cette grandeur dépend du type de compteur reçu par le message 30
+//! (`TypeCompteur` : 0 pour Vm, 1 pour Vb et 2 pour Masse) :
+//!
+//! | Grandeur    | Vm (par défaut) | Vb     | Masse |
+//! |-------------|-----------------|--------|-------|
+//! | Quantité    | L               | L15    | kg    |
+//! | Débit       | m³/h            | m³/h   | t/h   |
+//! | Température | °C              | °C     | °C    |
+//!
+//! Une saisie peut être suivie de l'unité de l'information ou d'une unité compatible
+//! (par exemple `12 m3` pour une quantité en litres est convertie en `12000`).
+
+use std::fmt::Display;
+
+use super::{truncate_str, Context, FormatInfo, IdInfo, TValue};
+
+/// Grandeur physique d'une information du contexte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Grandeur {
+    /// Quantité (en volume ou en masse selon le type de compteur)
+    Quantite,
+
+    /// Débit (en volume ou en masse par heure selon le type de compteur)
+    Debit,
+
+    /// Température
+    Temperature,
+}
+
+/// Unité d'une information du contexte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unite {
+    /// Litre (volume mesuré Vm)
+    Litre,
+
+    /// Litre à 15 °C (volume de base Vb)
+    Litre15,
+
+    /// Kilogramme (masse)
+    Kilogramme,
+
+    /// Mètre cube par heure (débit en volume)
+    MetreCubeHeure,
+
+    /// Tonne par heure (débit en masse)
+    TonneHeure,
+
+    /// Degré Celsius
+    DegreCelsius,
+}
+
+/// Toutes les unités
+const UNITES: [Unite; 6] = [
+    Unite::Litre,
+    Unite::Litre15,
+    Unite::Kilogramme,
+    Unite::MetreCubeHeure,
+    Unite::TonneHeure,
+    Unite::DegreCelsius,
+];
+
+impl Display for Unite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symboles()[0].0)
+    }
+}
+
+impl Unite {
+    /// Symboles acceptés en saisie pour cette unité, avec le facteur de conversion vers l'unité
+    /// (le premier symbole est celui de l'affichage)
+    fn symboles(self) -> &'static [(&'static str, f64)] {
+        match self {
+            Unite::Litre => &[("L", 1.0), ("m³", 1000.0), ("m3", 1000.0)],
+            Unite::Litre15 => &[("L15", 1.0), ("L", 1.0)],
+            Unite::Kilogramme => &[("kg", 1.0), ("t", 1000.0)],
+            Unite::MetreCubeHeure => &[("m³/h", 1.0), ("m3/h", 1.0), ("L/h", 0.001)],
+            Unite::TonneHeure => &[("t/h", 1.0), ("kg/h", 0.001)],
+            Unite::DegreCelsius => &[("°C", 1.0), ("C", 1.0)],
+        }
+    }
+
+    /// Facteur de conversion d'un symbole vers cette unité (None si le symbole n'est pas accepté)
+    fn facteur(self, symbole: &str) -> Option<f64> {
+        self.symboles()
+            .iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(symbole))
+            .map(|(_, facteur)| *facteur)
+    }
+}
+
+impl Context {
+    /// Unité d'une information du contexte (None pour une information sans unité)
+    /// L'unité d'une quantité ou d'un débit dépend du type de compteur (`TypeCompteur`)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub fn get_info_unite(&self, id_info: IdInfo) -> Option<Unite> {
+        let type_compteur = self.get_option_info_u8(IdInfo::TypeCompteur);
+        let unite = match (
+            Self::get_descripteur(id_info).option_grandeur?,
+            type_compteur,
+        ) {
+            (Grandeur::Quantite, Some(1)) => Unite::Litre15,
+            (Grandeur::Quantite, Some(2)) => Unite::Kilogramme,
+            (Grandeur::Quantite, _) => Unite::Litre,
+            (Grandeur::Debit, Some(2)) => Unite::TonneHeure,
+            (Grandeur::Debit, _) => Unite::MetreCubeHeure,
+            (Grandeur::Temperature, _) => Unite::DegreCelsius,
+        };
+        Some(unite)
+    }
+
+    /// Représentation 'textuelle' d'une information du contexte suivie de son unité
+    /// (par exemple `5000 L` ou `12.3 °C`) pour l'affichage
+    /// Un `string` est tronqué selon son nombre de caractères pour le message courant
+    pub fn get_info_to_string_with_unite(&self, id_info: IdInfo, output_none: &str) -> String {
+        let mut txt = self.get_info_to_string(id_info, output_none);
+        if let (Some(_), FormatInfo::String(width)) = (
+            &self.get_inner_info(id_info).option_t_value,
+            self.get_info_format(id_info),
+        ) {
+            txt = truncate_str(&txt, width).to_string();
+        }
+        match (
+            &self.get_inner_info(id_info).option_t_value,
+            self.get_info_unite(id_info),
+        ) {
+            (Some(_), Some(unite)) => format!("{txt} {unite}"),
+            _ => txt,
+        }
+    }
+
+    /// Représentation 'textuelle' d'une valeur d'une information du contexte
+    /// (avec le nombre de décimales de l'information pour un `F32`)
+    pub fn t_value_to_string(&self, id_info: IdInfo, t_value: &TValue) -> String {
+        match t_value {
+            TValue::F32(value) => {
+                format!("{value:.*}", Self::get_descripteur(id_info).precision)
+            }
+            _ => t_value.to_string(),
+        }
+    }
+
+    /// Saisie sans son unité, convertie dans l'unité de l'information si besoin
+    /// (la saisie est inchangée si elle n'est pas un nombre suivi d'une unité connue)
+    /// # Errors
+    /// Raison du refus si l'unité n'est pas compatible avec celle de l'information
+    pub(super) fn strip_unite(&self, id_info: IdInfo, input: &str) -> Result<String, String> {
+        let Some(unite) = self.get_info_unite(id_info) else {
+            return Ok(input.to_string());
+        };
+        let input = input.trim();
+        let index_symbole = input
+            .find(|c: char| !(c.is_ascii_digit() || ['-', '+', '.', ' '].contains(&c)))
+            .unwrap_or(input.len());
+        let (nombre, symbole) = input.split_at(index_symbole);
+        let nombre = nombre.trim_end();
+        if nombre.is_empty() {
+            return Ok(input.to_string());
+        }
+        if symbole.is_empty() {
+            return Ok(nombre.to_string());
+        }
+        let Some(facteur) = unite.facteur(symbole) else {
+            if UNITES.iter().any(|autre| autre.facteur(symbole).is_some()) {
+                return Err(format!("Unité '{unite}' attendue"));
+            }
+            return Ok(input.to_string());
+        };
+        if facteur == 1.0 {
+            return Ok(nombre.to_string());
+        }
+
+        // Conversion dans l'unité de l'information
+        let value = match nombre.parse::<f64>() {
+            Ok(value) if value.is_finite() => value * facteur,
+            _ => return Err("Nombre attendu".to_string()),
+        };
+        match self.get_info_format(id_info) {
+            FormatInfo::F32 => Ok(value.to_string()),
+            _ => {
+                let value_round = value.round();
+                if (value - value_round).abs() > 1e-6 {
+                    return Err(format!("Nombre entier de '{unite}' attendu"));
+                }
+                Ok(format!("{value_round:.0}"))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unite() {
+        let mut context = Context::default();
+        assert_eq!(context.get_info_unite(IdInfo::CodeProduit), None);
+        assert_eq!(
+            context.get_info_unite(IdInfo::TemperatureInstant),
+            Some(Unite::DegreCelsius)
+        );
+
+        // Unités selon le type de compteur
+        for (type_compteur, quantite, debit) in [
+            (0, "5000 L", "123.4 m³/h"),
+            (1, "5000 L15", "123.4 m³/h"),
+            (2, "5000 kg", "123.4 t/h"),
+        ] {
+            context.set_info_u8(IdInfo::TypeCompteur, type_compteur);
+            context.set_info_u32(IdInfo::QuantitePrincipale, 5000);
+            context.set_info_f32(IdInfo::DebitInstant, 123.4);
+            assert_eq!(
+                context.get_info_to_string_with_unite(IdInfo::QuantitePrincipale, "?"),
+                quantite
+            );
+            assert_eq!(
+                context.get_info_to_string_with_unite(IdInfo::DebitInstant, "?"),
+                debit
+            );
+        }
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::TemperatureMoyen, "?"),
+            "?"
+        );
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::CodeProduit, "?"),
+            "?"
+        );
+    }
+
+    #[test]
+    fn test_saisie_avec_unite() {
+        let mut context = Context::default();
+
+        // Unité de l'information ou unité compatible (avec conversion)
+        for (input, prede) in [
+            ("5000", 5000),
+            ("4000 L", 4000),
+            ("3l", 3),
+            ("1.5 m3", 1500),
+        ] {
+            assert_eq!(
+                context.set_info_from_string(IdInfo::Predetermination, input),
+                Ok(())
+            );
+            assert_eq!(
+                context.get_option_info_u32(IdInfo::Predetermination),
+                Some(prede)
+            );
+        }
+        assert_eq!(
+            context.set_info_from_string(IdInfo::TemperatureInstant, "-12.3 °C"),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_option_info_f32(IdInfo::TemperatureInstant),
+            Some(-12.3)
+        );
+
+        // Unité incompatible ou conversion impossible
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "5000 kg"),
+            Err("Unité 'L' attendue".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "0.0005 m3"),
+            Err("Nombre entier de 'L' attendu".to_string())
+        );
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "200 m3"),
+            Err("Valeur max. 99999".to_string())
+        );
+
+        // Compteur en masse
+        context.set_info_u8(IdInfo::TypeCompteur, 2);
+        assert_eq!(
+            context.set_info_from_string(IdInfo::Predetermination, "2.5 t"),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(2500)
+        );
+    }
+}
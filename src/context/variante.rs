@@ -0,0 +1,152 @@
+//! Formats et libellés des informations spécifiques à certains messages
+//!
+//! Une même information peut avoir un format différent selon l'édition de la ST2150 du message
+//! utilisé. Par exemple, le libellé produit a 5 caractères dans la réponse au message 32
+//! (édition A) et 10 caractères dans la réponse au message 38 (édition E). De même pour les
+//! tables des produits des messages 33 (édition A) et 35 (édition B).
+//!
+//! Le message courant du contexte (message sélectionné dans l'IHM ou message de la vacation en
+//! cours) détermine le format et le libellé utilisés pour la saisie, le codage, le décodage et
+//! l'affichage de ces informations.
+
+use super::{Context, FormatInfo, IdInfo};
+
+/// Format et libellé d'une information pour certains messages
+#[derive(Clone, Debug)]
+pub(super) struct VarianteInfo {
+    /// Numéros des messages concernés
+    pub(super) message_nums: &'static [u8],
+
+    /// Format de l'information pour ces messages
+    pub(super) format_info: FormatInfo,
+
+    /// Libellé de l'information pour ces messages (en français, clé de sa traduction)
+    /// `{}` est remplacé par l'index d'une information indexée
+    pub(super) label: &'static str,
+}
+
+impl VarianteInfo {
+    /// Constructeur
+    pub(super) const fn new(
+        message_nums: &'static [u8],
+        format_info: FormatInfo,
+        label: &'static str,
+    ) -> Self {
+        Self {
+            message_nums,
+            format_info,
+            label,
+        }
+    }
+}
+
+impl Context {
+    /// Message courant pour le format et le libellé des informations (None si aucun)
+    pub fn get_message_courant(&self) -> Option<u8> {
+        self.option_message_courant
+    }
+
+    /// Changement du message courant pour le format et le libellé des informations
+    pub fn set_message_courant(&mut self, option_message_num: Option<u8>) {
+        self.option_message_courant = option_message_num;
+    }
+
+    /// Format et libellé spécifiques d'une information pour le message courant
+    /// (None si l'information n'a pas de format spécifique pour ce message)
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu
+    pub(super) fn get_variante_info(&self, id_info: IdInfo) -> Option<&'static VarianteInfo> {
+        let message_num = self.option_message_courant?;
+        Self::get_descripteur(id_info)
+            .variantes
+            .iter()
+            .find(|variante| variante.message_nums.contains(&message_num))
+    }
+
+    /// Nombre de caractères d'une information de type `string` pour le message courant
+    /// # panics
+    /// panic! si l'`IdInfo` n'est pas reconnu ou n'est pas un string
+    pub fn get_info_string_width(&self, id_info: IdInfo) -> usize {
+        match self.get_info_format(id_info) {
+            FormatInfo::String(width) => width,
+            _ => panic!("{id_info:?} n'est pas un string"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variante_libelle_produit() {
+        let mut context = Context::default();
+
+        // Par défaut, le format de la ST2150 la plus récente
+        assert_eq!(context.get_info_string_width(IdInfo::LibelleProduit), 10);
+        assert_eq!(
+            context.set_info_from_string(IdInfo::LibelleProduit, "GAZOLE NON ROUTIER"),
+            Ok(())
+        );
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleProduit),
+            Some("GAZOLE NON ROUTIER".to_string())
+        );
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::LibelleProduit, "?"),
+            "GAZOLE NON"
+        );
+
+        // Libellé court du message 32 (édition A)
+        context.set_message_courant(Some(32));
+        assert_eq!(context.get_info_string_width(IdInfo::LibelleProduit), 5);
+        assert_eq!(
+            context.get_info_label(IdInfo::LibelleProduit),
+            "Libellé produit (court)"
+        );
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::LibelleProduit, "?"),
+            "GAZOL"
+        );
+        context.set_info_string(IdInfo::LibelleProduit, "GAZOLE SUP");
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::LibelleProduit, "?"),
+            "GAZOL"
+        );
+
+        // Libellé long du message 38 (édition E)
+        context.set_message_courant(Some(38));
+        assert_eq!(context.get_info_string_width(IdInfo::LibelleProduit), 10);
+        assert_eq!(
+            context.get_info_label(IdInfo::LibelleProduit),
+            "Libellé produit"
+        );
+
+        // Le libellé saisi avec le message 32 n'est pas perdu
+        assert_eq!(
+            context.get_option_info_string(IdInfo::LibelleProduit),
+            Some("GAZOLE SUP".to_string())
+        );
+        assert_eq!(
+            context.get_info_to_string_with_unite(IdInfo::LibelleProduit, "?"),
+            "GAZOLE SUP"
+        );
+    }
+
+    #[test]
+    fn test_variante_tables_produits() {
+        let mut context = Context::default();
+        for (message_num, width) in [(33, 5), (35, 10)] {
+            context.set_message_courant(Some(message_num));
+            assert_eq!(
+                context.get_info_string_width(IdInfo::LibelleTableProduits(1)),
+                width
+            );
+        }
+        context.set_message_courant(Some(33));
+        assert_eq!(
+            context.get_info_label(IdInfo::LibelleTableProduits(3)),
+            "Libellé table produit #3 (court)"
+        );
+    }
+}
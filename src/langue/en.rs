@@ -0,0 +1,437 @@
+//! Catalogue des traductions en anglais (texte français, traduction anglaise)
+//!
+//! Les clés avec des paramètres `{}` sont essayées dans l'ordre du catalogue : Une clé plus
+//! spécifique doit donc précéder une clé plus générale qui pourrait lui correspondre.
+
+/// Catalogue des traductions en anglais
+pub(super) const CATALOGUE: &[(&str, &str)] = &[
+    /* ----------------------------------- */
+    /*  Libellés des informations (Context) */
+    /* ----------------------------------- */
+    ("Non défini", "Undefined"),
+    ("Acquit message", "Message acknowledged"),
+    ("Refus message", "Message refused"),
+    ("En mesurage", "Measuring"),
+    ("En Code défaut", "Fault code"),
+    ("Arrêt intermédiaire", "Intermediate stop"),
+    ("Forçage petit débit", "Low flow forced"),
+    ("Mode connecté", "Connected mode"),
+    ("Totalisateur", "Totalizer"),
+    ("Débit instantané", "Instantaneous flow"),
+    ("Quantité", "Quantity"),
+    ("Quantité secondaire", "Secondary quantity"),
+    (
+        "Quantité Température instantanée",
+        "Instantaneous temperature",
+    ),
+    ("Température moyenne", "Average temperature"),
+    ("Prédétermination", "Preset quantity"),
+    ("Code produit", "Product code"),
+    ("Index sans remise à zéro", "Non-resettable index"),
+    ("Index journalier", "Daily index"),
+    ("Quantième", "Day of year"),
+    ("Heure de début", "Start time"),
+    ("Heure de fin", "End time"),
+    ("Identification TAG", "TAG identification"),
+    ("Référence et immatriculation", "Reference and registration"),
+    ("Version du logiciel", "Software version"),
+    ("Date et Heure", "Date and time"),
+    (
+        "Type de compteur (0:Vm, 1:Vb, 2:Masse)",
+        "Meter type (0:Vm, 1:Vb, 2:Mass)",
+    ),
+    (
+        "Nombre de mesurages pour un quantième",
+        "Number of measurements for a day",
+    ),
+    ("Libellé produit", "Product label"),
+    ("Libellé produit (court)", "Product label (short)"),
+    ("Nombre de fractionnements", "Number of splits"),
+    (
+        "Libellé table produit #{} (court)",
+        "Product table label #{} (short)",
+    ),
+    ("Libellé table produit #{}", "Product table label #{}"),
+    ("Libellé du code produit", "Product code label"),
+    ("Index fractionnement", "Split index"),
+    (
+        "(A)nticipation purge, li(B)ération, (C)hargement, pré(D)é, (L)ibre, (P)urge, (T)ransfert, (V)idange",
+        "(A) anticipated purge, (B) release, (C) loading, (D) preset, (L) free, (P) purge, (T) transfer, (V) emptying",
+    ),
+    ("Date", "Date"),
+    ("Heure (HH:MM:SS)", "Time (HH:MM:SS)"),
+    ("Heure (HH:MM)", "Time (HH:MM)"),
+    ("Nombre d'événements", "Number of events"),
+    ("Données techniques événement", "Event technical data"),
+    ("Libellé événement", "Event label"),
+    ("Code produit cpt #{}", "Product code cpt #{}"),
+    ("Quantité cpt #{}", "Quantity cpt #{}"),
+    ("Quantité totale cpts", "Total quantity cpts"),
+    ("Nombre de compartiments", "Number of compartments"),
+    ("Code produit dans le collecteur", "Product code in manifold"),
+    (
+        "Code produit dans la partie commune",
+        "Product code in common section",
+    ),
+    ("Code produit dans le flexible #1", "Product code in hose #1"),
+    ("Code produit dans le flexible #2", "Product code in hose #2"),
+    (
+        "Code erreur (1:Non supporté, 2:En opération)",
+        "Error code (1:Not supported, 2:In operation)",
+    ),
+    ("Code produit final", "Final product code"),
+    (
+        "No de compartiment (ou T pour remorque)",
+        "Compartment no. (or T for trailer)",
+    ),
+    ("Présence d'une remorque", "Trailer present"),
+    ("No de compartiment final (ou T)", "Final compartment no. (or T)"),
+    ("Ordre des compartiments", "Compartment order"),
+    ("Numéro de flexible", "Hose number"),
+    ("Numéro de flexible final", "Final hose number"),
+    ("Finir flexible vide", "Finish with empty hose"),
+    ("Oui", "Yes"),
+    ("Non", "No"),
+    /* ------------------------------------ */
+    /*  Titres des messages (`message_str`) */
+    /* ------------------------------------ */
+    ("Signe de vie", "Heartbeat"),
+    ("Informations instantanées", "Instantaneous information"),
+    ("État cargaison", "Cargo status"),
+    ("Présélection", "Preselection"),
+    (
+        "Dernier mesurage, demande de solde",
+        "Last measurement, balance request",
+    ),
+    ("Information compteur", "Meter information"),
+    (
+        "Nb mesurages pour un quantième",
+        "Nb of measurements for a day",
+    ),
+    ("Relevé mesurage", "Measurement record"),
+    ("Tables des produits (court)", "Product tables (short)"),
+    ("Relevé fractionnement", "Split record"),
+    ("Tables des produits (long)", "Product tables (long)"),
+    ("Relevé d'un événement", "Event record"),
+    ("Mise à jour du plan", "Loading plan update"),
+    ("Relevé mesurage étendu", "Extended measurement record"),
+    ("Synchronisation heure", "Time synchronisation"),
+    ("Prédétermination pompée", "Pumped preset"),
+    (
+        "Prédétermination pompée multi-compartiments",
+        "Pumped preset, multi-compartment",
+    ),
+    ("Prédétermination pompée libre", "Free pumped preset"),
+    (
+        "Prédétermination pompée libre multi-compartiments",
+        "Free pumped preset, multi-compartment",
+    ),
+    ("Purge", "Purge"),
+    (
+        "Prédétermination avec anticipation de purge",
+        "Preset with anticipated purge",
+    ),
+    (
+        "Prédé. avec anticipation de purge multi-comparts",
+        "Preset with anticipated purge, multi-cpts",
+    ),
+    ("Prédétermination gravitaire", "Gravity preset"),
+    ("Prédétermination gravitaire libre", "Free gravity preset"),
+    ("Transfert compartiment", "Compartment transfer"),
+    (
+        "Chargement produit vers compartiment",
+        "Product loading into compartment",
+    ),
+    ("Libération (vidange collecteur)", "Release (manifold draining)"),
+    ("Vidage gravitaire", "Gravity emptying"),
+    /* ------------------------------------ */
+    /*  Erreurs du protocole (ProtocolError) */
+    /* ------------------------------------ */
+    ("Pas de réponse du calculateur", "No reply from the computer"),
+    (
+        "Longueur incorrecte du message ({}/{} cars)",
+        "Incorrect message length ({}/{} chars)",
+    ),
+    (
+        "Checksum incorrect du message (0x{} vs 0x{} attendu)",
+        "Incorrect message checksum (0x{} vs 0x{} expected)",
+    ),
+    ("Pas de 'STX' en début de message", "No 'STX' at start of message"),
+    ("Pas de 'ETX' en fin de message", "No 'ETX' at end of message"),
+    (
+        "Réponse avec un message 50 d'erreur : {}",
+        "Reply with an error message 50: {}",
+    ),
+    (
+        "Numéro incorrect du message ({} vs {} attendu)",
+        "Incorrect message number ({} vs {} expected)",
+    ),
+    (
+        "Séparateur de champ attendu en position {} dans le message",
+        "Field separator expected at position {} in the message",
+    ),
+    ("Encodage trame impossible : {}", "Frame encoding impossible: {}"),
+    (
+        "Contenu '0x{}' incorrect pour décodage en {} du champ {}",
+        "Incorrect content '0x{}' for {} decoding of field {}",
+    ),
+    (
+        "Erreur lors de la conversion en {} du champ {}",
+        "Error during {} conversion of field {}",
+    ),
+    ("Champ #{} absent du message", "Field #{} missing from the message"),
+    (
+        "Nombre incorrect de champs du message ({}/{} champs)",
+        "Incorrect number of fields in the message ({}/{} fields)",
+    ),
+    (
+        "Longueur incorrecte du champ #{} ({}/{} cars)",
+        "Incorrect length of field #{} ({}/{} chars)",
+    ),
+    (
+        "Valeur incorrecte du champ '{}'={} : {}",
+        "Incorrect value of field '{}'={}: {}",
+    ),
+    ("Valeur non renseignée du champ '{}'", "Missing value for field '{}'"),
+    (
+        "Valeur refusée du champ '{}' : {}",
+        "Rejected value for field '{}': {}",
+    ),
+    (
+        "Exécution à blanc : Requête non envoyée",
+        "Dry run: Request not sent",
+    ),
+    ("Valeur non renseignée", "Missing value"),
+    (
+        "Code produit final sans code produit",
+        "Final product code without product code",
+    ),
+    (
+        "Compartiment final sans compartiment",
+        "Final compartment without compartment",
+    ),
+    ("Flexible final sans flexible", "Final hose without hose"),
+    ("Compartiment {} absent du véhicule", "Compartment {} not on the vehicle"),
+    ("Compartiment {} en double", "Compartment {} duplicated"),
+    (
+        "La valeur '{}' pour '{}' n'est pas dans le domaine des valeurs possibles : '{}..{}'",
+        "Value '{}' for '{}' is not within the range of possible values: '{}..{}'",
+    ),
+    ("Encoder {} en hexa sur 0 car ?", "Encode {} in hexa on 0 char?"),
+    (
+        "Encoder {} en hexa sur {} cars ?",
+        "Encode {} in hexa on {} chars?",
+    ),
+    ("Encoder {} sur 0 car ?", "Encode {} on 0 char?"),
+    ("Encoder {} sur {} cars ?", "Encode {} on {} chars?"),
+    ("{} n'est pas ASCII", "{} is not ASCII"),
+    ("Caractère {} non encodable en {} dans un champ", "Character {} cannot be encoded in {} in a field"),
+    ("code produit", "product code"),
+    ("numéro compartiment final", "final compartment number"),
+    ("numéro compartiment", "compartment number"),
+    ("numéro flexible", "hose number"),
+    ("'0' ou '1'", "'0' or '1'"),
+    ("Strict", "Strict"),
+    ("Tolérant", "Lenient"),
+    /* ------------------------------------ */
+    /*  Erreurs de saisie (Context)         */
+    /* ------------------------------------ */
+    ("Format incorrect", "Incorrect format"),
+    ("'Oui' ou 'Non' attendu", "'Yes' or 'No' expected"),
+    ("Un seul caractère attendu", "A single character expected"),
+    ("Numéro ou 'T' attendu", "Number or 'T' expected"),
+    ("'T' ou 'F' attendu", "'T' or 'F' expected"),
+    ("Nombre attendu", "Number expected"),
+    ("Nombre entier positif attendu", "Positive integer expected"),
+    ("Nombre trop grand", "Number too large"),
+    ("Valeur entre {} et {}", "Value between {} and {}"),
+    ("Valeur min. {}", "Min. value {}"),
+    (
+        "Valeur max. {} (réponse message {})",
+        "Max. value {} (message {} reply)",
+    ),
+    ("Valeur max. {}", "Max. value {}"),
+    ("Unité '{}' attendue", "Unit '{}' expected"),
+    ("Nombre entier de '{}' attendu", "Whole number of '{}' expected"),
+    (
+        "Date JJ/MM/AAAA ou AAMMJJ attendue",
+        "Date DD/MM/YYYY or YYMMDD expected",
+    ),
+    (
+        "Date et heure JJ/MM/AAAA HH:MM:SS ou AAMMJJHHMMSS attendues",
+        "Date and time DD/MM/YYYY HH:MM:SS or YYMMDDHHMMSS expected",
+    ),
+    (
+        "Heure HH:MM, HH:MM:SS, HHMM ou HHMMSS attendue",
+        "Time HH:MM, HH:MM:SS, HHMM or HHMMSS expected",
+    ),
+    ("Date AAMMJJ attendue", "Date YYMMDD expected"),
+    ("Année de {} à {}", "Year from {} to {}"),
+    ("Mois de 01 à 12", "Month from 01 to 12"),
+    ("Jour de 01 à {}", "Day from 01 to {}"),
+    ("Heure de 00 à 23", "Hour from 00 to 23"),
+    ("Minute de 00 à 59", "Minute from 00 to 59"),
+    ("Seconde de 00 à 59", "Second from 00 to 59"),
+    ("Nombre de {} de 1 à {}", "Number of {} from 1 to {}"),
+    ("produits", "products"),
+    ("compartiments", "compartments"),
+    ("flexibles", "hoses"),
+    ("Numéro de message attendu", "Message number expected"),
+    (
+        "Langue '{}' inconnue ('fr' ou 'en' attendu)",
+        "Unknown language '{}' ('fr' or 'en' expected)",
+    ),
+    /* ----------------------------------------------- */
+    /*  Sessions, presets et origine des valeurs        */
+    /* ----------------------------------------------- */
+    (
+        "Erreur d'accès au fichier session '{}' : {}",
+        "Session file access error '{}': {}",
+    ),
+    (
+        "Ligne {} incorrecte ('IdInfo = valeur' attendu)",
+        "Line {} incorrect ('IdInfo = value' expected)",
+    ),
+    (
+        "Ligne {} : Information '{}' inconnue",
+        "Line {}: Unknown information '{}'",
+    ),
+    (
+        "Ligne {} : Valeur '{}' incorrecte pour l'information '{}' ({})",
+        "Line {}: Incorrect value '{}' for information '{}' ({})",
+    ),
+    ("Saisie manuelle", "Manual input"),
+    ("Réponse message {}", "Reply to message {}"),
+    ("Rejeu vacation #{}", "Vacation #{} run again"),
+    (" (vacation précédente)", " (previous exchange)"),
+    (
+        "Horodatage (UTC);Vacation;Provenance;Information;Valeur;Unité",
+        "Timestamp (UTC);Exchange;Source;Information;Value;Unit",
+    ),
+    /* ----- */
+    /*  IHM  */
+    /* ----- */
+    (
+        "Simulateur v{} - Informatique Embarquée ALMA",
+        "Simulator v{} - ALMA On-board Computer",
+    ),
+    ("(Pas de champ)", "(No field)"),
+    ("Nom du preset", "Preset name"),
+    ("Nouveau preset", "New preset"),
+    ("(Pas d'information)", "(No information)"),
+    ("RÉPONSE : NACK !!!", "REPLY: NACK !!!"),
+    ("ERREUR : {} !!!", "ERROR: {} !!!"),
+    ("Exporter l'historique (CSV)", "Export history (CSV)"),
+    ("Historique : {} ({} valeurs)", "History: {} ({} values)"),
+    ("Exporter (CSV)", "Export (CSV)"),
+    ("Fermer", "Close"),
+    (
+        "Run Message {} ({}) sur le port {}",
+        "Run Message {} ({}) on port {}",
+    ),
+    ("Message '{}' sur le port {} : {}", "Message '{}' on port {}: {}"),
+    ("Requête impossible", "Request not possible"),
+    ("Run Message {} avant", "Run Message {} first"),
+    ("Prérequis automatiques", "Automatic prerequisites"),
+    ("Aperçu de la requête :", "Request preview:"),
+    ("Editions ST2150 : ", "ST2150 editions: "),
+    ("Décodage tolérant", "Lenient decoding"),
+    ("Langue : ", "Language: "),
+    ("Caractères : ", "Characters: "),
+    ("Session : ", "Session: "),
+    ("Fichier session", "Session file"),
+    ("Charger", "Load"),
+    ("Sauver", "Save"),
+    (
+        "Véhicule : {} produits, {} compartiments, {} flexibles",
+        "Vehicle: {} products, {} compartments, {} hoses",
+    ),
+    (
+        "Compartiments depuis message 11",
+        "Compartments from message 11",
+    ),
+    ("(Pas de requête)", "(No request)"),
+    ("Requête :", "Request:"),
+    ("(Pas de réponse)", "(No reply)"),
+    ("Réponse :", "Reply:"),
+    ("(Pas d'erreur)", "(No error)"),
+    ("/!\\ ERREUR : {} /!\\", "/!\\ ERROR: {} /!\\"),
+    ("Avertissement : {}", "Warning: {}"),
+    ("Journal des vacations ({})", "Vacation log ({})"),
+    (
+        "Vacation #{} : Message {} ({})",
+        "Vacation #{}: Message {} ({})",
+    ),
+    ("Durée {} ms : {}", "Duration {} ms: {}"),
+    ("OK ({} avertissements)", "OK ({} warnings)"),
+    ("Rejouer la vacation", "Run vacation again"),
+    ("Session '{}' chargée", "Session '{}' loaded"),
+    ("Session '{}' sauvegardée", "Session '{}' saved"),
+    ("Preset '{}' appliqué", "Preset '{}' applied"),
+    ("Preset '{}' sauvegardé", "Preset '{}' saved"),
+    ("Preset '{}' : {}", "Preset '{}': {}"),
+    ("Nom du preset à renseigner", "Preset name required"),
+    ("Historique exporté dans '{}'", "History exported to '{}'"),
+    (
+        "Erreur d'export de l'historique '{}' : {}",
+        "History export error '{}': {}",
+    ),
+    ("Profil sauvegardé dans '{}'", "Profile saved to '{}'"),
+    (
+        "Nombre de compartiments inconnu (message 11 à exécuter)",
+        "Unknown number of compartments (run message 11)",
+    ),
+    /* --------------------------------- */
+    /*  Ligne de commande                */
+    /* --------------------------------- */
+    ("Erreur option inconnue : '{}'", "Error, unknown option: '{}'"),
+    ("Erreur options inconnues : '{}'", "Error, unknown options: '{}'"),
+    ("Erreur session : {}", "Session error: {}"),
+    ("Erreur dernière session : {}", "Last session error: {}"),
+    ("Erreur langue : {}", "Language error: {}"),
+    (
+        "Désolé, pas de port série sur cette machine :(",
+        "Sorry, no serial port on this machine :(",
+    ),
+    ("Ports séries de cette machine :", "Serial ports of this machine:"),
+    (
+        "Simulateur d'informatique embarquée v{} - ALMA 2023-2024.",
+        "Embedded computer simulator v{} - ALMA 2023-2024.",
+    ),
+    ("Usage en mode graphique :", "Graphical mode usage:"),
+    (
+        "Pour une machine Windows avec un port série 'COM1'",
+        "For a Windows machine with a 'COM1' serial port",
+    ),
+    (
+        "Syntaxe de Windows après COM9 (étrange, mais bon...)",
+        "Windows syntax after COM9 (strange, but well...)",
+    ),
+    ("Pour une machine Linux", "For a Linux machine"),
+    (
+        "Options de session (après le port série) :",
+        "Session options (after the serial port):",
+    ),
+    (
+        "Restaure (et sauvegarde) la session de ce fichier",
+        "Restores (and saves) the session of this file",
+    ),
+    ("Restaure la dernière session", "Restores the last session"),
+    (
+        "Langue de l'outil (en premier, ou variable d'environnement SIM_IE_LANG) :",
+        "Tool language (first, or SIM_IE_LANG environment variable):",
+    ),
+    (
+        "Outil en anglais ('fr' par défaut)",
+        "English tool ('fr' by default)",
+    ),
+    ("Usage en mode terminal :", "Terminal mode usage:"),
+    ("Pour ce message d'aide", "For this help message"),
+    ("sim_ie --ports ou --list", "sim_ie --ports or --list"),
+    ("Liste des ports de la machine", "List of the machine serial ports"),
+    /* ---------------------------------------------------- */
+    /*  Clés très générales (à garder en fin de catalogue)  */
+    /* ---------------------------------------------------- */
+    ("{} il y a {}", "{}, {} ago"),
+    ("{} = {} : {}", "{} = {}: {}"),
+];
@@ -0,0 +1,233 @@
+//! Localisation des libellés, des erreurs et de l'IHM
+//!
+//! Tous les textes de l'outil sont écrits en français dans le code : Ce texte français est la
+//! clé de sa traduction dans le catalogue d'une autre langue (voir `en.rs` pour l'anglais).
+//! Un texte absent du catalogue est présenté en français.
+//!
+//! Une clé du catalogue peut contenir des paramètres `{}` pour traduire un texte déjà formaté.
+//! Par exemple, la clé `Quantité cpt #{}` traduit `Quantité cpt #3` en `Quantity cpt #3`.
+//! Chaque paramètre est lui-même traduit s'il est présent dans le catalogue (cas d'un libellé
+//! cité dans un message d'erreur).
+//!
+//! La langue de l'outil est choisie par l'option `--lang` de la ligne de commande, par la
+//! variable d'environnement `SIM_IE_LANG` ou dans l'IHM. Par défaut, la langue est le français.
+
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+mod en;
+
+/// Nom de la variable d'environnement pour choisir la langue (`fr` ou `en`)
+pub const LANGUE_ENV_VAR: &str = "SIM_IE_LANG";
+
+/// Langues de l'outil
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Langue {
+    /// Français (langue des libellés dans le code)
+    #[default]
+    Francais,
+
+    /// Anglais (voir le catalogue `en`)
+    Anglais,
+}
+
+/// Toutes les langues de l'outil
+pub const LANGUES: [Langue; 2] = [Langue::Francais, Langue::Anglais];
+
+impl Display for Langue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Langue::Francais => write!(f, "Français"),
+            Langue::Anglais => write!(f, "English"),
+        }
+    }
+}
+
+impl FromStr for Langue {
+    type Err = String;
+
+    /// Décodage d'une langue : `fr` ou `en` (majuscules ou minuscules),
+    /// éventuellement suivi d'un pays comme dans la variable d'environnement `LANG` (`en_US.UTF-8`)
+    fn from_str(txt: &str) -> Result<Self, Self::Err> {
+        let code = txt.get(..2).unwrap_or_default().to_ascii_lowercase();
+        match code.as_str() {
+            "fr" => Ok(Langue::Francais),
+            "en" => Ok(Langue::Anglais),
+            _ => Err(format!("Langue '{txt}' inconnue ('fr' ou 'en' attendu)")),
+        }
+    }
+}
+
+impl Langue {
+    /// Catalogue des traductions (texte français, traduction) de cette langue
+    fn catalogue(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Langue::Francais => &[],
+            Langue::Anglais => en::CATALOGUE,
+        }
+    }
+}
+
+/// Langue courante de l'outil (index dans `LANGUES`)
+static LANGUE_COURANTE: AtomicU8 = AtomicU8::new(0);
+
+/// Langue courante de l'outil
+pub fn langue() -> Langue {
+    LANGUES[usize::from(LANGUE_COURANTE.load(Ordering::Relaxed)) % LANGUES.len()]
+}
+
+/// Changement de la langue courante de l'outil
+pub fn set_langue(langue: Langue) {
+    let index = LANGUES.iter().position(|l| *l == langue).unwrap_or(0);
+    LANGUE_COURANTE.store(u8::try_from(index).unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Langue choisie par la variable d'environnement `SIM_IE_LANG` (None si non définie)
+/// # Errors
+/// Erreur si la langue de la variable d'environnement est inconnue
+pub fn langue_from_env() -> Result<Option<Langue>, String> {
+    match std::env::var(LANGUE_ENV_VAR) {
+        Ok(txt) => txt.parse().map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Traduction d'un texte français dans la langue courante de l'outil
+pub fn tr(txt: &str) -> String {
+    traduction(langue(), txt)
+}
+
+/// Traduction d'un texte français constant (par exemple un libellé de la table des
+/// descripteurs) dans la langue courante de l'outil, sans allocation
+/// (seule une clé exacte du catalogue est traduite, sans paramètres)
+pub fn tr_static(txt: &'static str) -> &'static str {
+    langue()
+        .catalogue()
+        .iter()
+        .find(|(cle, _)| *cle == txt)
+        .map_or(txt, |(_, traduit)| traduit)
+}
+
+/// Traduction d'un texte français dans une langue
+/// (le texte est inchangé s'il n'est pas dans le catalogue de cette langue)
+pub fn traduction(langue: Langue, txt: &str) -> String {
+    let catalogue = langue.catalogue();
+
+    // Texte exact
+    if let Some((_, traduit)) = catalogue.iter().find(|(cle, _)| *cle == txt) {
+        return (*traduit).to_string();
+    }
+
+    // Texte formaté (clé avec paramètres)
+    for (cle, traduit) in catalogue.iter().filter(|(cle, _)| cle.contains("{}")) {
+        if let Some(parametres) = match_cle(cle, txt) {
+            let mut parametres = parametres.into_iter();
+            let mut resultat = String::new();
+            let mut parts = traduit.split("{}");
+            resultat.push_str(parts.next().unwrap_or_default());
+            for part in parts {
+                let parametre = parametres.next().unwrap_or_default();
+                resultat.push_str(&traduction(langue, parametre));
+                resultat.push_str(part);
+            }
+            return resultat;
+        }
+    }
+
+    txt.to_string()
+}
+
+/// Paramètres d'un texte formaté selon une clé avec des paramètres `{}`
+/// (None si le texte ne correspond pas à la clé)
+fn match_cle<'a>(cle: &str, txt: &'a str) -> Option<Vec<&'a str>> {
+    let parts: Vec<&str> = cle.split("{}").collect();
+    let (premier, suivants) = parts.split_first()?;
+    let mut reste = txt.strip_prefix(premier)?;
+    let mut parametres = vec![];
+    for (index, part) in suivants.iter().enumerate() {
+        let is_dernier = index + 1 == suivants.len();
+        let position = if is_dernier {
+            if !reste.ends_with(part) {
+                return None;
+            }
+            reste.len() - part.len()
+        } else if part.is_empty() {
+            // Deux paramètres consécutifs : Ambigu
+            return None;
+        } else {
+            reste.find(part)?
+        };
+        parametres.push(&reste[..position]);
+        reste = &reste[position + part.len()..];
+    }
+    Some(parametres)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_langue_from_str() {
+        assert_eq!("fr".parse(), Ok(Langue::Francais));
+        assert_eq!("EN".parse(), Ok(Langue::Anglais));
+        assert_eq!("en_US.UTF-8".parse(), Ok(Langue::Anglais));
+        assert!("de".parse::<Langue>().is_err());
+        assert_eq!(Langue::default(), Langue::Francais);
+    }
+
+    #[test]
+    fn test_traduction() {
+        // Le français est la langue des clés
+        assert_eq!(traduction(Langue::Francais, "Quantité"), "Quantité");
+
+        // Texte exact, texte formaté et texte absent du catalogue
+        assert_eq!(traduction(Langue::Anglais, "Quantité"), "Quantity");
+        assert_eq!(tr_static("Texte inconnu"), "Texte inconnu");
+        assert_eq!(
+            traduction(Langue::Anglais, "Quantité cpt #3"),
+            "Quantity cpt #3"
+        );
+        assert_eq!(
+            traduction(Langue::Anglais, "Texte inconnu"),
+            "Texte inconnu"
+        );
+        assert_eq!(
+            traduction(Langue::Anglais, "Libellé table produit #3 (court)"),
+            "Product table label #3 (short)"
+        );
+
+        // Paramètre lui-même traduit
+        assert_eq!(
+            traduction(
+                Langue::Anglais,
+                "Valeur non renseignée du champ 'Prédétermination'"
+            ),
+            "Missing value for field 'Preset quantity'"
+        );
+        assert_eq!(
+            traduction(
+                Langue::Anglais,
+                "Ligne 3 : Valeur '13' incorrecte pour l'information 'CodeProduit' (Valeur max. 12)"
+            ),
+            "Line 3: Incorrect value '13' for information 'CodeProduit' (Max. value 12)"
+        );
+    }
+
+    #[test]
+    fn test_catalogue_en() {
+        // Pas de clé en double et autant de paramètres dans la clé et dans sa traduction
+        for (index, (cle, traduit)) in en::CATALOGUE.iter().enumerate() {
+            assert!(
+                en::CATALOGUE[index + 1..].iter().all(|(c, _)| c != cle),
+                "Clé '{cle}' en double"
+            );
+            assert_eq!(
+                cle.matches("{}").count(),
+                traduit.matches("{}").count(),
+                "Paramètres de la clé '{cle}'"
+            );
+        }
+    }
+}
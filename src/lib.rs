@@ -0,0 +1,44 @@
+//! Simulateur d'informatique embarquée ALMA - ST 2150 (librairie)
+//!
+//! Cette librairie regroupe les briques réutilisables de l'outil `sim_ie` :
+//!
+//! * [`st2150`] : Codec du protocole ALMA IE selon la ST 2150 (trames, champs et messages)
+//! * [`serial_com`] : Gestion d'un port série réel ou FAKE pour les tests
+//! * [`context`] : Informations 'atomiques' échangées par le protocole
+//! * [`langue`] : Traduction des libellés, des erreurs et de l'IHM (français par défaut)
+//!
+//! L'interface graphique n'est pas dans cette librairie. Elle est dans le binaire `sim_ie`
+//! qui n'est construit qu'avec la feature `gui` (active par défaut). Pour un usage sans IHM :
+//!
+//! ```toml
+//! sim_ie = { version = "0.5", default-features = false }
+//! ```
+//!
+//! Exemple d'une vacation du message 00 (signe de vie) sur un port FAKE :
+//!
+//! ```
+//! use sim_ie::context::{Context, IdInfo};
+//! use sim_ie::serial_com::SerialCom;
+//! use sim_ie::st2150::{ProtocolError, ST2150};
+//!
+//! let port = SerialCom::new("FAKE", 9600);
+//! let mut st2150 = ST2150::new(port);
+//! let mut context = Context::default();
+//!
+//! // Le port FAKE ne répond jamais...
+//! assert!(ST2150::message_availability(&context, 0).is_ok());
+//! assert_eq!(
+//!     st2150.do_message_vacation(&mut context, 0),
+//!     Err(ProtocolError::NoReply)
+//! );
+//! assert!(context.get_option_info_bool(IdInfo::EnMesurage).is_none());
+//! ```
+
+pub mod context;
+pub mod langue;
+pub mod serial_com;
+pub mod st2150;
+
+pub use context::Context;
+pub use serial_com::{CommonSerialComTrait, SerialCom};
+pub use st2150::ST2150;
@@ -1,21 +1,28 @@
 //! Simulateur d'informatique embarquée ALMA - ST 2150
 use std::env;
+use std::path::{Path, PathBuf};
 
 mod app_view;
-mod context;
-mod serial_com;
-mod st2150;
 
-use context::Context;
-use serial_com::{CommonSerialComTrait, SerialCom};
-use st2150::ST2150;
+use sim_ie::context::{Context, LAST_SESSION_FILE_NAME};
+use sim_ie::langue::{self, set_langue, tr};
+use sim_ie::serial_com::{self, SerialCom};
+use sim_ie::st2150::ST2150;
 
 /// Version de l'application (selon définition dans Cargo.toml)
 pub const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Nom du fichier session proposé par défaut
+const DEFAULT_SESSION_FILE_NAME: &str = "sim_ie_session.txt";
+
 /// Point d'entrée de l'outil
 fn main() {
-    let command_args: Vec<String> = env::args().collect();
+    let mut command_args: Vec<String> = env::args().collect();
+
+    // Langue de l'outil (option `--lang` retirée des arguments)
+    if !langue_from_options(&mut command_args) {
+        return;
+    }
 
     if command_args.len() == 2 {
         if [
@@ -41,7 +48,10 @@ fn main() {
             // Option inconnue
             print_help();
             eprintln!();
-            eprintln!("Erreur option inconnue : '{}'\n", command_args[1]);
+            eprintln!(
+                "{}\n",
+                tr(&format!("Erreur option inconnue : '{}'", command_args[1]))
+            );
         } else {
             // port série défini en ligne de commande
             let port = SerialCom::new(&command_args[1], 9600);
@@ -53,7 +63,18 @@ fn main() {
             // run_on_terminal(&mut st2150);
 
             // IHM application avec l'utilisateur
-            app_view::run(st2150);
+            app_view::run(
+                st2150,
+                Context::default(),
+                PathBuf::from(DEFAULT_SESSION_FILE_NAME),
+            );
+        }
+    } else if (3..=4).contains(&command_args.len()) && !command_args[1].starts_with('-') {
+        // port série et options de session définis en ligne de commande
+        if let Some((context, session_path)) = session_from_options(&command_args[2..]) {
+            let port = SerialCom::new(&command_args[1], 9600);
+            let st2150 = ST2150::new(port);
+            app_view::run(st2150, context, session_path);
         }
     } else {
         // Sans argument ou avec trop d'arguments, on affiche l'aide à l'utilisateur
@@ -63,6 +84,76 @@ fn main() {
     }
 }
 
+/// Contexte initial et fichier session selon les options de la ligne de commande
+/// * `--session fichier` : Restaure la session de ce fichier (et l'IHM sauvegarde dans ce fichier)
+/// * `--last-session` : Restaure la dernière session (sauvegardée automatiquement par l'IHM)
+///
+/// Retourne `None` (après un message pour l'utilisateur) si les options sont incorrectes
+fn session_from_options(options: &[String]) -> Option<(Context, PathBuf)> {
+    let mut context = Context::default();
+    let session_path = match options {
+        [option, file_name] if option == "--session" => {
+            let session_path = PathBuf::from(file_name);
+            if session_path.exists() {
+                if let Err(e) = context.load_session(&session_path) {
+                    eprintln!("{}", tr(&format!("Erreur session : {e}")));
+                    return None;
+                }
+            }
+            session_path
+        }
+        [option] if option == "--last-session" => {
+            let last_session_path = Path::new(LAST_SESSION_FILE_NAME);
+            if last_session_path.exists() {
+                if let Err(e) = context.load_session(last_session_path) {
+                    eprintln!("{}", tr(&format!("Erreur dernière session : {e}")));
+                    return None;
+                }
+            }
+            PathBuf::from(DEFAULT_SESSION_FILE_NAME)
+        }
+        _ => {
+            print_help();
+            eprintln!();
+            eprintln!(
+                "{}\n",
+                tr(&format!(
+                    "Erreur options inconnues : '{}'",
+                    options.join(" ")
+                ))
+            );
+            return None;
+        }
+    };
+    Some((context, session_path))
+}
+
+/// Langue de l'outil selon l'option `--lang fr|en` de la ligne de commande (option retirée des
+/// arguments), sinon selon la variable d'environnement `SIM_IE_LANG` (français par défaut)
+///
+/// Retourne `false` (après un message pour l'utilisateur) si la langue est inconnue
+fn langue_from_options(command_args: &mut Vec<String>) -> bool {
+    let option_langue = match command_args.iter().position(|arg| arg == "--lang") {
+        Some(index) if index + 1 < command_args.len() => {
+            let txt_langue = command_args.remove(index + 1);
+            command_args.remove(index);
+            txt_langue.parse().map(Some)
+        }
+        Some(_) => Err("Langue '' inconnue ('fr' ou 'en' attendu)".to_string()),
+        None => langue::langue_from_env(),
+    };
+    match option_langue {
+        Ok(option_langue) => {
+            set_langue(option_langue.unwrap_or_default());
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", tr(&format!("Erreur langue : {e}")));
+            false
+        }
+    }
+}
+
 /// Fonction pour test sur le terminal (sans IHM)
 #[allow(dead_code)]
 fn run_on_terminal(st2150: &mut ST2150) {
@@ -82,31 +173,83 @@ fn run_on_terminal(st2150: &mut ST2150) {
     }
 }
 
-/// Affiche l'aide pour l'utilisateur
+/// Largeur min. des exemples de commande dans l'aide (alignement des commentaires)
+const HELP_COMMAND_WIDTH: usize = 26;
+
+/// Aide pour l'utilisateur : Rubriques avec leurs exemples de commande et leur commentaire
+/// (textes traduits à l'affichage, voir `print_help`)
+const HELP: &[(&str, &[(&str, &str)])] = &[
+    (
+        "Usage en mode graphique :",
+        &[
+            (
+                "sim_ie COM1",
+                "Pour une machine Windows avec un port série 'COM1'",
+            ),
+            (
+                r#"sim_ie "\.\COM10""#,
+                "Syntaxe de Windows après COM9 (étrange, mais bon...)",
+            ),
+            (r#"sim_ie "/dev/ttyUSB0""#, "Pour une machine Linux"),
+        ],
+    ),
+    (
+        "Options de session (après le port série) :",
+        &[
+            (
+                "sim_ie COM1 --session session.txt",
+                "Restaure (et sauvegarde) la session de ce fichier",
+            ),
+            ("sim_ie COM1 --last-session", "Restaure la dernière session"),
+        ],
+    ),
+    (
+        "Langue de l'outil (en premier, ou variable d'environnement SIM_IE_LANG) :",
+        &[(
+            "sim_ie --lang en COM1",
+            "Outil en anglais ('fr' par défaut)",
+        )],
+    ),
+    (
+        "Usage en mode terminal :",
+        &[
+            ("sim_ie --help", "Pour ce message d'aide"),
+            ("sim_ie --ports ou --list", "Liste des ports de la machine"),
+        ],
+    ),
+];
+
+/// Affiche l'aide pour l'utilisateur (dans la langue de l'outil)
 fn print_help() {
+    eprintln!();
     eprintln!(
-        r#"
-Simulateur d'informatique embarquée v{APP_VERSION} - ALMA 2023-2024.
-
-Usage en mode graphique :
-    sim_ie COM1               # Pour une machine Windows avec un port série 'COM1'
-    sim_ie "\.\COM10"         # Syntaxe de Windows après COM9 (étrange, mais bon...)
-    sim_ie "/dev/ttyUSB0"     # Pour une machine Linux
-
-Usage en mode terminal :
-    sim_ie --help             # Pour ce message d'aide
-    sim_ie --ports ou --list  # Liste des ports de la machine
-"#
+        "{}",
+        tr(&format!(
+            "Simulateur d'informatique embarquée v{APP_VERSION} - ALMA 2023-2024."
+        ))
     );
+    for (rubrique, exemples) in HELP {
+        eprintln!();
+        eprintln!("{}", tr(rubrique));
+        let commandes: Vec<String> = exemples.iter().map(|(commande, _)| tr(commande)).collect();
+        let width = commandes
+            .iter()
+            .map(|commande| commande.chars().count() + 3)
+            .fold(HELP_COMMAND_WIDTH, usize::max);
+        for (commande, (_, commentaire)) in commandes.iter().zip(exemples.iter()) {
+            eprintln!("    {commande:<width$}# {}", tr(commentaire));
+        }
+    }
+    eprintln!();
 }
 
 /// Affiche la liste des noms des ports séries de la machine
 fn print_serial_com_name_list() {
     let port_names_list = serial_com::available_names_list();
     if port_names_list.is_empty() {
-        eprintln!("Désolé, pas de port série sur cette machine :(");
+        eprintln!("{}", tr("Désolé, pas de port série sur cette machine :("));
     } else {
-        eprintln!("Ports séries de cette machine :");
+        eprintln!("{}", tr("Ports séries de cette machine :"));
         for name in port_names_list {
             eprintln!("{name}");
         }
@@ -9,16 +9,18 @@ pub struct FakeSerialCom {
 
     /// Réponse à faire pour le prochain `read`
     will_read: Vec<u8>,
+
+    /// Nombre max. d'octets retournés par chaque `read` (None pour tout retourner d'un coup)
+    option_chunk_len: Option<usize>,
 }
 
 impl CommonSerialComTrait for FakeSerialCom {
     /// Fake read
     fn read(&mut self, buffer: &mut [u8]) -> usize {
-        for (dst, src) in buffer.iter_mut().zip(self.will_read.iter()) {
-            *dst = *src;
-        }
-        let len = self.will_read.len();
-        self.will_read = vec![]; // valable 1 fois
+        let len = usize::min(buffer.len(), self.will_read.len());
+        let len = usize::min(len, self.option_chunk_len.unwrap_or(len));
+        buffer[..len].copy_from_slice(&self.will_read[..len]);
+        self.will_read.drain(..len); // Le reste éventuel pour la lecture suivante
         len
     }
 
@@ -46,6 +48,13 @@ impl CommonSerialComTrait for FakeSerialCom {
         for byte in buffer {
             self.will_read.push(*byte);
         }
+        self.option_chunk_len = None;
+    }
+
+    /// Prédéfini les prochaines lectures du FAKE port, par morceaux
+    fn will_read_chunks(&mut self, buffer: &[u8], chunk_len: usize) {
+        self.will_read(buffer);
+        self.option_chunk_len = Some(chunk_len.max(1));
     }
 }
 
@@ -72,6 +81,14 @@ mod tests {
         // Mais valable que pour un 'read'
         let mut buffer: [u8; 512] = [0; 512];
         assert_eq!(fake.read(&mut buffer), 0);
+
+        // Ou la lire par morceaux
+        fake.will_read_chunks(&[1, 2, 3, 4, 5], 2);
+        assert_eq!(fake.read(&mut buffer), 2);
+        assert_eq!(fake.read(&mut buffer), 2);
+        assert_eq!(fake.read(&mut buffer), 1);
+        assert_eq!(buffer[..1], [5]);
+        assert_eq!(fake.read(&mut buffer), 0);
     }
 
     #[test]
@@ -42,6 +42,9 @@ pub trait CommonSerialComTrait {
 
     /// FAKE : Force les lectures à suivre
     fn will_read(&mut self, buffer: &[u8]);
+
+    /// FAKE : Force les lectures à suivre, par morceaux de `chunk_len` octets au plus
+    fn will_read_chunks(&mut self, buffer: &[u8], chunk_len: usize);
 }
 
 impl SerialCom {
@@ -92,6 +95,12 @@ impl CommonSerialComTrait for SerialCom {
     fn will_read(&mut self, buffer: &[u8]) {
         self.port.will_read(buffer);
     }
+
+    /// Primitive pour les FAKE ports uniquement
+    /// Sans effet si le port n'est pas un FAKE port
+    fn will_read_chunks(&mut self, buffer: &[u8], chunk_len: usize) {
+        self.port.will_read_chunks(buffer, chunk_len);
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +113,15 @@ impl CommonSerialComTrait for TrueSerialCom {
             self.name
         );
     }
+
+    /// Primitive pour les FAKE ports uniquement
+    /// Sans effet si le port n'est pas un FAKE port
+    fn will_read_chunks(&mut self, _buffer: &[u8], _chunk_len: usize) {
+        eprint!(
+            "Usage inattendu de 'will_read_chunks' avec un port existant ({})",
+            self.name
+        );
+    }
 }
 
 #[cfg(test)]
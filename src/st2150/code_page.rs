@@ -0,0 +1,288 @@
+//! Jeux de caractères des champs chaînes de caractères des trames
+//!
+//! La ST2150 ne précise pas le codage des caractères non ASCII (accents des libellés produits ou
+//! des libellés des événements). Selon le calculateur, ces caractères sont codés sur un octet
+//! (ISO-8859-1 ou Windows-1252) ou en UTF-8 (plusieurs octets par caractère).
+//!
+//! Un caractère non représentable dans le jeu choisi est refusé à l'encodage, de même qu'un
+//! octet non défini dans ce jeu au décodage (pas de substitution silencieuse).
+//!
+//! Les caractères de contrôle sont refusés dans tous les jeux, ainsi que l'octet du séparateur
+//! (0xFE, 'þ' en ISO-8859-1 et Windows-1252) : Un tel octet dans un champ chaîne
+//! (STX, ETX, SEPARATOR, ACK, NACK...) corromprait le découpage de la trame.
+
+use std::fmt::Display;
+
+use super::field::Field;
+use super::protocol;
+use super::ProtocolError;
+
+/// Jeu de caractères des champs chaînes de caractères
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum CodePage {
+    /// UTF-8 (par défaut) : 1 à 4 octets par caractère
+    #[default]
+    Utf8,
+
+    /// ISO-8859-1 (Latin-1) : 1 octet par caractère, de U+0000 à U+00FF
+    Latin1,
+
+    /// Windows-1252 : ISO-8859-1 avec des caractères imprimables de 0x80 à 0x9F (€, Œ, œ, etc.)
+    Windows1252,
+}
+
+/// Tous les jeux de caractères
+pub const CODE_PAGES: [CodePage; 3] = [CodePage::Utf8, CodePage::Latin1, CodePage::Windows1252];
+
+/// Caractères Windows-1252 des octets 0x80 à 0x9F (None pour un octet non défini)
+const WINDOWS_1252_80_9F: [Option<char>; 32] = [
+    Some('€'),
+    None,
+    Some('‚'),
+    Some('ƒ'),
+    Some('„'),
+    Some('…'),
+    Some('†'),
+    Some('‡'),
+    Some('ˆ'),
+    Some('‰'),
+    Some('Š'),
+    Some('‹'),
+    Some('Œ'),
+    None,
+    Some('Ž'),
+    None,
+    None,
+    Some('‘'),
+    Some('’'),
+    Some('“'),
+    Some('”'),
+    Some('•'),
+    Some('–'),
+    Some('—'),
+    Some('˜'),
+    Some('™'),
+    Some('š'),
+    Some('›'),
+    Some('œ'),
+    None,
+    Some('ž'),
+    Some('Ÿ'),
+];
+
+impl Display for CodePage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodePage::Utf8 => write!(f, "UTF-8"),
+            CodePage::Latin1 => write!(f, "ISO-8859-1"),
+            CodePage::Windows1252 => write!(f, "Windows-1252"),
+        }
+    }
+}
+
+impl CodePage {
+    /// Caractère d'un octet d'un jeu de caractères sur un octet
+    /// (None si l'octet n'est pas défini dans ce jeu ou interdit dans un champ)
+    fn byte_to_char(self, byte: u8) -> Option<char> {
+        match (self, byte) {
+            (_, protocol::SEPARATOR) => None,
+            (CodePage::Windows1252, 0x80..=0x9F) => WINDOWS_1252_80_9F[usize::from(byte - 0x80)],
+            _ => Some(char::from(byte)).filter(|car| !car.is_control()),
+        }
+    }
+
+    /// Octet d'un caractère d'un jeu de caractères sur un octet
+    /// (None si le caractère n'est pas représentable dans ce jeu)
+    fn char_to_byte(self, car: char) -> Option<u8> {
+        if self == CodePage::Windows1252 {
+            if let Some(index) = WINDOWS_1252_80_9F.iter().position(|c| *c == Some(car)) {
+                return u8::try_from(index + 0x80).ok();
+            }
+        }
+        u8::try_from(u32::from(car))
+            .ok()
+            .filter(|byte| self.byte_to_char(*byte) == Some(car))
+    }
+
+    /// Encodage d'une chaîne dans ce jeu de caractères
+    /// # Errors
+    /// `ProtocolError::IllegalCharEncoding` si un caractère n'est pas représentable
+    /// ou est interdit dans un champ (caractère de contrôle ou octet du séparateur)
+    pub fn encode(self, value: &str) -> Result<Vec<u8>, ProtocolError> {
+        let illegal_char = |car| ProtocolError::IllegalCharEncoding(self.to_string(), car);
+        if self == CodePage::Utf8 {
+            // Les octets d'un caractère UTF-8 sur plusieurs octets sont tous entre 0x80 et 0xF4
+            return match value.chars().find(|car| car.is_control()) {
+                Some(car) => Err(illegal_char(car)),
+                None => Ok(value.as_bytes().to_vec()),
+            };
+        }
+        value
+            .chars()
+            .map(|car| self.char_to_byte(car).ok_or_else(|| illegal_char(car)))
+            .collect()
+    }
+
+    /// Décodage du contenu d'un champ dans ce jeu de caractères
+    /// # Errors
+    /// `ProtocolError::IllegalFieldCharDecode` pour le premier octet incorrect dans ce jeu
+    /// ou interdit dans un champ (caractère de contrôle ou octet du séparateur)
+    pub fn decode(self, field: &Field) -> Result<String, ProtocolError> {
+        let data = field.decode_as_vec();
+        let illegal_byte =
+            |byte| ProtocolError::IllegalFieldCharDecode(self.to_string(), field.clone(), byte);
+        if self == CodePage::Utf8 {
+            let txt = String::from_utf8(data).map_err(|e| {
+                let index = e.utf8_error().valid_up_to();
+                illegal_byte(e.as_bytes()[index])
+            })?;
+            return match txt.char_indices().find(|(_, car)| car.is_control()) {
+                Some((index, _)) => Err(illegal_byte(txt.as_bytes()[index])),
+                None => Ok(txt),
+            };
+        }
+        data.iter()
+            .map(|byte| self.byte_to_char(*byte).ok_or_else(|| illegal_byte(*byte)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_page_encode() {
+        let libelle = "Gazole Supérieur";
+        assert_eq!(CodePage::Utf8.encode(libelle).unwrap().len(), libelle.len());
+        for code_page in [CodePage::Latin1, CodePage::Windows1252] {
+            let data = code_page.encode(libelle).unwrap();
+            assert_eq!(data.len(), libelle.chars().count());
+            assert_eq!(data[10], 0xE9);
+        }
+
+        // Caractères propres à Windows-1252
+        assert_eq!(
+            CodePage::Windows1252.encode("5€ Œ"),
+            Ok(vec![b'5', 0x80, b' ', 0x8C])
+        );
+        assert_eq!(
+            CodePage::Latin1.encode("5€"),
+            Err(ProtocolError::IllegalCharEncoding(
+                "ISO-8859-1".to_string(),
+                '€'
+            ))
+        );
+
+        // Octets réservés par le protocole et caractères de contrôle
+        for code_page in CODE_PAGES {
+            for car in [
+                '\u{2}', '\u{3}', '\u{6}', '\u{15}', '\n', '\u{7F}', '\u{85}',
+            ] {
+                assert_eq!(
+                    code_page.encode(&format!("A{car}")),
+                    Err(ProtocolError::IllegalCharEncoding(
+                        code_page.to_string(),
+                        car
+                    ))
+                );
+            }
+        }
+        for code_page in [CodePage::Latin1, CodePage::Windows1252] {
+            assert_eq!(
+                code_page.encode("þ"),
+                Err(ProtocolError::IllegalCharEncoding(
+                    code_page.to_string(),
+                    'þ'
+                ))
+            );
+        }
+        assert_eq!(CodePage::Utf8.encode("þ"), Ok(vec![0xC3, 0xBE]));
+
+        // Caractère hors des jeux sur un octet
+        assert!(CodePage::Windows1252.encode("→").is_err());
+
+        // Champ en UTF-8 : Caractères de contrôle refusés et troncature sans couper un caractère
+        assert_eq!(
+            Field::encode_str_code_page("A\u{2}B", 5, CodePage::Utf8),
+            Err(ProtocolError::IllegalCharEncoding(
+                "UTF-8".to_string(),
+                '\u{2}'
+            ))
+        );
+        assert_eq!(
+            Field::encode_str_code_page("ABCDé", 5, CodePage::Utf8)
+                .unwrap()
+                .as_bytes(),
+            b"ABCD "
+        );
+    }
+
+    #[test]
+    fn test_code_page_decode() {
+        let field = Field::new(&[b'S', b'U', b'P', 0xC9, b'R', 0x80]);
+        assert_eq!(
+            CodePage::Windows1252.decode(&field),
+            Ok("SUPÉR€".to_string())
+        );
+        assert_eq!(
+            CodePage::Latin1.decode(&field),
+            Err(ProtocolError::IllegalFieldCharDecode(
+                "ISO-8859-1".to_string(),
+                field.clone(),
+                0x80
+            ))
+        );
+        assert_eq!(
+            CodePage::Utf8.decode(&field),
+            Err(ProtocolError::IllegalFieldCharDecode(
+                "UTF-8".to_string(),
+                field.clone(),
+                0xC9
+            ))
+        );
+
+        // Octet non défini en Windows-1252
+        let field = Field::new(&[b'A', 0x81]);
+        assert_eq!(
+            CodePage::Windows1252.decode(&field),
+            Err(ProtocolError::IllegalFieldCharDecode(
+                "Windows-1252".to_string(),
+                field.clone(),
+                0x81
+            ))
+        );
+
+        // Octets réservés par le protocole et caractères de contrôle
+        for code_page in CODE_PAGES {
+            for byte in [
+                protocol::STX,
+                protocol::ETX,
+                protocol::SEPARATOR,
+                protocol::ACK,
+                protocol::NACK,
+                b'\n',
+                0x7F,
+            ] {
+                let field = Field::new(&[b'A', byte]);
+                assert_eq!(
+                    code_page.decode(&field),
+                    Err(ProtocolError::IllegalFieldCharDecode(
+                        code_page.to_string(),
+                        field.clone(),
+                        byte
+                    ))
+                );
+            }
+        }
+
+        // Aller-retour de tous les octets définis
+        for code_page in [CodePage::Latin1, CodePage::Windows1252] {
+            let data: Vec<u8> = (0_u8..=255)
+                .filter(|byte| code_page.byte_to_char(*byte).is_some())
+                .collect();
+            let txt = code_page.decode(&Field::new(&data)).unwrap();
+            assert_eq!(code_page.encode(&txt), Ok(data));
+        }
+    }
+}
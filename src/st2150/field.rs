@@ -1,24 +1,70 @@
 //! Gestion du contenu d'un champ pour le protocole ALMA IE - ST2150
+//!
+//! Les règles d'encodage et de décodage sont celles du codec `no_std` (voir `nostd::field`),
+//! ce `Field` n'y ajoute que le stockage dynamique et les erreurs `ProtocolError`.
 
+use std::cmp::Ordering;
+use std::fmt::{self, Debug, Display};
 use std::ops::RangeInclusive;
 
-use super::{protocol, ProtocolError};
+use super::nostd::field as codec;
+use super::nostd::CodecError;
+use super::{CodePage, ProtocolError};
 
 /// Champ d'une requête ou d'une réponse
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd)]
+/// Deux champs sont égaux s'ils ont le même contenu, quelle que soit leur position
+#[derive(Clone)]
 pub struct Field {
     /// Table des octets d'un champ
     data: Vec<u8>,
+
+    /// Position du champ dans sa trame (voir `Frame::add_field`), pour situer les octets en cause
+    /// d'une erreur de décodage (voir `Frame::positions_en_cause`)
+    option_index: Option<usize>,
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for Field {}
+
+impl PartialOrd for Field {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.data.partial_cmp(&other.data)
+    }
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field").field("data", &self.data).finish()
+    }
 }
 
 impl Field {
     /// Constructeur champ avec un array de u8
     pub fn new(field: &[u8]) -> Self {
-        let mut data = vec![];
-        for v in field {
-            data.push(*v);
+        Self {
+            data: field.to_vec(),
+            option_index: None,
         }
-        Self { data }
+    }
+
+    /// Position du champ dans sa trame (None si le champ n'est pas dans une trame)
+    pub fn option_index(&self) -> Option<usize> {
+        self.option_index
+    }
+
+    /// Mémorise la position du champ dans sa trame (voir `Frame::add_field`)
+    pub(super) fn set_index(&mut self, index: usize) {
+        self.option_index = Some(index);
+    }
+
+    /// Contenu du champ
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
     }
 
     /// Décodage sous forme d'un vecteur de u8 (inner)
@@ -27,6 +73,54 @@ impl Field {
         self.data.clone()
     }
 
+    /// Helper pour un champ d'une taille définie, rempli par `encode` (voir `nostd::field`)
+    /// `value` et `format` ne servent qu'au libellé de l'erreur
+    fn encode_with(
+        value: &dyn Display,
+        format: &str,
+        width: usize,
+        encode: impl FnOnce(&mut [u8]) -> Result<(), CodecError>,
+    ) -> Result<Self, ProtocolError> {
+        if width == 0 {
+            return Err(ProtocolError::IllegalNumberEncoding(format!(
+                "Encoder {value}{format} sur 0 car ?"
+            )));
+        }
+        let mut data = vec![b' '; width];
+        encode(&mut data).map_err(|_e| {
+            ProtocolError::IllegalNumberEncoding(format!(
+                "Encoder {value}{format} sur {width} cars ?"
+            ))
+        })?;
+        Ok(Self {
+            data,
+            option_index: None,
+        })
+    }
+
+    /// Helper pour convertir une erreur de décodage du codec `no_std` (`str_decode` : type de champ)
+    fn decode_error(&self, str_decode: &str, error: CodecError) -> ProtocolError {
+        match error {
+            CodecError::IllegalFieldCharDecode(car) => {
+                ProtocolError::IllegalFieldCharDecode(str_decode.to_string(), self.clone(), car)
+            }
+            _ => ProtocolError::ErrFieldConversion(str_decode.to_string(), self.clone()),
+        }
+    }
+
+    /// Helper pour convertir une valeur décodée dans le type attendu (`str_decode` : type de champ)
+    fn decode_into<T, V>(
+        &self,
+        str_decode: &str,
+        value: Result<V, CodecError>,
+    ) -> Result<T, ProtocolError>
+    where
+        T: TryFrom<V>,
+    {
+        T::try_from(value.map_err(|e| self.decode_error(str_decode, e))?)
+            .map_err(|_e| self.decode_error(str_decode, CodecError::ErrFieldConversion))
+    }
+
     /// Helper pour vérifier le domaine de valeurs d'un u8 avant de l'encoder
     /// A utiliser par exemple pour vérifier qu'un code produit est bien entre 0 et 16
     /// avant d'y ajouter b'0' dans l'encodage (overflow possible si `code_produit + b'0' > 255`)
@@ -53,7 +147,10 @@ impl Field {
     /// (Ne peut donner qu'un champ d'une longueur de 1 octet)
     #[allow(dead_code)]
     pub fn encode_binary(value: u8) -> Self {
-        Self { data: vec![value] }
+        Self {
+            data: vec![value],
+            option_index: None,
+        }
     }
 
     /// Extraction champ d'une valeur binaire (typiquement ACK ou NACK)
@@ -61,40 +158,25 @@ impl Field {
     /// Si le champ est d'une taille autre qu'un seul caractère
     #[allow(dead_code)]
     pub fn decode_binary(&self) -> Result<u8, ProtocolError> {
-        if self.data.len() == 1 {
-            Ok(self.data[0])
-        } else {
-            Err(ProtocolError::ErrFieldConversion(
-                "binary".to_string(),
-                self.clone(),
-            ))
-        }
+        self.decode_into("binary", codec::read_binary(&self.data))
     }
 
-    /// Constructeur champ numérique entier (supposé positif)
-    /// Transforme une valeur entière en un champ ASCII d'une taille définie (0 padded à gauche)
+    /// Constructeur champ numérique entier positif (voir `nostd::field::write_number`)
     /// Par exemple la valeur 2 sur une width de 2 retourne vec![0x30, 0x32]
     /// # Errors
-    /// si la valeur est trop grande pour la taille demandée
+    /// si la valeur est négative ou trop grande pour la taille demandée
     /// si taille demandée = 0
     #[allow(dead_code)]
     pub fn encode_number<T>(value: T, width: usize) -> Result<Self, ProtocolError>
     where
-        T: std::fmt::Display,
+        T: Display + Copy + TryInto<u64>,
     {
-        if width == 0 {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} sur 0 car ?"
-            )));
-        }
-        let str = format!("{value:0width$}");
-        let data = str.as_bytes().to_vec();
-        if data.len() != width {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} sur {width} cars ?"
-            )));
-        }
-        Ok(Self { data })
+        Self::encode_with(&value, "", width, |digits| {
+            let value = value
+                .try_into()
+                .map_err(|_e| CodecError::IllegalNumberEncoding(width))?;
+            codec::write_number(value, digits)
+        })
     }
 
     /// Extraction d'un valeur numérique entière encodée en ASCII
@@ -104,48 +186,27 @@ impl Field {
     #[allow(dead_code)]
     pub fn decode_number<T>(&self) -> Result<T, ProtocolError>
     where
-        T: std::convert::TryFrom<u64>,
+        T: TryFrom<u64>,
     {
-        let mut ret = 0_u64;
-        for value in &self.data {
-            if !(*value >= b'0' && *value <= b'9') {
-                return Err(ProtocolError::IllegalFieldCharDecode(
-                    "number".to_string(),
-                    self.clone(),
-                    *value,
-                ));
-            }
-            let value = u64::from(*value - b'0');
-            ret = 10_u64 * ret + value;
-        }
-        ret.try_into()
-            .map_err(|_e| ProtocolError::ErrFieldConversion("number".to_string(), self.clone()))
+        self.decode_into("number", codec::read_number(&self.data))
     }
 
-    /// Constructeur champ numérique entier signé (Le 1er car est un signe '+' ou '-')
-    /// Transforme une valeur entière en un champ ASCII d'une taille définie (0 padded à gauche)
-    /// Par exemple la valeur 2 sur une width de 2 retourne vec![0x30, 0x32]
+    /// Constructeur champ numérique entier signé (voir `nostd::field::write_signed_number`)
+    /// Par exemple la valeur -12 sur une width de 4 retourne "-012"
     /// # Errors
     /// si la valeur est trop grande pour la taille demandée
-    /// si taille demandée = 0
+    /// si taille demandée < 2
     #[allow(dead_code)]
     pub fn encode_signed_number<T>(value: T, width: usize) -> Result<Self, ProtocolError>
     where
-        T: std::fmt::Display,
+        T: Display + Copy + TryInto<i64>,
     {
-        if width == 0 {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} sur 0 car ?"
-            )));
-        }
-        let str = format!("{value:+0width$}");
-        let data = str.as_bytes().to_vec();
-        if data.len() != width {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} sur {width} cars ?"
-            )));
-        }
-        Ok(Self { data })
+        Self::encode_with(&value, "", width, |digits| {
+            let value = value
+                .try_into()
+                .map_err(|_e| CodecError::IllegalNumberEncoding(width))?;
+            codec::write_signed_number(value, digits)
+        })
     }
 
     /// Extraction d'un valeur numérique entière et signée encodée en ASCII
@@ -157,45 +218,45 @@ impl Field {
     #[allow(dead_code)]
     pub fn decode_signed_number<T>(&self) -> Result<T, ProtocolError>
     where
-        T: std::convert::TryFrom<i64>,
+        T: TryFrom<i64>,
     {
-        // Commence optionnellement par un signe + ou -
-        let (is_negative, index_start) = match self.data.first().expect("Champ signé vide") {
-            b'-' => (true, 1),
-            b'+' => (false, 1),
-            _ => (false, 0),
-        };
-        let mut ret = 0_i64;
-        for value in &self.data[index_start..] {
-            if !(*value >= b'0' && *value <= b'9') {
-                return Err(ProtocolError::IllegalFieldCharDecode(
-                    "signed number".to_string(),
-                    self.clone(),
-                    *value,
-                ));
-            }
-            let value = i64::from(*value - b'0');
-            ret = 10_i64 * ret + value;
-        }
-        if is_negative {
-            ret = -ret;
-        }
-        ret.try_into().map_err(|_e| {
-            ProtocolError::ErrFieldConversion("signed number".to_string(), self.clone())
-        })
+        self.decode_into("signed number", codec::read_signed_number(&self.data))
     }
 
-    /// Constructeur champ chaîne de caractères
-    /// Transforme une chaîne en un champ ASCII d'une taille définie (space padded à droite)
+    /// Constructeur champ chaîne de caractères (voir `nostd::field::write_str`)
     /// Par exemple la valeur "ABC" sur une width de 4 retourne vec![0x41, 0x42, 0x43, 0x20]
     /// La chaîne est tronquée si trop grande pour la taille définie
+    /// (sans couper un caractère UTF-8 multi-octets, complété alors par des espaces)
     #[allow(dead_code)]
     pub fn encode_str(value: &str, width: usize) -> Self {
-        let str = format!("{value:width$}");
-        // Tronque les caractères de fin car format! ne le fait pas...
-        let data: Vec<u8> = str.as_bytes().iter().copied().take(width).collect();
-        assert_eq!(data.len(), width);
-        Self { data }
+        let mut data = vec![b' '; width];
+        codec::write_str(value, &mut data);
+        Self {
+            data,
+            option_index: None,
+        }
+    }
+
+    /// Constructeur champ chaîne de caractères dans un jeu de caractères (voir `CodePage`)
+    /// La chaîne est tronquée si trop grande pour la taille définie (space padded à droite)
+    /// # Errors
+    /// Si un caractère n'est pas représentable dans ce jeu de caractères
+    pub fn encode_str_code_page(
+        value: &str,
+        width: usize,
+        code_page: CodePage,
+    ) -> Result<Self, ProtocolError> {
+        let mut data = code_page.encode(value)?;
+        if code_page == CodePage::Utf8 {
+            // Sans couper un caractère UTF-8 multi-octets (voir `encode_str`)
+            return Ok(Self::encode_str(value, width));
+        }
+        data.truncate(width);
+        data.resize(width, b' ');
+        Ok(Self {
+            data,
+            option_index: None,
+        })
     }
 
     /// Extraction d'une chaîne de caractère
@@ -203,22 +264,27 @@ impl Field {
     /// Si le contenu ne peut pas être convertit en une `String` UTF-8
     #[allow(dead_code)]
     pub fn decode_str(&self) -> Result<String, ProtocolError> {
-        String::from_utf8(self.data.clone())
-            .map_err(|_e| ProtocolError::ErrFieldConversion("string".to_string(), self.clone()))
+        codec::read_str(&self.data)
+            .map(str::to_string)
+            .map_err(|e| self.decode_error("string", e))
+    }
+
+    /// Extraction d'une chaîne de caractère dans un jeu de caractères (voir `CodePage`)
+    /// # Errors
+    /// Si un octet n'est pas défini dans ce jeu de caractères
+    pub fn decode_str_code_page(&self, code_page: CodePage) -> Result<String, ProtocolError> {
+        code_page.decode(self)
     }
 
     /// Constructeur champ d'un caractère ASCII
     /// Transforme un caractère en un champ d'une taille de 1 (Que de l'ASCII géré, par d'UTF-8)
-    /// # Panics
-    /// panic! si le caractère n'est pas de l'ASCII (0x20 - 0x7F)
+    /// # Errors
+    /// Si le caractère n'est pas de l'ASCII
     #[allow(dead_code)]
     pub fn encode_char(car: char) -> Result<Self, ProtocolError> {
-        if !car.is_ascii() {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "{car} n'est pas ASCII"
-            )));
-        }
-        Ok(Self::new(&[car as u8]))
+        codec::write_char(car)
+            .map(|octet| Self::new(&[octet]))
+            .map_err(|_e| ProtocolError::IllegalNumberEncoding(format!("{car} n'est pas ASCII")))
     }
 
     /// Extraction d'un caractère en ASCII
@@ -226,49 +292,25 @@ impl Field {
     /// Si le champ ne contient pas qu'un caractère ASCII
     #[allow(dead_code)]
     pub fn decode_char(&self) -> Result<char, ProtocolError> {
-        if self.data.len() != 1 {
-            return Err(ProtocolError::ErrFieldConversion(
-                "char".to_string(),
-                self.clone(),
-            ));
-        }
-        if !(0x20..=0x7F).contains(&self.data[0]) {
-            return Err(ProtocolError::IllegalFieldCharDecode(
-                "char".to_string(),
-                self.clone(),
-                self.data[0],
-            ));
-        }
-
-        Ok(self.data[0] as char)
+        codec::read_char(&self.data).map_err(|e| self.decode_error("char", e))
     }
 
-    /// Constructeur champ en hexadécimal
-    /// Transforme une valeur entière en un champ hexadécimal d'une taille définie (cars hexadécimal en majuscule)
+    /// Constructeur champ en hexadécimal (voir `nostd::field::write_hexa`)
     /// Par exemple 0xA23 sur une width de 4 retourne vec![0x30, 0x41, 0x32, 0x33]
     /// # Errors
-    /// si la valeur est trop grande pour la taille demandée
+    /// si la valeur est négative ou trop grande pour la taille demandée
     /// si taille demandée = 0
     #[allow(dead_code)]
     pub fn encode_hexa<T>(value: T, width: usize) -> Result<Self, ProtocolError>
     where
-        T: std::fmt::Display + std::fmt::UpperHex,
+        T: Display + Copy + TryInto<u64>,
     {
-        if width == 0 {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} en hexa sur 0 car ?"
-            )));
-        } // format! va ajouter un "0x" devant (on ajoute +2 à la taille demandée)
-        let w = width + 2;
-        let str = format!("{value:#0w$X}");
-        // Supprime les 2 premiers caractères
-        let data: Vec<u8> = str.as_bytes().iter().copied().skip(2).collect();
-        if data.len() != width {
-            return Err(ProtocolError::IllegalNumberEncoding(format!(
-                "Encoder {value} en hexa sur {width} cars ?"
-            )));
-        }
-        Ok(Self { data })
+        Self::encode_with(&value, " en hexa", width, |digits| {
+            let value = value
+                .try_into()
+                .map_err(|_e| CodecError::IllegalNumberEncoding(width))?;
+            codec::write_hexa(value, digits)
+        })
     }
 
     /// Extraction d'une valeur hexa en ASCII
@@ -278,22 +320,9 @@ impl Field {
     #[allow(dead_code)]
     pub fn decode_hexa<T>(&self) -> Result<T, ProtocolError>
     where
-        T: std::convert::TryFrom<u64>,
+        T: TryFrom<u64>,
     {
-        let mut ret = 0_u64;
-        for value in &self.data {
-            if !(*value).is_ascii_hexdigit() {
-                return Err(ProtocolError::IllegalFieldCharDecode(
-                    "hexa".to_string(),
-                    self.clone(),
-                    *value,
-                ));
-            }
-            let value = u64::from(protocol::car_hexa_to_value(*value));
-            ret = 16_u64 * ret + value;
-        }
-        ret.try_into()
-            .map_err(|_e| ProtocolError::ErrFieldConversion("hexa".to_string(), self.clone()))
+        self.decode_into("hexa", codec::read_hexa(&self.data))
     }
 
     /// Trame pour la requête
@@ -450,9 +479,10 @@ mod tests {
             .is_err());
 
         // Trop grand pour un u16
-        assert!(Field::new(&[b'9', b'9', b'9', b'9', b'9', b'9'])
-            .decode_number::<u16>()
-            .is_err());
+        assert!(Field::new(b"999999").decode_number::<u16>().is_err());
+
+        // Trop grand même pour un u64
+        assert!(Field::new(&[b'9'; 30]).decode_number::<u64>().is_err());
     }
 
     #[test]
@@ -551,23 +581,23 @@ mod tests {
     #[test]
     fn test_error_decode_signed_number() {
         // Pas que des chiffres + ou 0-9 au début
-        assert!(Field::new(&[b'X', b'1', b'2'])
-            .decode_hexa::<u16>()
-            .is_err());
+        assert!(Field::new(b"X12").decode_hexa::<u16>().is_err());
 
         // Pas que des chiffres après le signe
-        assert!(Field::new(&[b'-', b'1', b'F', b'2'])
-            .decode_hexa::<u16>()
-            .is_err());
+        assert!(Field::new(b"-1F2").decode_hexa::<u16>().is_err());
 
         // Impossible à convertir (signé pour un u16)
-        assert!(Field::new(&[b'-', b'1', b'2'])
-            .decode_hexa::<u16>()
-            .is_err());
+        assert!(Field::new(b"-12").decode_hexa::<u16>().is_err());
 
         // Trop grand pour un u16
-        assert!(Field::new(&[b'+', b'9', b'9', b'9', b'9', b'9'])
-            .decode_hexa::<u16>()
+        assert!(Field::new(b"+99999").decode_hexa::<u16>().is_err());
+
+        // Champ vide
+        assert!(Field::new(&[]).decode_signed_number::<i16>().is_err());
+
+        // Trop grand même pour un i64
+        assert!(Field::new(&[b'9'; 30])
+            .decode_signed_number::<i64>()
             .is_err());
     }
 
@@ -590,6 +620,28 @@ mod tests {
         assert_eq!(f.to_frame(), vec![0x41, 0x42, 0x43, 0x20, 0x20]);
     }
 
+    #[test]
+    fn test_encode_str_utf8() {
+        // Pas de caractère UTF-8 coupé en plein milieu
+        let f = Field::encode_str("SUPÉR", 4);
+        assert_eq!(f.to_frame(), b"SUP ".to_vec());
+        let f = Field::encode_str("SUPÉR", 5);
+        assert_eq!(f.decode_str(), Ok("SUPÉ".to_string()));
+    }
+
+    #[test]
+    fn test_encode_str_code_page() {
+        let f = Field::encode_str_code_page("Supérieur", 5, CodePage::Latin1).unwrap();
+        assert_eq!(f.to_frame(), vec![b'S', b'u', b'p', 0xE9, b'r']);
+        assert_eq!(
+            f.decode_str_code_page(CodePage::Latin1),
+            Ok("Supér".to_string())
+        );
+        let f = Field::encode_str_code_page("Œuf", 5, CodePage::Windows1252).unwrap();
+        assert_eq!(f.to_frame(), vec![0x8C, b'u', b'f', b' ', b' ']);
+        assert!(Field::encode_str_code_page("Œuf", 5, CodePage::Latin1).is_err());
+    }
+
     #[test]
     fn test_encode_str_0() {
         // "ABC", width 0 -> ''
@@ -636,7 +688,7 @@ mod tests {
     #[test]
     fn test_error_decode_char() {
         // Pas qu'un seul caractère
-        assert!(Field::new(&[b'1', b'2']).decode_char().is_err());
+        assert!(Field::new(b"12").decode_char().is_err());
 
         // Pas de l'ASCII
         assert!(Field::new(&[0xFF]).decode_char().is_err());
@@ -716,13 +768,12 @@ mod tests {
     #[test]
     fn test_error_decode_hexa() {
         // Pas que des chiffres 0-9 / A-F / a-f
-        assert!(Field::new(&[b'1', b'G', b'2'])
-            .decode_hexa::<u16>()
-            .is_err());
+        assert!(Field::new(b"1G2").decode_hexa::<u16>().is_err());
 
         // Trop grand pour un u16
-        assert!(Field::new(&[b'F', b'F', b'F', b'F', b'F'])
-            .decode_hexa::<u16>()
-            .is_err());
+        assert!(Field::new(b"FFFFF").decode_hexa::<u16>().is_err());
+
+        // Trop grand même pour un u64
+        assert!(Field::new(&[b'F'; 20]).decode_hexa::<u64>().is_err());
     }
 }
@@ -1,20 +1,66 @@
 //! Helper pour l'encodage/décodage des trames du protocole ALMA IE - ST2150
+//!
+//! Les règles d'encodage et de décodage sont celles du codec `no_std` (voir `nostd::frame`),
+//! ce `Frame` n'y ajoute que le stockage dynamique, le décodage tolérant et les erreurs
+//! `ProtocolError`.
 
 use super::field::Field;
+use super::nostd::frame as codec;
+use super::nostd::CodecError;
 use super::protocol;
 use super::ProtocolError;
+use crate::context::IdInfo;
 
 /// Libellé pour un message d'erreur mal formé
 const MESSAGE_50_MALFORMED: &str = "??? Malformed ???";
 
 /// Support générique pour un message du protocole
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Frame {
     /// Numéro de message
     pub message_num: u8,
 
     /// Champ de la requête
     pub fields: Vec<Field>,
+
+    /// Information du contexte encodée dans chaque champ de la requête
+    /// (None pour un champ sans information associée, par exemple une longueur, ou reçu)
+    pub id_infos: Vec<Option<IdInfo>>,
+}
+
+/// Nature d'un segment d'une trame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NatureSegment {
+    /// STX en début de trame
+    Stx,
+
+    /// Numéro du message
+    NumeroMessage,
+
+    /// SEPARATOR avant un champ ou le checksum
+    Separateur,
+
+    /// Champ du message (index)
+    Champ(usize),
+
+    /// Checksum (LRC) de la trame
+    Checksum,
+
+    /// ETX en fin de trame
+    Etx,
+}
+
+/// Segment d'une trame : Suite d'octets de même nature (pour l'affichage annoté d'une trame)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// Nature des octets
+    pub nature: NatureSegment,
+
+    /// Position du premier octet dans la trame
+    pub position: usize,
+
+    /// Octets du segment
+    pub octets: Vec<u8>,
 }
 
 impl Frame {
@@ -23,6 +69,7 @@ impl Frame {
         Self {
             message_num: numero,
             fields: vec![],
+            id_infos: vec![],
         }
     }
 
@@ -38,49 +85,200 @@ impl Frame {
 
     /// Longueur d'une trame de réponse selon longueur des champs attendus
     pub fn len_expected_response(len_fields: &[usize]) -> usize {
-        // Longueur attendue = STX(1) + message_num(2) + [SEP(1) + len_field]* + SEP(1) + checksum(2) + ETx(1)
-        //                   = map(len + 1) + 1 + 2 + 1 + 2 + 1 = map(len + 1) + 7
-        len_fields.iter().map(|len| *len + 1).sum::<usize>() + 7
+        super::nostd::Frame::len_expected_response(len_fields)
     }
 
-    /// Ajout d'un champ dans le message
-    pub fn add_field(&mut self, field: Field) {
+    /// Accès à un champ du message
+    /// # Errors
+    /// Si le champ n'existe pas dans le message
+    pub fn field(&self, index: usize) -> Result<&Field, ProtocolError> {
+        self.fields
+            .get(index)
+            .ok_or(ProtocolError::MissingField(index))
+    }
+
+    /// Ajout d'un champ dans le message (qui mémorise sa position, voir `Field::option_index`)
+    pub fn add_field(&mut self, mut field: Field) {
+        field.set_index(self.fields.len());
         self.fields.push(field);
+        self.id_infos.push(None);
     }
 
-    /// Création de la trame pour le message
+    /// Ajout d'un champ qui encode une information du contexte dans le message
+    pub fn add_info_field(&mut self, id_info: IdInfo, mut field: Field) {
+        field.set_index(self.fields.len());
+        self.fields.push(field);
+        self.id_infos.push(Some(id_info));
+    }
+
+    /// Information du contexte encodée dans un champ du message (None si aucune)
+    pub fn id_info(&self, index: usize) -> Option<IdInfo> {
+        self.id_infos.get(index).copied().flatten()
+    }
+
+    /// Création de la trame pour le message (voir `nostd::frame::write_frame`)
     pub fn to_frame(&self) -> Vec<u8> {
         let mut req = vec![];
+        // Seul un numéro de message sur plus de 2 chiffres est refusé
+        codec::write_frame(
+            self.message_num,
+            self.fields.iter().map(Field::as_bytes),
+            |data| {
+                req.extend_from_slice(data);
+                Ok(())
+            },
+        )
+        .unwrap();
+        req
+    }
 
-        // STX au début
-        req.push(protocol::STX);
+    /// Découpage d'une trame en segments : STX, numéro du message, SEPARATOR, champs, checksum
+    /// et ETX
+    /// Le découpage est tolérant pour afficher toute trame reçue : Sans STX ou ETX (trame
+    /// incomplète), les segments correspondants sont absents et le dernier segment avant l'ETX
+    /// est le checksum
+    pub fn segments(buffer: &[u8]) -> Vec<Segment> {
+        let mut segments = vec![];
+
+        let mut start = 0;
+        if buffer.first() == Some(&protocol::STX) {
+            segments.push(Segment {
+                nature: NatureSegment::Stx,
+                position: 0,
+                octets: vec![protocol::STX],
+            });
+            start = 1;
+        }
+        let mut end = buffer.len();
+        let with_etx = end > start && buffer[end - 1] == protocol::ETX;
+        if with_etx {
+            end -= 1;
+        }
 
-        // Numéro de message sur 2 octets
-        let command_field = Field::encode_number(self.message_num, 2).unwrap();
-        req.extend(command_field.to_frame());
+        // Numéro du message, champs et checksum séparés par des SEPARATOR
+        let data = &buffer[start..end];
+        let nb_parts = data.split(|car| *car == protocol::SEPARATOR).count();
+        let mut position = start;
+        for (index, part) in data.split(|car| *car == protocol::SEPARATOR).enumerate() {
+            if index > 0 {
+                segments.push(Segment {
+                    nature: NatureSegment::Separateur,
+                    position,
+                    octets: vec![protocol::SEPARATOR],
+                });
+                position += 1;
+            }
+            let nature = if index == 0 {
+                NatureSegment::NumeroMessage
+            } else if with_etx && index == nb_parts - 1 {
+                NatureSegment::Checksum
+            } else {
+                NatureSegment::Champ(index - 1)
+            };
+            if !part.is_empty() || matches!(nature, NatureSegment::Champ(_)) {
+                segments.push(Segment {
+                    nature,
+                    position,
+                    octets: part.to_vec(),
+                });
+            }
+            position += part.len();
+        }
 
-        // Tous le champs du message précédés d'un SEPARATOR
-        for field in &self.fields {
-            req.push(protocol::SEPARATOR);
-            req.extend(field.to_frame());
+        if with_etx {
+            segments.push(Segment {
+                nature: NatureSegment::Etx,
+                position: end,
+                octets: vec![protocol::ETX],
+            });
         }
 
-        // checksum précédé d'un SEPARATOR
-        req.push(protocol::SEPARATOR);
-        // Le checksum est calculé sur l'ensemble de la trame sans le STX initial mais
-        // avec le SEPARATOR avant le checksum
-        let checksum = protocol::calcul_checksum(&req[1..]);
-        let checksum_field = Field::encode_hexa(checksum, 2).unwrap();
-        req.extend(checksum_field.to_frame());
+        segments
+    }
 
-        // ETX final
-        req.push(protocol::ETX);
+    /// Positions des octets en cause dans une trame reçue pour une erreur de décodage
+    /// (vide si l'erreur ne désigne pas d'octet de la trame, par exemple une longueur incorrecte)
+    pub fn positions_en_cause(buffer: &[u8], error: &ProtocolError) -> Vec<usize> {
+        if buffer.is_empty() {
+            return vec![];
+        }
+        let segments = Self::segments(buffer);
+        let positions_segment =
+            |segment: &Segment| segment.position..segment.position + segment.octets.len();
+        let positions_nature = |nature: NatureSegment| -> Vec<usize> {
+            segments
+                .iter()
+                .filter(|segment| segment.nature == nature)
+                .flat_map(positions_segment)
+                .collect()
+        };
+        // Segment d'un champ selon sa position dans la trame
+        let option_segment_field = |field: &Field| {
+            let index = field.option_index()?;
+            segments
+                .iter()
+                .find(|segment| segment.nature == NatureSegment::Champ(index))
+        };
+
+        match error {
+            ProtocolError::MissingSTX => vec![0],
+            ProtocolError::MissingETX => vec![buffer.len() - 1],
+            ProtocolError::BadChecksum(_, _) => positions_nature(NatureSegment::Checksum),
+            ProtocolError::BadMessageNumber(_, _) => positions_nature(NatureSegment::NumeroMessage),
+            ProtocolError::SeparatorExpected(position) => vec![*position],
+            ProtocolError::BadFieldLen(index, _, _) => {
+                positions_nature(NatureSegment::Champ(*index))
+            }
+            ProtocolError::IllegalFieldCharDecode(_, field, car) => option_segment_field(field)
+                .and_then(|segment| {
+                    let index = segment.octets.iter().position(|octet| octet == car)?;
+                    Some(vec![segment.position + index])
+                })
+                .unwrap_or_default(),
+            ProtocolError::ErrFieldConversion(_, field)
+            | ProtocolError::IllegalRepFieldValue(field, _, _) => option_segment_field(field)
+                .map(|segment| positions_segment(segment).collect())
+                .unwrap_or_default(),
+            _ => vec![],
+        }
+    }
 
-        // et voilà :)
-        req
+    /// Erreur pour une erreur du codec `no_std` lors du décodage de `buffer`
+    fn decode_error(buffer: &[u8], error: CodecError) -> ProtocolError {
+        // Seul le numéro du message est décodé par le contrôle de la trame
+        let num_field = || Field::new(buffer.get(1..3).unwrap_or_default());
+        match error {
+            CodecError::BadMessageLen(nb, nb_expected) => {
+                ProtocolError::BadMessageLen(nb, nb_expected)
+            }
+            CodecError::BadChecksum(checksum, checksum_expected) => {
+                ProtocolError::BadChecksum(checksum, checksum_expected)
+            }
+            CodecError::MissingSTX => ProtocolError::MissingSTX,
+            CodecError::MissingETX => ProtocolError::MissingETX,
+            CodecError::ErrorMessage50(option_txt) => ProtocolError::ErrorMessage50(
+                option_txt
+                    .and_then(|txt| String::from_utf8(txt.to_vec()).ok())
+                    .unwrap_or(MESSAGE_50_MALFORMED.to_string()),
+            ),
+            CodecError::BadMessageNumber(num, num_expected) => {
+                ProtocolError::BadMessageNumber(num, num_expected)
+            }
+            CodecError::SeparatorExpected(position) => ProtocolError::SeparatorExpected(position),
+            CodecError::IllegalFieldCharDecode(car) => {
+                ProtocolError::IllegalFieldCharDecode("number".to_string(), num_field(), car)
+            }
+            CodecError::ErrFieldConversion => {
+                ProtocolError::ErrFieldConversion("number".to_string(), num_field())
+            }
+            CodecError::MissingField(index) => ProtocolError::MissingField(index),
+            CodecError::IllegalNumberEncoding(_) | CodecError::CapacityOverflow(_, _) => {
+                ProtocolError::IllegalNumberEncoding(error.to_string())
+            }
+        }
     }
 
-    /// Décodage et validation d'une trame d'un message
+    /// Décodage et validation d'une trame d'un message (voir `nostd::frame::read_fields`)
     /// `buffer` : Message à décoder
     /// `message_num` : Numéro de message attendu
     /// `len_fields` : Nombre et taille (en octet) des différents champs attendus dans le message
@@ -92,84 +290,69 @@ impl Frame {
         message_num: u8,
         len_fields: &[usize],
     ) -> Result<Self, ProtocolError> {
-        let rec_len = buffer.len();
-
-        // Le message doit faire au moins STX + num(2) + SEPARATOR + checksum(2) + ETX
-        if rec_len < 7 {
-            return Err(ProtocolError::BadMessageLen(rec_len, 7));
-        }
-
-        // Commence par STX ?
-        if buffer[0] != protocol::STX {
-            return Err(ProtocolError::MissingSTX);
-        }
-
-        // Termine par ETX ?
-        if buffer[buffer.len() - 1] != protocol::ETX {
-            return Err(ProtocolError::MissingETX);
-        }
-
-        // Checksum OK ?
-        let rec_checksum = protocol::car_hexa_to_value(buffer[rec_len - 3]) * 16
-            + protocol::car_hexa_to_value(buffer[rec_len - 2]);
-        let checksum = protocol::calcul_checksum(&buffer[1..rec_len - 3]);
-        if checksum != rec_checksum {
-            return Err(ProtocolError::BadChecksum(rec_checksum, checksum));
-        }
-
-        // Numéro de message
-        let rec_message_num = (buffer[1] - b'0') * 10 + (buffer[2] - b'0');
-
-        // Message 50 d'erreur ?
-        if rec_message_num == 50 {
-            /* Un message 50 devrait être de la forme STX + "50" + SEP  + "ERREUR" + SEP + checksum(2) + ETX */
-            if rec_len == 14 {
-                let txt = String::from_utf8(buffer[4..=9].to_vec())
-                    .unwrap_or(MESSAGE_50_MALFORMED.to_string());
-                return Err(ProtocolError::ErrorMessage50(txt));
-            }
-            return Err(ProtocolError::ErrorMessage50(
-                MESSAGE_50_MALFORMED.to_string(),
-            ));
-        }
+        let mut frame = Self::new(message_num);
+        codec::read_fields(buffer, message_num, len_fields, |field| {
+            frame.add_field(Field::new(field));
+            Ok(())
+        })
+        .map_err(|e| Self::decode_error(buffer, e))?;
+        Ok(frame)
+    }
 
-        // Numéro de message OK ?
-        if rec_message_num != message_num {
-            return Err(ProtocolError::BadMessageNumber(
-                rec_message_num,
-                message_num,
-            ));
+    /// Décodage tolérant d'une trame d'un message
+    /// Les contrôles de `try_from_buffer` sur le STX, l'ETX, le checksum et le numéro du message
+    /// restent des erreurs. Par contre, les champs sont découpés selon les SEPARATOR reçus :
+    /// * Un champ en trop ou manquant donne un avertissement `BadFieldsCount`
+    /// * Un champ d'une longueur inattendue donne un avertissement `BadFieldLen` (les espaces
+    ///   en trop à la fin d'un champ sont supprimés)
+    ///
+    /// Retourne la trame et la liste des avertissements
+    pub fn try_from_buffer_lenient(
+        buffer: &[u8],
+        message_num: u8,
+        len_fields: &[usize],
+    ) -> Result<(Self, Vec<ProtocolError>), ProtocolError> {
+        // Champs entre le numéro de message et le checksum : { SEPARATOR + champ(n) }* + SEPARATOR
+        let data_fields =
+            codec::check_frame(buffer, message_num).map_err(|e| Self::decode_error(buffer, e))?;
+        let rec_len = buffer.len();
+        if data_fields.first() != Some(&protocol::SEPARATOR) {
+            return Err(ProtocolError::SeparatorExpected(3));
         }
-
-        // Longueur du message OK ?
-        let expected_rec_len = Self::len_expected_response(len_fields);
-        if rec_len != expected_rec_len {
-            return Err(ProtocolError::BadMessageLen(rec_len, expected_rec_len));
+        if data_fields.last() != Some(&protocol::SEPARATOR) {
+            return Err(ProtocolError::SeparatorExpected(rec_len - 4));
         }
 
-        // On est plutôt bien parti, reste à valider les champs..
         let mut frame = Self::new(message_num);
-
-        let mut cur_position = 1 + 2; /* Après le STX num(2) */
-        for len_field in len_fields {
-            // On doit trouver un SEPARATOR avant le champ
-            if buffer[cur_position] != protocol::SEPARATOR {
-                return Err(ProtocolError::SeparatorExpected(cur_position));
+        let mut warnings = vec![];
+
+        if data_fields.len() > 1 {
+            for (index, data) in data_fields[1..data_fields.len() - 1]
+                .split(|car| *car == protocol::SEPARATOR)
+                .enumerate()
+            {
+                let mut data = data;
+                if let Some(len_field) = len_fields.get(index) {
+                    if data.len() != *len_field {
+                        warnings.push(ProtocolError::BadFieldLen(index, data.len(), *len_field));
+                        // Supprime les espaces en trop en fin de champ
+                        while data.len() > *len_field && data.last() == Some(&b' ') {
+                            data = &data[..data.len() - 1];
+                        }
+                    }
+                }
+                frame.add_field(Field::new(data));
             }
-            cur_position += 1;
-            // Les len_field caractères qui suivent sont un champ
-            let field = Field::new(&buffer[cur_position..cur_position + len_field]);
-            frame.add_field(field);
-            cur_position += len_field;
         }
 
-        // On doit trouver encore un SEPARATOR avant le checksum
-        if buffer[cur_position] != protocol::SEPARATOR {
-            return Err(ProtocolError::SeparatorExpected(cur_position));
+        if frame.fields.len() != len_fields.len() {
+            warnings.push(ProtocolError::BadFieldsCount(
+                frame.fields.len(),
+                len_fields.len(),
+            ));
         }
 
-        // On est tout bon :)
-        Ok(frame)
+        Ok((frame, warnings))
     }
 }
 
@@ -245,6 +428,92 @@ mod tests {
         assert!(req.is_nack());
     }
 
+    #[test]
+    fn test_field() {
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_str("ABCD", 4));
+
+        assert_eq!(frame.field(0), Ok(&Field::encode_str("ABCD", 4)));
+        assert_eq!(frame.field(1), Err(ProtocolError::MissingField(1)));
+    }
+
+    #[test]
+    fn test_segments() {
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_str("ABCD", 4));
+        frame.add_info_field(IdInfo::Quantieme, Field::encode_number(42, 3).unwrap());
+        assert_eq!(frame.id_info(0), None);
+        assert_eq!(frame.id_info(1), Some(IdInfo::Quantieme));
+
+        let trame = frame.to_frame();
+        let natures: Vec<(NatureSegment, usize)> = Frame::segments(&trame)
+            .into_iter()
+            .map(|segment| (segment.nature, segment.position))
+            .collect();
+        assert_eq!(
+            natures,
+            vec![
+                (NatureSegment::Stx, 0),
+                (NatureSegment::NumeroMessage, 1),
+                (NatureSegment::Separateur, 3),
+                (NatureSegment::Champ(0), 4),
+                (NatureSegment::Separateur, 8),
+                (NatureSegment::Champ(1), 9),
+                (NatureSegment::Separateur, 12),
+                (NatureSegment::Checksum, 13),
+                (NatureSegment::Etx, 15),
+            ]
+        );
+
+        // Trame incomplète : Pas de checksum ni d'ETX
+        let segments = Frame::segments(&trame[..10]);
+        assert_eq!(segments.len(), 6);
+        assert_eq!(segments[5].nature, NatureSegment::Champ(1));
+        assert_eq!(segments[5].octets, vec![b'0']);
+    }
+
+    #[test]
+    fn test_positions_en_cause() {
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_str("AB", 2));
+        frame.add_field(Field::encode_number(42, 3).unwrap());
+        let mut trame = frame.to_frame();
+
+        // Checksum incorrect
+        trame[11] = if trame[11] == b'0' { b'1' } else { b'0' };
+        let error = Frame::try_from_buffer(&trame, 12, &[2, 3]).unwrap_err();
+        assert!(matches!(error, ProtocolError::BadChecksum(_, _)));
+        assert_eq!(Frame::positions_en_cause(&trame, &error), vec![11, 12]);
+
+        // Séparateur attendu
+        let error = Frame::try_from_buffer(&frame.to_frame(), 12, &[3, 2]).unwrap_err();
+        assert_eq!(error, ProtocolError::SeparatorExpected(7));
+        assert_eq!(Frame::positions_en_cause(&trame, &error), vec![7]);
+
+        // Caractère incorrect d'un champ
+        let error = frame.field(0).unwrap().decode_number::<u8>().unwrap_err();
+        assert_eq!(Frame::positions_en_cause(&trame, &error), vec![4]);
+
+        // Valeur incorrecte d'un champ de même contenu qu'un autre champ
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_number(42, 3).unwrap());
+        frame.add_field(Field::encode_number(42, 3).unwrap());
+        let trame_doublon = frame.to_frame();
+        let error = ProtocolError::IllegalRepFieldValue(
+            frame.field(1).unwrap().clone(),
+            "Champ".to_string(),
+            "0 à 10".to_string(),
+        );
+        assert_eq!(
+            Frame::positions_en_cause(&trame_doublon, &error),
+            vec![8, 9, 10]
+        );
+
+        // Pas d'octet en cause
+        assert!(Frame::positions_en_cause(&trame, &ProtocolError::NoReply).is_empty());
+        assert!(Frame::positions_en_cause(&[], &ProtocolError::MissingSTX).is_empty());
+    }
+
     #[test]
     fn test_try_from_buffer_is_ok() {
         // On utilise ici la possibilité de créer une trame et son message et
@@ -284,6 +553,52 @@ mod tests {
         assert_eq!(ret.unwrap(), frame);
     }
 
+    #[test]
+    fn test_try_from_buffer_lenient() {
+        // Trame conforme : Pas d'avertissement
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_number(12345, 5).unwrap());
+        frame.add_field(Field::encode_str("VALUE", 10));
+        let ret = Frame::try_from_buffer_lenient(&frame.to_frame(), 12, &[5, 10]);
+        assert_eq!(ret.unwrap(), (frame, vec![]));
+
+        // Champ en trop et libellé plus court
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_number(12345, 5).unwrap());
+        frame.add_field(Field::encode_str("VALUE", 5));
+        frame.add_field(Field::encode_str("EXTRA", 5));
+        let (rec_frame, warnings) =
+            Frame::try_from_buffer_lenient(&frame.to_frame(), 12, &[5, 10]).unwrap();
+        assert_eq!(rec_frame, frame);
+        assert_eq!(
+            warnings,
+            vec![
+                ProtocolError::BadFieldLen(1, 5, 10),
+                ProtocolError::BadFieldsCount(3, 2)
+            ]
+        );
+
+        // Espaces en trop à la fin d'un champ et champ manquant
+        let mut frame = Frame::new(12);
+        frame.add_field(Field::encode_str("123", 5));
+        let (rec_frame, warnings) =
+            Frame::try_from_buffer_lenient(&frame.to_frame(), 12, &[3, 1]).unwrap();
+        assert_eq!(rec_frame.fields, vec![Field::encode_str("123", 3)]);
+        assert_eq!(
+            warnings,
+            vec![
+                ProtocolError::BadFieldLen(0, 5, 3),
+                ProtocolError::BadFieldsCount(1, 2)
+            ]
+        );
+
+        // Les erreurs de trame restent des erreurs
+        let mut buffer = Frame::new(12).to_frame();
+        buffer[1] = b'1';
+        buffer[2] = b'3';
+        assert!(Frame::try_from_buffer_lenient(&buffer, 12, &[]).is_err());
+    }
+
     #[test]
     fn test_try_from_buffer_is_err_part_1() {
         /* buffer / num message / len_fields / ProtocolError */
@@ -428,6 +743,25 @@ mod tests {
                 &[2, 1], /* Inversion des longueurs des champs #1/#2 ici */
                 ProtocolError::SeparatorExpected(6),
             ),
+            // Message avec un numéro qui n'est pas en chiffres ASCII
+            (
+                &[
+                    protocol::STX,
+                    0x00, /* Numéro de message incorrect */
+                    b'0',
+                    protocol::SEPARATOR,
+                    b'C',
+                    b'E',
+                    protocol::ETX,
+                ],
+                0,
+                &[],
+                ProtocolError::IllegalFieldCharDecode(
+                    "number".to_string(),
+                    Field::new(&[0x00, b'0']),
+                    0x00,
+                ),
+            ),
         ];
 
         for (buffer, message_num, len_fields, expected) in err_tests {
@@ -0,0 +1,295 @@
+//! Diagnostic des informations du contexte nécessaires à la requête d'un message
+//!
+//! Le diagnostic liste toutes les anomalies des informations de la requête : Informations non
+//! renseignées, valeurs hors de leur domaine (par exemple, un code produit au-delà du profil du
+//! véhicule) et incohérences entre informations (par exemple, un code produit final sans code
+//! produit).
+//!
+//! Pour ajouter un contrôle de cohérence, il suffit d'ajouter une règle dans `COHERENCE_RULES`
+
+use std::fmt::Display;
+
+use super::context::{Context, TValue, U8OrT};
+use super::IdInfo;
+use super::ProtocolError;
+use crate::langue::tr;
+
+/// Raison d'une incohérence de `OrdreCompartiments` : Compartiment absent du véhicule
+/// (clé de traduction, `{}` : numéro du compartiment)
+const RAISON_COMPARTIMENT_ABSENT: &str = "Compartiment {} absent du véhicule";
+
+/// Raison d'une incohérence de `OrdreCompartiments` : Compartiment en double
+/// (clé de traduction, `{}` : numéro du compartiment)
+const RAISON_COMPARTIMENT_EN_DOUBLE: &str = "Compartiment {} en double";
+
+/// Anomalie d'une information de la requête
+/// Les raisons sont dans la langue de l'outil (traduites lors du diagnostic)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Anomalie {
+    /// Information non renseignée
+    Manquante,
+
+    /// Valeur hors du domaine de l'information (libellé du domaine de valeurs)
+    HorsDomaine(String),
+
+    /// Valeur incohérente avec d'autres informations de la requête (raison)
+    Incoherente(String),
+}
+
+impl Display for Anomalie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Anomalie::Manquante => write!(f, "{}", tr("Valeur non renseignée")),
+            Anomalie::HorsDomaine(raison) | Anomalie::Incoherente(raison) => {
+                write!(f, "{raison}")
+            }
+        }
+    }
+}
+
+/// Anomalie d'une information de la requête
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagnosticInfo {
+    /// Information en cause
+    pub id_info: IdInfo,
+
+    /// Anomalie relevée
+    pub anomalie: Anomalie,
+}
+
+impl DiagnosticInfo {
+    /// Erreur correspondant à cette anomalie (pour `availability`)
+    /// (la raison est déjà dans la langue de l'outil)
+    pub fn to_protocol_error(&self, context: &Context) -> ProtocolError {
+        let nom = context.get_info_label(self.id_info).into_owned();
+        match &self.anomalie {
+            Anomalie::Manquante => ProtocolError::ContextMissing(nom),
+            Anomalie::HorsDomaine(raison) | Anomalie::Incoherente(raison) => {
+                ProtocolError::ContextIllegal(nom, raison.clone())
+            }
+        }
+    }
+}
+
+/// Règle de cohérence entre informations d'une requête
+struct CoherenceRule {
+    /// Informations concernées
+    /// (la règle ne s'applique qu'aux requêtes qui contiennent toutes ces informations)
+    id_infos: &'static [IdInfo],
+
+    /// Contrôle de cohérence : Information en cause et raison si les informations sont
+    /// incohérentes
+    check: fn(&Context) -> Option<(IdInfo, String)>,
+}
+
+/// Table de toutes les règles de cohérence entre informations d'une requête
+const COHERENCE_RULES: &[CoherenceRule] = &[
+    // CodeProduitFinal sans CodeProduit
+    CoherenceRule {
+        id_infos: &[IdInfo::CodeProduit, IdInfo::CodeProduitFinal],
+        check: |context| {
+            final_without_initial(
+                context,
+                IdInfo::CodeProduitFinal,
+                IdInfo::CodeProduit,
+                "Code produit final sans code produit",
+            )
+        },
+    },
+    // NumeroCompartimentFinal sans NumeroCompartiment
+    CoherenceRule {
+        id_infos: &[IdInfo::NumeroCompartiment, IdInfo::NumeroCompartimentFinal],
+        check: |context| {
+            final_without_initial(
+                context,
+                IdInfo::NumeroCompartimentFinal,
+                IdInfo::NumeroCompartiment,
+                "Compartiment final sans compartiment",
+            )
+        },
+    },
+    // NumeroFlexibleFinal sans NumeroFlexible
+    CoherenceRule {
+        id_infos: &[IdInfo::NumeroFlexible, IdInfo::NumeroFlexibleFinal],
+        check: |context| {
+            final_without_initial(
+                context,
+                IdInfo::NumeroFlexibleFinal,
+                IdInfo::NumeroFlexible,
+                "Flexible final sans flexible",
+            )
+        },
+    },
+    // OrdreCompartiments : Compartiments du véhicule, sans doublon
+    // (0 ne désigne pas de compartiment, voir le bourrage de ce champ dans la requête)
+    CoherenceRule {
+        id_infos: &[IdInfo::OrdreCompartiments],
+        check: |context| {
+            let ordre = context.get_option_info_string(IdInfo::OrdreCompartiments)?;
+            for (index, car) in ordre.char_indices().filter(|(_, car)| *car != '0') {
+                let num_compart = car.to_digit(10).and_then(|n| u8::try_from(n).ok())?;
+                let t_value = TValue::U8OrT(U8OrT::U8(num_compart));
+                if context
+                    .check_info_domain(IdInfo::NumeroCompartiment, &t_value)
+                    .is_err()
+                {
+                    return Some((
+                        IdInfo::OrdreCompartiments,
+                        raison_compartiment(RAISON_COMPARTIMENT_ABSENT, num_compart),
+                    ));
+                }
+                if ordre[..index].contains(car) {
+                    return Some((
+                        IdInfo::OrdreCompartiments,
+                        raison_compartiment(RAISON_COMPARTIMENT_EN_DOUBLE, num_compart),
+                    ));
+                }
+            }
+            None
+        },
+    },
+];
+
+/// Incohérence si une information 'finale' est renseignée sans l'information 'initiale'
+fn final_without_initial(
+    context: &Context,
+    id_info_final: IdInfo,
+    id_info: IdInfo,
+    raison: &str,
+) -> Option<(IdInfo, String)> {
+    let is_incoherent = context.get_option_info_t_value(id_info_final).is_some()
+        && context.get_option_info_t_value(id_info).is_none();
+    is_incoherent.then(|| (id_info_final, tr(raison)))
+}
+
+/// Raison d'une incohérence sur un compartiment : `raison` est traduite avant d'y placer le
+/// numéro du compartiment
+fn raison_compartiment(raison: &str, num_compart: u8) -> String {
+    tr(raison).replace("{}", &num_compart.to_string())
+}
+
+/// Diagnostic des informations d'une requête (toutes les anomalies, vide si aucune)
+/// Les informations qui ne concernent pas le véhicule (voir `ProfilVehicule`) ne sont pas
+/// nécessaires
+pub fn diagnostic_request(context: &Context, id_infos_request: &[IdInfo]) -> Vec<DiagnosticInfo> {
+    let mut diagnostic = vec![];
+
+    // Informations manquantes ou hors domaine
+    for id_info in id_infos_request {
+        if !context.is_info_relevant(*id_info) {
+            continue;
+        }
+        let option_anomalie = match context.get_option_info_t_value(*id_info) {
+            None => Some(Anomalie::Manquante),
+            Some(t_value) => context
+                .check_info_domain(*id_info, &t_value)
+                .err()
+                .map(|raison| Anomalie::HorsDomaine(tr(&raison))),
+        };
+        if let Some(anomalie) = option_anomalie {
+            diagnostic.push(DiagnosticInfo {
+                id_info: *id_info,
+                anomalie,
+            });
+        }
+    }
+
+    // Incohérences entre informations
+    for rule in COHERENCE_RULES
+        .iter()
+        .filter(|rule| rule.id_infos.iter().all(|id| id_infos_request.contains(id)))
+    {
+        if let Some((id_info, raison)) = (rule.check)(context) {
+            diagnostic.push(DiagnosticInfo {
+                id_info,
+                anomalie: Anomalie::Incoherente(raison),
+            });
+        }
+    }
+
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ProfilVehicule;
+    use crate::langue::{traduction, Langue};
+
+    #[test]
+    fn test_diagnostic_request() {
+        let mut context = Context::default();
+        context.set_profil_vehicule(ProfilVehicule::new(16, 5, 3).unwrap());
+        let id_infos_request = [
+            IdInfo::Predetermination,
+            IdInfo::CodeProduit,
+            IdInfo::CodeProduitFinal,
+            IdInfo::NumeroCompartiment,
+            IdInfo::NumeroCompartimentFinal,
+        ];
+
+        // Toutes les informations manquantes
+        let diagnostic = diagnostic_request(&context, &id_infos_request);
+        assert_eq!(diagnostic.len(), id_infos_request.len());
+        assert!(diagnostic
+            .iter()
+            .all(|diag| diag.anomalie == Anomalie::Manquante));
+
+        // Incohérence et valeur hors du profil du véhicule
+        context.set_info_u32(IdInfo::Predetermination, 1000);
+        context.set_info_u8(IdInfo::CodeProduitFinal, 2);
+        context.set_info_u8_or_t(IdInfo::NumeroCompartiment, U8OrT::U8(4));
+        context.set_info_u8_or_t(IdInfo::NumeroCompartimentFinal, U8OrT::U8(5));
+        context.set_profil_vehicule(ProfilVehicule::new(16, 3, 3).unwrap());
+        assert_eq!(
+            diagnostic_request(&context, &id_infos_request),
+            vec![
+                DiagnosticInfo {
+                    id_info: IdInfo::CodeProduit,
+                    anomalie: Anomalie::Manquante
+                },
+                DiagnosticInfo {
+                    id_info: IdInfo::NumeroCompartiment,
+                    anomalie: Anomalie::HorsDomaine("Valeur max. 3".to_string())
+                },
+                DiagnosticInfo {
+                    id_info: IdInfo::NumeroCompartimentFinal,
+                    anomalie: Anomalie::HorsDomaine("Valeur max. 3".to_string())
+                },
+                DiagnosticInfo {
+                    id_info: IdInfo::CodeProduitFinal,
+                    anomalie: Anomalie::Incoherente(
+                        "Code produit final sans code produit".to_string()
+                    )
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_ordre_compartiments() {
+        let mut context = Context::default();
+        context.set_profil_vehicule(ProfilVehicule::new(16, 5, 3).unwrap());
+        for (ordre, option_raison) in [
+            ("135", None),
+            ("136", Some("Compartiment 6 absent du véhicule")),
+            ("1300", None),
+            ("1351", Some("Compartiment 1 en double")),
+        ] {
+            context.set_info_string(IdInfo::OrdreCompartiments, ordre);
+            let diagnostic = diagnostic_request(&context, &[IdInfo::OrdreCompartiments]);
+            assert_eq!(
+                diagnostic.first().map(|diag| diag.anomalie.clone()),
+                option_raison.map(|raison| Anomalie::Incoherente(raison.to_string())),
+                "{ordre}"
+            );
+        }
+
+        // Les raisons sont traduites avant d'y placer le numéro du compartiment
+        for raison in [RAISON_COMPARTIMENT_ABSENT, RAISON_COMPARTIMENT_EN_DOUBLE] {
+            let traduit = traduction(Langue::Anglais, raison);
+            assert_ne!(traduit, raison);
+            assert!(traduit.contains("{}"));
+        }
+    }
+}
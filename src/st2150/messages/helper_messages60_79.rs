@@ -7,13 +7,15 @@
 
 use super::field::Field;
 use super::frame::Frame;
+use super::set_rep_info;
 use super::Edition2150;
 use super::IdInfo;
-use crate::context;
 use crate::context::Context;
+use crate::context::TValue;
 use crate::context::U8OrT;
 use crate::st2150::protocol;
 use crate::st2150::ProtocolError;
+use crate::st2150::ST2150;
 
 /// Configuration des différents messages pour un mouvement de produit
 struct MessageDefinition {
@@ -165,16 +167,16 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                 let prede = context
                     .get_option_info_u32(IdInfo::Predetermination)
                     .unwrap();
-                req.add_field(Field::encode_number(prede, 5)?);
+                req.add_info_field(id_info, Field::encode_number(prede, 5)?);
             }
             IdInfo::CodeProduit => {
                 let code_prod = context.get_option_info_u8(IdInfo::CodeProduit).unwrap();
                 Field::check_binary_domain(
                     "code produit",
                     code_prod,
-                    0_u8..=u8::try_from(context::NB_PRODUITS).unwrap(),
+                    0_u8..=context.get_profil_vehicule().nb_produits(),
                 )?;
-                req.add_field(Field::encode_binary(code_prod + b'0'));
+                req.add_info_field(id_info, Field::encode_binary(code_prod + b'0'));
             }
             IdInfo::CodeProduitFinal => {
                 let code_prod = context
@@ -183,9 +185,9 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                 Field::check_binary_domain(
                     "code produit",
                     code_prod,
-                    0_u8..=u8::try_from(context::NB_PRODUITS).unwrap(),
+                    0_u8..=context.get_profil_vehicule().nb_produits(),
                 )?;
-                req.add_field(Field::encode_binary(code_prod + b'0'));
+                req.add_info_field(id_info, Field::encode_binary(code_prod + b'0'));
             }
             IdInfo::NumeroCompartiment => {
                 let compart_num = context
@@ -196,12 +198,12 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                         Field::check_binary_domain(
                             "numéro compartiment",
                             num,
-                            0_u8..=u8::try_from(context::NB_COMPARTIMENTS).unwrap(),
+                            0_u8..=context.get_profil_vehicule().nb_compartiments(),
                         )?;
-                        req.add_field(Field::encode_binary(num + b'0'));
+                        req.add_info_field(id_info, Field::encode_binary(num + b'0'));
                     }
                     U8OrT::T => {
-                        req.add_field(Field::encode_char('T')?);
+                        req.add_info_field(id_info, Field::encode_char('T')?);
                     }
                 }
             }
@@ -214,12 +216,12 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                         Field::check_binary_domain(
                             "numéro compartiment final",
                             num,
-                            0_u8..=u8::try_from(context::NB_COMPARTIMENTS).unwrap(),
+                            0_u8..=context.get_profil_vehicule().nb_compartiments(),
                         )?;
-                        req.add_field(Field::encode_binary(num + b'0'));
+                        req.add_info_field(id_info, Field::encode_binary(num + b'0'));
                     }
                     U8OrT::T => {
-                        req.add_field(Field::encode_char('T')?);
+                        req.add_info_field(id_info, Field::encode_char('T')?);
                     }
                 }
             }
@@ -231,16 +233,16 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                 while compart_order.len() < 9 {
                     compart_order.push('0');
                 }
-                req.add_field(Field::encode_str(&compart_order, 9));
+                req.add_info_field(id_info, Field::encode_str(&compart_order, 9));
             }
             IdInfo::NumeroFlexible => {
                 let flexible_num = context.get_option_info_u8(IdInfo::NumeroFlexible).unwrap();
                 Field::check_binary_domain(
                     "numéro flexible",
                     flexible_num,
-                    0_u8..=u8::try_from(context::NB_FLEXIBLES).unwrap(),
+                    0_u8..=context.get_profil_vehicule().nb_flexibles(),
                 )?;
-                req.add_field(Field::encode_binary(flexible_num + b'0'));
+                req.add_info_field(id_info, Field::encode_binary(flexible_num + b'0'));
             }
             IdInfo::NumeroFlexibleFinal => {
                 let flexible_num = context
@@ -249,16 +251,16 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
                 Field::check_binary_domain(
                     "numéro flexible",
                     flexible_num,
-                    0_u8..=u8::try_from(context::NB_FLEXIBLES).unwrap(),
+                    0_u8..=context.get_profil_vehicule().nb_flexibles(),
                 )?;
-                req.add_field(Field::encode_binary(flexible_num + b'0'));
+                req.add_info_field(id_info, Field::encode_binary(flexible_num + b'0'));
             }
             IdInfo::FinirFlexibleVide => {
                 let finir_vide = context
                     .get_option_info_bool(IdInfo::FinirFlexibleVide)
                     .unwrap();
                 let finir_vide = if finir_vide { 'V' } else { '0' };
-                req.add_field(Field::encode_char(finir_vide)?);
+                req.add_info_field(id_info, Field::encode_char(finir_vide)?);
             }
             _ => {
                 panic!("IdInfo {id_info:?} n'est pas valide pour une requête mouvement de produit");
@@ -270,45 +272,52 @@ pub fn create_frame_request(message_num: u8, context: &Context) -> Result<Frame,
 }
 
 /// Longueur des différents champs dans la réponse à un message de mouvement de produit
-pub fn rep_len_fields(_message_num: u8) -> &'static [usize] {
-    &[1, 2]
+/// (voir `layout::MESSAGE_LAYOUTS`)
+pub fn rep_len_fields(message_num: u8) -> &'static [usize] {
+    super::rep_len_fields(message_num)
 }
 
 /// Mise à jour du contexte selon la réponse reçue à un message de mouvement de produit
 pub fn update_context_from_rep(
     _message_num: u8,
+    st2150: &mut ST2150,
     context: &mut Context,
     frame: &Frame,
 ) -> Result<(), ProtocolError> {
-    assert!(
-        frame.fields.len() == 2,
-        "Frame avec 2 champs attendus en réponse d'un message de mouvement de produit"
-    );
-
     // #0 - Ack ou Nack
-    match frame.fields[0].decode_binary()? {
-        protocol::ACK => {
-            context.set_info_bool(IdInfo::Ack, true);
-            context.set_info_bool(IdInfo::Nack, false);
-        }
-        protocol::NACK => {
-            context.set_info_bool(IdInfo::Ack, false);
-            context.set_info_bool(IdInfo::Nack, true);
-        }
-        n => {
-            context.set_info_bool(IdInfo::Ack, false);
-            context.set_info_bool(IdInfo::Nack, false);
-            return Err(ProtocolError::IllegalFieldCharDecode(
-                "Ack/Nack".to_string(),
-                frame.fields[0].clone(),
-                n,
-            ));
+    st2150.decode_rep_field(|| {
+        match frame.field(0)?.decode_binary()? {
+            protocol::ACK => {
+                context.set_info_bool(IdInfo::Ack, true);
+                context.set_info_bool(IdInfo::Nack, false);
+            }
+            protocol::NACK => {
+                context.set_info_bool(IdInfo::Ack, false);
+                context.set_info_bool(IdInfo::Nack, true);
+            }
+            n => {
+                context.set_info_bool(IdInfo::Ack, false);
+                context.set_info_bool(IdInfo::Nack, false);
+                return Err(ProtocolError::IllegalFieldCharDecode(
+                    "Ack/Nack".to_string(),
+                    frame.field(0)?.clone(),
+                    n,
+                ));
+            }
         }
-    }
+        Ok(())
+    })?;
 
     // #1 - Code erreur spécifique pour les requêtes de mouvement de produit
-    let code_erreur: u8 = frame.fields[1].decode_number()?;
-    context.set_info_u8(IdInfo::CodeErreurMouvementProduit, code_erreur);
+    st2150.decode_rep_field(|| {
+        let field = frame.field(1)?;
+        set_rep_info(
+            context,
+            IdInfo::CodeErreurMouvementProduit,
+            field,
+            TValue::U8(field.decode_number()?),
+        )
+    })?;
 
     Ok(())
 }
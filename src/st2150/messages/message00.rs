@@ -3,6 +3,7 @@
 use crate::context::Context;
 
 use super::frame;
+use super::rep_len_fields;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
@@ -54,7 +55,7 @@ impl CommonMessageTrait for Message00 {
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[1, 1, 1, 1, 1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -63,68 +64,83 @@ impl CommonMessageTrait for Message00 {
         // Mise à jour du contexte
 
         // #0 : En mesurage
-        match frame.fields[0].decode_char()? {
-            '0' => context.set_info_bool(IdInfo::EnMesurage, false),
-            '1' => context.set_info_bool(IdInfo::EnMesurage, true),
-            _ => {
-                return Err(ProtocolError::IllegalRepFieldValue(
-                    frame.fields[0].clone(),
-                    "en mesurage".to_string(),
-                    "'0' ou '1'".to_string(),
-                ))
+        st2150.decode_rep_field(|| {
+            match frame.field(0)?.decode_char()? {
+                '0' => context.set_info_bool(IdInfo::EnMesurage, false),
+                '1' => context.set_info_bool(IdInfo::EnMesurage, true),
+                _ => {
+                    return Err(ProtocolError::IllegalRepFieldValue(
+                        frame.field(0)?.clone(),
+                        "en mesurage".to_string(),
+                        "'0' ou '1'".to_string(),
+                    ))
+                }
             }
-        }
+            Ok(())
+        })?;
 
         // #1 : Code défaut
-        let code_defaut = frame.fields[1].decode_binary()?;
-        if (0x20..=0x9F).contains(&code_defaut) {
-            context.set_info_u8(IdInfo::CodeDefaut, code_defaut - 0x20);
-        } else {
-            return Err(ProtocolError::IllegalRepFieldValue(
-                frame.fields[1].clone(),
-                "code défaut".to_string(),
-                "Valeur entre 0x20 et 0x9F".to_string(),
-            ));
-        }
+        st2150.decode_rep_field(|| {
+            let code_defaut = frame.field(1)?.decode_binary()?;
+            if (0x20..=0x9F).contains(&code_defaut) {
+                context.set_info_u8(IdInfo::CodeDefaut, code_defaut - 0x20);
+            } else {
+                return Err(ProtocolError::IllegalRepFieldValue(
+                    frame.field(1)?.clone(),
+                    "code défaut".to_string(),
+                    "Valeur entre 0x20 et 0x9F".to_string(),
+                ));
+            }
+            Ok(())
+        })?;
 
         // #2 : Arrêt intermédiaire
-        match frame.fields[2].decode_char()? {
-            '0' => context.set_info_bool(IdInfo::ArretIntermediaire, false),
-            '1' => context.set_info_bool(IdInfo::ArretIntermediaire, true),
-            _ => {
-                return Err(ProtocolError::IllegalRepFieldValue(
-                    frame.fields[2].clone(),
-                    "arrêt intermédiaire".to_string(),
-                    "'0' ou '1'".to_string(),
-                ))
+        st2150.decode_rep_field(|| {
+            match frame.field(2)?.decode_char()? {
+                '0' => context.set_info_bool(IdInfo::ArretIntermediaire, false),
+                '1' => context.set_info_bool(IdInfo::ArretIntermediaire, true),
+                _ => {
+                    return Err(ProtocolError::IllegalRepFieldValue(
+                        frame.field(2)?.clone(),
+                        "arrêt intermédiaire".to_string(),
+                        "'0' ou '1'".to_string(),
+                    ))
+                }
             }
-        }
+            Ok(())
+        })?;
 
         // #3 : Forçage petit débit
-        match frame.fields[3].decode_char()? {
-            '0' => context.set_info_bool(IdInfo::ForcagePetitDebit, false),
-            '1' => context.set_info_bool(IdInfo::ForcagePetitDebit, true),
-            _ => {
-                return Err(ProtocolError::IllegalRepFieldValue(
-                    frame.fields[3].clone(),
-                    "forçage petit debit".to_string(),
-                    "'0' ou '1'".to_string(),
-                ))
+        st2150.decode_rep_field(|| {
+            match frame.field(3)?.decode_char()? {
+                '0' => context.set_info_bool(IdInfo::ForcagePetitDebit, false),
+                '1' => context.set_info_bool(IdInfo::ForcagePetitDebit, true),
+                _ => {
+                    return Err(ProtocolError::IllegalRepFieldValue(
+                        frame.field(3)?.clone(),
+                        "forçage petit debit".to_string(),
+                        "'0' ou '1'".to_string(),
+                    ))
+                }
             }
-        }
+            Ok(())
+        })?;
 
         // #4 : Mode connecté
-        match frame.fields[4].decode_char()? {
-            '0' => context.set_info_bool(IdInfo::ModeConnecte, false),
-            '1' => context.set_info_bool(IdInfo::ModeConnecte, true),
-            _ => {
-                return Err(ProtocolError::IllegalRepFieldValue(
-                    frame.fields[4].clone(),
-                    "mode connecté".to_string(),
-                    "'0' ou '1'".to_string(),
-                ))
+        st2150.decode_rep_field(|| {
+            match frame.field(4)?.decode_char()? {
+                '0' => context.set_info_bool(IdInfo::ModeConnecte, false),
+                '1' => context.set_info_bool(IdInfo::ModeConnecte, true),
+                _ => {
+                    return Err(ProtocolError::IllegalRepFieldValue(
+                        frame.field(4)?.clone(),
+                        "mode connecté".to_string(),
+                        "'0' ou '1'".to_string(),
+                    ))
+                }
             }
-        }
+            Ok(())
+        })?;
 
         // C'est tout bon
         Ok(())
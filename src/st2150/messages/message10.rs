@@ -3,11 +3,14 @@
 use crate::context::Context;
 
 use super::frame;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::TValue;
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 10;
@@ -53,7 +56,7 @@ impl CommonMessageTrait for Message10 {
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[8, 4, 5, 4, 5];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -62,33 +65,61 @@ impl CommonMessageTrait for Message10 {
         // Mise à jour du contexte
 
         // #0 : Totalisateur
-        context.set_info_u32(
-            IdInfo::Totalisateur,
-            frame.fields[0].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::Totalisateur,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #1 : Débit instantanée (1234 pour 123.4 m3/h)
-        let debit10 = frame.fields[1].decode_number::<u16>()?;
-        let debit10 = f32::from(debit10);
-
-        context.set_info_f32(IdInfo::DebitInstant, debit10 / 10_f32);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            let debit10 = f32::from(field.decode_number::<u16>()?);
+            set_rep_info(
+                context,
+                IdInfo::DebitInstant,
+                field,
+                TValue::F32(debit10 / 10_f32),
+            )
+        })?;
 
         // #2 : Quantité courante
-        context.set_info_u32(
-            IdInfo::QuantitePrincipale,
-            frame.fields[2].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantitePrincipale,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #3 : Température instantanée +123 pour 12.3°C
-        let tempe10 = frame.fields[3].decode_signed_number::<i16>()?;
-        let tempe10 = f32::from(tempe10);
-        context.set_info_f32(IdInfo::TemperatureInstant, tempe10 / 10_f32);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            let tempe10 = f32::from(field.decode_signed_number::<i16>()?);
+            set_rep_info(
+                context,
+                IdInfo::TemperatureInstant,
+                field,
+                TValue::F32(tempe10 / 10_f32),
+            )
+        })?;
 
         // #4 : Prédétermination
-        context.set_info_u32(
-            IdInfo::Predetermination,
-            frame.fields[4].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(4)?;
+            set_rep_info(
+                context,
+                IdInfo::Predetermination,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -99,7 +130,7 @@ impl CommonMessageTrait for Message10 {
 mod tests {
     use super::*;
     use crate::context::Context;
-    use crate::st2150::protocol;
+    use crate::st2150::{protocol, DecodingMode};
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
 
@@ -195,4 +226,79 @@ mod tests {
             Some(12345)
         );
     }
+
+    #[test]
+    fn test_message10_lenient() {
+        // Réponse hors spécification : Un champ en trop et une température incorrecte
+        #[rustfmt::skip]
+        let reponse = [
+            protocol::STX,
+            b'1', b'0', // Numéro de message
+            protocol::SEPARATOR,
+            b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', // Totalisateur
+            protocol::SEPARATOR,
+            b'1', b'2', b'3', b'4', // Débit 1234 pour 123.4
+            protocol::SEPARATOR,
+            b'1', b'2', b'3', b'4', b'5', // Quantité
+            protocol::SEPARATOR,
+            b'+', b'1', b'X', b'3', // Température incorrecte
+            protocol::SEPARATOR,
+            b'1', b'2', b'3', b'4', b'5', // Prédétermination
+            protocol::SEPARATOR,
+            b'9', // Champ en trop
+            protocol::SEPARATOR,
+            b'B', b'B', // Checksum
+            protocol::ETX,
+        ];
+
+        // En mode strict (par défaut), la réponse est rejetée
+        let mut context = Context::default();
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+        fake_port.will_read(&reponse);
+        let mut st = ST2150::new(fake_port);
+        assert_eq!(
+            st.do_message_vacation(&mut context, MESSAGE_NUM),
+            Err(ProtocolError::BadMessageLen(40, 38))
+        );
+        assert!(context.get_option_info_u32(IdInfo::Totalisateur).is_none());
+
+        // En mode tolérant, tous les champs corrects sont décodés avec des avertissements
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+        fake_port.will_read(&reponse);
+        let mut st = ST2150::new(fake_port);
+        st.decoding_mode = DecodingMode::Lenient;
+        assert_eq!(st.do_message_vacation(&mut context, MESSAGE_NUM), Ok(()));
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Totalisateur),
+            Some(12_345_678)
+        );
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(12345)
+        );
+        assert!(context
+            .get_option_info_f32(IdInfo::TemperatureInstant)
+            .is_none());
+        assert_eq!(st.last_warnings.len(), 2);
+        assert_eq!(st.last_warnings[0], ProtocolError::BadFieldsCount(6, 5));
+        assert!(matches!(
+            st.last_warnings[1],
+            ProtocolError::IllegalFieldCharDecode(_, _, b'X')
+        ));
+
+        // En mode tolérant, la réponse plus longue que prévu reçue par morceaux est lue
+        // jusqu'à son ETX (et pas seulement jusqu'à la longueur attendue)
+        let mut context = Context::default();
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+        fake_port.will_read_chunks(&reponse, 2);
+        let mut st = ST2150::new(fake_port);
+        st.decoding_mode = DecodingMode::Lenient;
+        assert_eq!(st.do_message_vacation(&mut context, MESSAGE_NUM), Ok(()));
+        assert_eq!(st.last_rep, reponse);
+        assert_eq!(
+            context.get_option_info_u32(IdInfo::Predetermination),
+            Some(12345)
+        );
+        assert_eq!(st.last_warnings[0], ProtocolError::BadFieldsCount(6, 5));
+    }
 }
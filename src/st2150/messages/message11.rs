@@ -2,12 +2,17 @@
 
 use crate::context::Context;
 
+use super::field::Field;
 use super::frame;
+use super::illegal_rep_value;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::TValue;
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 11;
@@ -54,6 +59,7 @@ impl CommonMessageTrait for Message11 {
             IdInfo::QuantiteCompartiment(8),
             IdInfo::CodeProduitCompartiment(9),
             IdInfo::QuantiteCompartiment(9),
+            IdInfo::QuantiteTotaleCompartiments,
             IdInfo::PresenceRemorque,
             IdInfo::CodeProduitCollecteur,
             IdInfo::CodeProduitPartieCommune,
@@ -62,6 +68,23 @@ impl CommonMessageTrait for Message11 {
         ]
     }
 
+    fn id_infos_response_fields(&self, _nb_fields: usize) -> Vec<Vec<IdInfo>> {
+        let mut id_infos_fields = vec![vec![IdInfo::NombreCompartiments]];
+        for compart_num in 1..=9 {
+            id_infos_fields.push(vec![IdInfo::CodeProduitCompartiment(compart_num)]);
+            id_infos_fields.push(vec![IdInfo::QuantiteCompartiment(compart_num)]);
+        }
+        id_infos_fields.push(vec![IdInfo::PresenceRemorque]);
+        // Codes produits dans la tuyauterie dans un seul champ
+        id_infos_fields.push(vec![
+            IdInfo::CodeProduitCollecteur,
+            IdInfo::CodeProduitPartieCommune,
+            IdInfo::CodeProduitFlexible1,
+            IdInfo::CodeProduitFlexible2,
+        ]);
+        id_infos_fields
+    }
+
     fn do_vacation(&self, st2150: &mut ST2150, context: &mut Context) -> Result<(), ProtocolError> {
         // Contexte OK ?
         Self::availability(self, context)?;
@@ -72,9 +95,7 @@ impl CommonMessageTrait for Message11 {
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[
-            1, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 4,
-        ];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
 
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
@@ -84,50 +105,89 @@ impl CommonMessageTrait for Message11 {
         // Mise à jour du contexte
 
         // #0 - Nombre compartiments
-        context.set_info_u8(
-            IdInfo::NombreCompartiments,
-            frame.fields[0].decode_number::<u8>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::NombreCompartiments,
+                field,
+                TValue::U8(field.decode_number::<u8>()?),
+            )
+        })?;
 
         // #1 à ?? : 9 fois par compartiment codeProduit et Quantité
         let mut index_champ = 1;
         for compart_num in 1..=9 {
-            context.set_info_u8(
-                IdInfo::CodeProduitCompartiment(compart_num),
-                frame.fields[index_champ].decode_number::<u8>()?,
-            );
-            context.set_info_u32(
-                IdInfo::QuantiteCompartiment(compart_num),
-                frame.fields[index_champ + 1].decode_number::<u32>()?,
-            );
+            st2150.decode_rep_field(|| {
+                let field = frame.field(index_champ)?;
+                set_rep_info(
+                    context,
+                    IdInfo::CodeProduitCompartiment(compart_num),
+                    field,
+                    TValue::U8(field.decode_number::<u8>()?),
+                )
+            })?;
+            st2150.decode_rep_field(|| {
+                let field = frame.field(index_champ + 1)?;
+                set_rep_info(
+                    context,
+                    IdInfo::QuantiteCompartiment(compart_num),
+                    field,
+                    TValue::U32(field.decode_number::<u32>()?),
+                )
+            })?;
             index_champ += 2;
         }
 
         // #ensuite #(index_champ) : Présence remorque
-        let presence_remorque = matches!(frame.fields[index_champ].decode_char()?, 'T');
-        context.set_info_bool(IdInfo::PresenceRemorque, presence_remorque);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(index_champ)?;
+            let presence_remorque = match field.decode_char()? {
+                'T' => true,
+                'F' => false,
+                _ => {
+                    return Err(illegal_rep_value(context, IdInfo::PresenceRemorque, field)(
+                        "'T' ou 'F' attendu".to_string(),
+                    ))
+                }
+            };
+            set_rep_info(
+                context,
+                IdInfo::PresenceRemorque,
+                field,
+                TValue::Bool(presence_remorque),
+            )
+        })?;
 
         // #et enfin (index_champ+1) : Les codes produits dans la tuyauterie
         // 4 fois un u8 avec #0: Collecteur, #1: partie commune, #2: flexible1 et #3: flexible2
-        let code_produits_tuyauterie = frame.fields[index_champ + 1].decode_as_vec();
-        if code_produits_tuyauterie.len() != 4 {
-            return Err(ProtocolError::IllegalRepFieldValue(
-                frame.fields[index_champ + 1].clone(),
-                "produits tuyauterie".to_string(),
-                "4 x code produit".to_string(),
-            ));
-        }
-        let code_produits_tuyauterie: Vec<u8> = code_produits_tuyauterie
-            .iter()
-            .map(|code| *code - b'0')
-            .collect();
-        context.set_info_u8(IdInfo::CodeProduitCollecteur, code_produits_tuyauterie[0]);
-        context.set_info_u8(
-            IdInfo::CodeProduitPartieCommune,
-            code_produits_tuyauterie[1],
-        );
-        context.set_info_u8(IdInfo::CodeProduitFlexible1, code_produits_tuyauterie[2]);
-        context.set_info_u8(IdInfo::CodeProduitFlexible2, code_produits_tuyauterie[3]);
+        st2150.decode_rep_field(|| {
+            let field_tuyauterie = frame.field(index_champ + 1)?;
+            let code_produits_tuyauterie = field_tuyauterie.decode_as_vec();
+            if code_produits_tuyauterie.len() != 4 {
+                return Err(ProtocolError::IllegalRepFieldValue(
+                    field_tuyauterie.clone(),
+                    "produits tuyauterie".to_string(),
+                    "4 x code produit".to_string(),
+                ));
+            }
+            let code_produits_tuyauterie = code_produits_tuyauterie
+                .iter()
+                .map(|code| Field::new(&[*code]).decode_number::<u8>())
+                .collect::<Result<Vec<u8>, ProtocolError>>()?;
+            for (id_info, code_produit) in [
+                IdInfo::CodeProduitCollecteur,
+                IdInfo::CodeProduitPartieCommune,
+                IdInfo::CodeProduitFlexible1,
+                IdInfo::CodeProduitFlexible2,
+            ]
+            .into_iter()
+            .zip(code_produits_tuyauterie)
+            {
+                set_rep_info(context, id_info, field_tuyauterie, TValue::U8(code_produit))?;
+            }
+            Ok(())
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -239,6 +299,7 @@ mod tests {
                 Some(u32::try_from(compart_num).unwrap() * 1000_u32)
             );
         }
+        assert_eq!(context.get_option_info_u32(IdInfo::QuantiteTotaleCompartiments), Some(45_000));
         assert_eq!(context.get_option_info_bool(IdInfo::PresenceRemorque), Some(true));
 
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduitCollecteur), Some(1));
@@ -249,4 +310,98 @@ mod tests {
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduitFlexible1), Some(3));
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduitFlexible2), Some(4));
     }
+
+    #[test]
+    fn test_message11_tuyauterie_incorrecte() {
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+
+        // Contexte avec les valeurs d'une vacation précédente
+        let mut context = Context::default();
+        context.set_info_u8(IdInfo::NombreCompartiments, 2);
+        context.set_info_u32(IdInfo::QuantiteCompartiment(1), 500);
+
+        fake_port.should_write(&[
+            protocol::STX,
+            b'1',
+            b'1',
+            protocol::SEPARATOR,
+            70,
+            69,
+            protocol::ETX,
+        ]);
+
+        // Réponse simulée : Tous les compartiments sont corrects mais un code produit
+        // de la tuyauterie est incorrect ('X')
+        let mut rep = vec![
+            protocol::STX,
+            b'1',
+            b'1',
+            protocol::SEPARATOR,
+            b'9',
+            protocol::SEPARATOR,
+        ];
+        for compart_num in 1..=9 {
+            rep.extend_from_slice(&[b'0' + compart_num, protocol::SEPARATOR]);
+            rep.extend_from_slice(&[
+                b'0',
+                b'0' + compart_num,
+                b'0',
+                b'0',
+                b'0',
+                protocol::SEPARATOR,
+            ]);
+        }
+        rep.extend_from_slice(&[
+            b'T',
+            protocol::SEPARATOR,
+            b'1',
+            b'2',
+            b'X',
+            b'4',
+            protocol::SEPARATOR,
+        ]);
+        rep.extend_from_slice(&[b'0', b'2', protocol::ETX]);
+        fake_port.will_read(&rep);
+
+        let mut st = ST2150::new(fake_port);
+        assert!(matches!(
+            st.do_message_vacation(&mut context, MESSAGE_NUM),
+            Err(ProtocolError::IllegalFieldCharDecode(_, _, b'X'))
+        ));
+
+        // Aucune valeur de la réponse n'est reprise dans le contexte et les valeurs
+        // de la vacation précédente ne sont plus valides
+        for id_info in Message11::default().id_infos_response() {
+            assert_eq!(
+                context.get_info_to_string(id_info, "None"),
+                "None",
+                "{id_info:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_message11_presence_remorque_incorrecte() {
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+        fake_port.should_write(&frame::Frame::new(MESSAGE_NUM).to_frame());
+
+        // Réponse simulée : La présence remorque n'est ni 'T' ni 'F'
+        let mut rep = frame::Frame::new(MESSAGE_NUM);
+        rep.add_field(Field::encode_binary(b'9'));
+        for compart_num in 1..=9 {
+            rep.add_field(Field::encode_binary(b'0' + compart_num));
+            rep.add_field(Field::encode_number(1000, 5).unwrap());
+        }
+        rep.add_field(Field::encode_binary(b'X'));
+        rep.add_field(Field::new(b"1234"));
+        fake_port.will_read(&rep.to_frame());
+
+        let mut context = Context::default();
+        let mut st = ST2150::new(fake_port);
+        assert!(matches!(
+            st.do_message_vacation(&mut context, MESSAGE_NUM),
+            Err(ProtocolError::IllegalRepFieldValue(_, _, _))
+        ));
+        assert_eq!(context.get_option_info_bool(IdInfo::PresenceRemorque), None);
+    }
 }
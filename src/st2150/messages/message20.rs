@@ -1,10 +1,10 @@
 //! Message 20 : Présélection
 
-use crate::context;
 use crate::context::{Context, IdInfo};
 
 use super::field::Field;
 use super::frame;
+use super::rep_len_fields;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
@@ -49,22 +49,22 @@ impl CommonMessageTrait for Message20 {
         let prede = context
             .get_option_info_u32(IdInfo::Predetermination)
             .unwrap();
-        req.add_field(Field::encode_number(prede, 5)?);
+        req.add_info_field(IdInfo::Predetermination, Field::encode_number(prede, 5)?);
 
         // Code produit
         let code_prod = context.get_option_info_u8(IdInfo::CodeProduit).unwrap();
         Field::check_binary_domain(
             "code produit",
             code_prod,
-            0_u8..=u8::try_from(context::NB_PRODUITS).unwrap(),
+            0_u8..=context.get_profil_vehicule().nb_produits(),
         )?;
-        req.add_field(Field::encode_binary(code_prod + b'0'));
+        req.add_info_field(IdInfo::CodeProduit, Field::encode_binary(code_prod + b'0'));
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -3,11 +3,15 @@
 use crate::context::Context;
 
 use super::frame;
+use super::illegal_rep_value;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::{Heure, TValue};
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 21;
@@ -51,6 +55,19 @@ impl CommonMessageTrait for Message21 {
         ]
     }
 
+    fn id_infos_response_fields(&self, nb_fields: usize) -> Vec<Vec<IdInfo>> {
+        // 2 réponses possibles : NACK ou compte rendu de mesurage
+        if nb_fields == 1 {
+            vec![vec![IdInfo::Nack]]
+        } else {
+            self.id_infos_response()
+                .into_iter()
+                .filter(|id_info| *id_info != IdInfo::Nack)
+                .map(|id_info| vec![id_info])
+                .collect()
+        }
+    }
+
     fn do_vacation(&self, st2150: &mut ST2150, context: &mut Context) -> Result<(), ProtocolError> {
         // Contexte OK ?
         Self::availability(self, context)?;
@@ -61,12 +78,13 @@ impl CommonMessageTrait for Message21 {
 
         // Réception réponse (2 réponses possibles)
         let mut buffer = [0; 200];
-        let lens_expected = &[5, 4, 5, 8, 3, 3, 3, 1, 4, 4];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue : 2 réponses possibles : NACK ou compte rendu de mesurage
-        // On tente d'abord de décoder un NACK (dans une trame correcte)
-        if let Ok(frame) = st2150.try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, &[1]) {
+        // On tente d'abord de décoder un NACK (dans une trame correcte, toujours en décodage strict
+        // car une trame tolérante accepterait ici n'importe quel compte rendu de mesurage)
+        if let Ok(frame) = frame::Frame::try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, &[1]) {
             context.set_info_bool(IdInfo::Nack, frame.is_nack());
             return Ok(());
         }
@@ -78,63 +96,136 @@ impl CommonMessageTrait for Message21 {
         // Mise à jour du contexte
 
         // #0 : Quantité principale
-        context.set_info_u32(
-            IdInfo::QuantitePrincipale,
-            frame.fields[0].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantitePrincipale,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #1 : Température moyenne +123 pour 12.3°C
-        let tempe10 = frame.fields[1].decode_signed_number::<i16>()?;
-        let tempe10 = f32::from(tempe10);
-        context.set_info_f32(IdInfo::TemperatureMoyen, tempe10 / 10_f32);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            let tempe10 = f32::from(field.decode_signed_number::<i16>()?);
+            set_rep_info(
+                context,
+                IdInfo::TemperatureMoyen,
+                field,
+                TValue::F32(tempe10 / 10_f32),
+            )
+        })?;
 
         // #2 : Quantité secondaire
-        context.set_info_u32(
-            IdInfo::QuantiteSecondaire,
-            frame.fields[2].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantiteSecondaire,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #2 : Quantité courante
-        context.set_info_u32(
-            IdInfo::QuantitePrincipale,
-            frame.fields[2].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantitePrincipale,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #3 : Totalisateur
-        context.set_info_u32(
-            IdInfo::Totalisateur,
-            frame.fields[3].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            set_rep_info(
+                context,
+                IdInfo::Totalisateur,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #4 : Index sans Raz
-        context.set_info_u16(
-            IdInfo::IndexSansRaz,
-            frame.fields[4].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(4)?;
+            set_rep_info(
+                context,
+                IdInfo::IndexSansRaz,
+                field,
+                TValue::U16(field.decode_number::<u16>()?),
+            )
+        })?;
 
         // #5 : Index journalier
-        context.set_info_u16(
-            IdInfo::IndexJournalier,
-            frame.fields[5].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(5)?;
+            set_rep_info(
+                context,
+                IdInfo::IndexJournalier,
+                field,
+                TValue::U16(field.decode_number::<u16>()?),
+            )
+        })?;
 
         // #6 : Quantième
-        context.set_info_u16(IdInfo::Quantieme, frame.fields[6].decode_number::<u16>()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(6)?;
+            set_rep_info(
+                context,
+                IdInfo::Quantieme,
+                field,
+                TValue::U16(field.decode_number::<u16>()?),
+            )
+        })?;
 
         // #7: Code produit
-        context.set_info_u8(IdInfo::CodeProduit, frame.fields[7].decode_number::<u8>()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(7)?;
+            set_rep_info(
+                context,
+                IdInfo::CodeProduit,
+                field,
+                TValue::U8(field.decode_number::<u8>()?),
+            )
+        })?;
 
         // #8 : Heure de début
-        context.set_info_u16(
-            IdInfo::HeureHHMMDebut,
-            frame.fields[8].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(8)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // #8 : Heure de fin
-        context.set_info_u16(
-            IdInfo::HeureHHMMFin,
-            frame.fields[9].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(9)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -264,12 +355,12 @@ mod tests {
         assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), Some(123));
         assert_eq!(context.get_option_info_u8(IdInfo::CodeProduit), Some(1));
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMDebut),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMDebut),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMFin),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMFin),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
     }
 
@@ -317,4 +408,16 @@ mod tests {
         // Vérification de ce qui a été mis à jour dans le contexte
         assert_eq!(context.get_option_info_bool(IdInfo::Nack), Some(true));
     }
+
+    #[test]
+    fn test_message21_id_infos_response_fields() {
+        let message = Message21::default();
+        assert_eq!(
+            message.id_infos_response_fields(1),
+            vec![vec![IdInfo::Nack]]
+        );
+        let id_infos_fields = message.id_infos_response_fields(10);
+        assert_eq!(id_infos_fields.len(), 10);
+        assert_eq!(id_infos_fields[0], vec![IdInfo::QuantitePrincipale]);
+    }
 }
@@ -4,6 +4,7 @@ use crate::context::Context;
 
 use super::field::Field;
 use super::frame;
+use super::rep_len_fields;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
@@ -49,25 +50,20 @@ impl CommonMessageTrait for Message22 {
             None => String::new(),
             Some(txt) => txt.trim().to_string(),
         };
+        // Longueur en octets dans le jeu de caractères des trames
+        let identification_tag = st2150.code_page.encode(&identification_tag)?;
 
         // #0 : Longueur de l'identification tag sur 3
         req.add_field(Field::encode_number(identification_tag.len(), 3)?);
 
         // #1 : Identification tag
-        if identification_tag.is_empty() {
-            req.add_field(Field::new(&[]));
-        } else {
-            req.add_field(Field::encode_str(
-                &identification_tag,
-                identification_tag.len(),
-            ));
-        }
+        req.add_info_field(IdInfo::IdentificationTag, Field::new(&identification_tag));
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -89,6 +85,7 @@ mod tests {
     use super::*;
     use crate::context::Context;
     use crate::st2150::protocol;
+    use crate::st2150::CodePage;
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
 
@@ -150,4 +147,82 @@ mod tests {
         assert_eq!(context.get_option_info_bool(IdInfo::Nack), Some(false));
         assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(true));
     }
+
+    #[test]
+    fn test_message22_dry_run() {
+        // Pas d'écriture attendue sur le FAKE port
+        let mut st = ST2150::new(SerialCom::new("FAKE", 9600));
+        let mut context = Context::default();
+
+        // Information manquante
+        assert!(matches!(
+            st.message_dry_run(&context, MESSAGE_NUM),
+            Err(ProtocolError::ContextMissing(_))
+        ));
+
+        // Trame construite, avec l'information de chaque champ
+        context.set_info_string(IdInfo::IdentificationTag, "ABCDE");
+        let req = st.message_dry_run(&context, MESSAGE_NUM).unwrap();
+        assert_eq!(req.id_infos, vec![None, Some(IdInfo::IdentificationTag)]);
+        assert_eq!(
+            req.to_frame(),
+            [
+                &[protocol::STX, b'2', b'2', protocol::SEPARATOR][..],
+                b"005",
+                &[protocol::SEPARATOR],
+                b"ABCDE",
+                &[protocol::SEPARATOR, 56, 65, protocol::ETX],
+            ]
+            .concat()
+        );
+        assert!(st.last_req.is_empty());
+
+        // Encodage impossible dans le jeu de caractères des trames
+        st.code_page = CodePage::Latin1;
+        context.set_info_string(IdInfo::IdentificationTag, "AB€");
+        assert_eq!(
+            st.message_dry_run(&context, MESSAGE_NUM),
+            Err(ProtocolError::IllegalCharEncoding(
+                "ISO-8859-1".to_string(),
+                '€'
+            ))
+        );
+    }
+
+    #[test]
+    fn test_message22_separateur_dans_tag() {
+        // 'þ' est codé 0xFE (SEPARATOR) en ISO-8859-1 : Rien ne doit être envoyé
+        let mut st = ST2150::new(SerialCom::new("FAKE", 9600));
+        st.code_page = CodePage::Latin1;
+        let mut context = Context::default();
+        context.set_info_string(IdInfo::IdentificationTag, "AþB");
+        // Réponse d'une vacation précédente
+        context.set_info_bool(IdInfo::Ack, true);
+        context.set_info_bool(IdInfo::Nack, false);
+
+        assert_eq!(
+            st.do_message_vacation(&mut context, MESSAGE_NUM),
+            Err(ProtocolError::IllegalCharEncoding(
+                "ISO-8859-1".to_string(),
+                'þ'
+            ))
+        );
+        assert!(st.last_req.is_empty());
+
+        // Rien n'a été envoyé : La réponse précédente reste valide
+        assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(true));
+        assert_eq!(context.get_option_info_bool(IdInfo::Nack), Some(false));
+
+        // Idem pour STX et ETX, quel que soit le jeu de caractères
+        st.code_page = CodePage::Utf8;
+        context.set_info_string(IdInfo::IdentificationTag, "A\u{2}B\u{3}");
+        assert_eq!(
+            st.do_message_vacation(&mut context, MESSAGE_NUM),
+            Err(ProtocolError::IllegalCharEncoding(
+                "UTF-8".to_string(),
+                '\u{2}'
+            ))
+        );
+        assert!(st.last_req.is_empty());
+    }
 }
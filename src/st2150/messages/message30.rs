@@ -3,11 +3,15 @@
 use crate::context::Context;
 
 use super::frame;
+use super::illegal_rep_value;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::{DateHeure, TValue};
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 30;
@@ -52,31 +56,61 @@ impl CommonMessageTrait for Message30 {
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[15, 10, 12, 1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
         let frame = st2150.try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, lens_expected)?;
 
         // Mise à jour du contexte
+        let code_page = st2150.code_page;
 
         // #0 : Référence compteur et immatriculation véhicule
-        context.set_info_string(
-            IdInfo::ReferenceEtImmatriculation,
-            &frame.fields[0].decode_str()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::ReferenceEtImmatriculation,
+                field,
+                TValue::String(field.decode_str_code_page(code_page)?),
+            )
+        })?;
 
         // #1 : Version logiciel
-        context.set_info_string(IdInfo::VersionLogiciel, &frame.fields[1].decode_str()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            set_rep_info(
+                context,
+                IdInfo::VersionLogiciel,
+                field,
+                TValue::String(field.decode_str_code_page(code_page)?),
+            )
+        })?;
 
         // #2 : Date & heure
-        context.set_info_u64(
-            IdInfo::DateAAMMJJHeureHHMMSS,
-            frame.fields[2].decode_number::<u64>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            let date_heure = DateHeure::from_aammjjhhmmss(field.decode_number()?).map_err(
+                illegal_rep_value(context, IdInfo::DateAAMMJJHeureHHMMSS, field),
+            )?;
+            set_rep_info(
+                context,
+                IdInfo::DateAAMMJJHeureHHMMSS,
+                field,
+                TValue::DateHeure(date_heure),
+            )
+        })?;
 
         // #3 : Type compteur
-        context.set_info_u8(IdInfo::TypeCompteur, frame.fields[3].decode_number::<u8>()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            set_rep_info(
+                context,
+                IdInfo::TypeCompteur,
+                field,
+                TValue::U8(field.decode_number::<u8>()?),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -87,7 +121,8 @@ impl CommonMessageTrait for Message30 {
 mod tests {
     use super::*;
     use crate::context::Context;
-    use crate::st2150::protocol;
+    use crate::st2150::field::Field;
+    use crate::st2150::{protocol, DecodingMode};
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
 
@@ -182,9 +217,81 @@ mod tests {
             Some("1.00010101".to_string())
         );
         assert_eq!(
-            context.get_option_info_u64(IdInfo::DateAAMMJJHeureHHMMSS),
-            Some(99_12_31_23_59_59_u64)
+            context.get_option_info_date_heure(IdInfo::DateAAMMJJHeureHHMMSS),
+            Some(DateHeure::from_aammjjhhmmss(99_12_31_23_59_59).unwrap())
         );
         assert_eq!(context.get_option_info_u8(IdInfo::TypeCompteur), Some(0));
     }
+
+    #[test]
+    fn test_message30_hors_domaine() {
+        // Réponse avec une date impossible (31 février) et un type compteur inconnu
+        let date_heure = b"990231235959";
+        let mut rep = vec![protocol::STX, b'3', b'0', protocol::SEPARATOR];
+        rep.extend_from_slice(b"12345ABC1234XYZ");
+        rep.push(protocol::SEPARATOR);
+        rep.extend_from_slice(b"1.00010101");
+        rep.push(protocol::SEPARATOR);
+        rep.extend_from_slice(date_heure);
+        rep.push(protocol::SEPARATOR);
+        rep.push(b'3'); // Type compteur 0:Vm, 1:Vb, 2:Masse
+        rep.extend_from_slice(&[protocol::SEPARATOR, b'F', b'F', protocol::ETX]);
+
+        for decoding_mode in [DecodingMode::Strict, DecodingMode::Lenient] {
+            let mut fake_port = SerialCom::new("FAKE", 9600);
+            fake_port.should_write(&[
+                protocol::STX,
+                b'3',
+                b'0',
+                protocol::SEPARATOR,
+                b'F',
+                b'D',
+                protocol::ETX,
+            ]);
+            fake_port.will_read(&rep);
+
+            let mut context = Context::default();
+            let mut st = ST2150::new(fake_port);
+            st.decoding_mode = decoding_mode;
+
+            let err_date = ProtocolError::IllegalRepFieldValue(
+                Field::new(date_heure),
+                context
+                    .get_info_label(IdInfo::DateAAMMJJHeureHHMMSS)
+                    .to_string(),
+                "Jour de 01 à 28".to_string(),
+            );
+            let err_type_compteur = ProtocolError::IllegalRepFieldValue(
+                Field::new(b"3"),
+                context.get_info_label(IdInfo::TypeCompteur).to_string(),
+                "Valeur max. 2".to_string(),
+            );
+
+            match decoding_mode {
+                // Arrêt sur la première valeur hors domaine
+                DecodingMode::Strict => {
+                    assert_eq!(
+                        st.do_message_vacation(&mut context, MESSAGE_NUM),
+                        Err(err_date)
+                    );
+                }
+                // Toutes les valeurs hors domaine sont signalées
+                DecodingMode::Lenient => {
+                    assert_eq!(st.do_message_vacation(&mut context, MESSAGE_NUM), Ok(()));
+                    assert_eq!(st.last_warnings, vec![err_date, err_type_compteur]);
+                    assert_eq!(
+                        context.get_option_info_string(IdInfo::VersionLogiciel),
+                        Some("1.00010101".to_string())
+                    );
+                }
+            }
+
+            // Les valeurs hors domaine ne sont pas reprises dans le contexte
+            assert_eq!(
+                context.get_option_info_date_heure(IdInfo::DateAAMMJJHeureHHMMSS),
+                None
+            );
+            assert_eq!(context.get_option_info_u8(IdInfo::TypeCompteur), None);
+        }
+    }
 }
@@ -4,11 +4,14 @@ use crate::context::Context;
 
 use super::field::Field;
 use super::frame;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::TValue;
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 31;
@@ -46,13 +49,13 @@ impl CommonMessageTrait for Message31 {
         let mut req = frame::Frame::new(MESSAGE_NUM);
 
         let quantieme = context.get_option_info_u16(IdInfo::Quantieme).unwrap();
-        req.add_field(Field::encode_number(quantieme, 3)?);
+        req.add_info_field(IdInfo::Quantieme, Field::encode_number(quantieme, 3)?);
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[3];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -61,10 +64,15 @@ impl CommonMessageTrait for Message31 {
         // Mise à jour du contexte
 
         // #0 : Nombre de mesurages pour le quantième
-        context.set_info_u16(
-            IdInfo::NbMesuragesQuantieme,
-            frame.fields[0].decode_number()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::NbMesuragesQuantieme,
+                field,
+                TValue::U16(field.decode_number()?),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -74,8 +82,9 @@ impl CommonMessageTrait for Message31 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::context::Context;
+    use crate::context::{Context, Provenance};
     use crate::st2150::protocol;
+    use crate::st2150::vacations::ValeurInfo;
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
 
@@ -134,4 +143,105 @@ mod tests {
             Some(12)
         );
     }
+
+    #[test]
+    fn test_message31_journal_vacations() {
+        let mut st = ST2150::new(SerialCom::new("FAKE", 9600));
+        let mut context = Context::default();
+        let req = [
+            protocol::STX,
+            b'3',
+            b'1',
+            protocol::SEPARATOR,
+            b'1', // Quantième
+            b'2',
+            b'3',
+            protocol::SEPARATOR,
+            51, // Checksum
+            50,
+            protocol::ETX,
+        ];
+        let rep = [
+            protocol::STX,
+            b'3',
+            b'1',
+            protocol::SEPARATOR,
+            b'0', // Nombre de mesurages pour ce quantième
+            b'1',
+            b'2',
+            protocol::SEPARATOR,
+            b'3', // Checksum
+            b'1',
+            protocol::ETX,
+        ];
+        let valeurs = |valeur_infos: &[ValeurInfo]| -> Vec<(IdInfo, TValue)> {
+            valeur_infos
+                .iter()
+                .map(|valeur_info| (valeur_info.id_info, valeur_info.t_value.clone()))
+                .collect()
+        };
+
+        // Vacation mémorisée dans le journal
+        context.set_info_u16(IdInfo::Quantieme, 123);
+        st.port.should_write(&req);
+        st.port.will_read(&rep);
+        assert_eq!(st.do_message_vacation(&mut context, MESSAGE_NUM), Ok(()));
+        assert_eq!(st.vacations.len(), 1);
+        let trace = st.vacations[0].clone();
+        assert_eq!(trace.message_num, MESSAGE_NUM);
+        assert_eq!(
+            valeurs(&trace.request_values),
+            vec![(IdInfo::Quantieme, TValue::U16(123))]
+        );
+        assert_eq!(
+            valeurs(&trace.response_values),
+            vec![(IdInfo::NbMesuragesQuantieme, TValue::U16(12))]
+        );
+        assert_eq!(trace.req, req);
+        assert_eq!(trace.rep, rep);
+        assert_eq!(trace.compte_rendu(), "OK");
+
+        // Libellés et valeurs tels qu'affichés lors de la vacation
+        assert_eq!(
+            trace.request_values[0].label,
+            context.get_info_label(IdInfo::Quantieme)
+        );
+        assert_eq!(trace.request_values[0].valeur, "123");
+        assert_eq!(trace.response_values[0].valeur, "12");
+
+        // Valeurs de chaque champ des trames lors de cette vacation
+        assert_eq!(
+            trace.request_field_values(0),
+            vec![(IdInfo::Quantieme, Some(&trace.request_values[0]))]
+        );
+        assert_eq!(
+            trace.response_field_values(0),
+            vec![(
+                IdInfo::NbMesuragesQuantieme,
+                Some(&trace.response_values[0])
+            )]
+        );
+
+        // Rejeu avec les mêmes valeurs de requête (ici sans réponse)
+        context.set_info_u16(IdInfo::Quantieme, 200);
+        st.port.should_write(&req);
+        assert_eq!(
+            st.rerun_vacation(&mut context, &trace),
+            Err(ProtocolError::NoReply)
+        );
+        assert_eq!(context.get_option_info_u16(IdInfo::Quantieme), Some(123));
+        assert_eq!(
+            context
+                .get_option_info_origine(IdInfo::Quantieme)
+                .map(|origine| origine.provenance.clone()),
+            Some(Provenance::Rejeu(trace.num_vacation))
+        );
+        assert_eq!(st.vacations.len(), 2);
+        assert!(st.vacations[1].response_values.is_empty());
+        assert_eq!(
+            st.vacations[1].response_field_values(0),
+            vec![(IdInfo::NbMesuragesQuantieme, None)]
+        );
+        assert_eq!(st.vacations[1].option_error, Some(ProtocolError::NoReply));
+    }
 }
@@ -3,11 +3,14 @@
 use crate::context::Context;
 
 use super::frame;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::TValue;
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 33;
@@ -57,20 +60,26 @@ impl CommonMessageTrait for Message33 {
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[5; 8];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
         let frame = st2150.try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, lens_expected)?;
 
         // Mise à jour du contexte
+        let code_page = st2150.code_page;
 
         // #0 - #7 : Libellé table produits(i+1)
         for indice_champ in 0_usize..=7 {
-            context.set_info_string(
-                IdInfo::LibelleTableProduits(indice_champ + 1),
-                &frame.fields[indice_champ].decode_str()?,
-            );
+            st2150.decode_rep_field(|| {
+                let field = frame.field(indice_champ)?;
+                set_rep_info(
+                    context,
+                    IdInfo::LibelleTableProduits(indice_champ + 1),
+                    field,
+                    TValue::String(field.decode_str_code_page(code_page)?),
+                )
+            })?;
         }
 
         // C'est tout bon
@@ -4,10 +4,15 @@ use crate::context::Context;
 
 use super::field::Field;
 use super::frame;
+use super::illegal_rep_value;
+use super::prerequis::Prerequis;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
+use crate::context::{Heure, TValue};
 
 use crate::context::IdInfo;
 
@@ -48,6 +53,13 @@ impl CommonMessageTrait for Message34 {
         ]
     }
 
+    fn prerequis(&self) -> Vec<Prerequis> {
+        vec![
+            Prerequis::new(IdInfo::IndexJournalier, IdInfo::NbMesuragesQuantieme, 31),
+            Prerequis::new(IdInfo::IndexFractionnement, IdInfo::NbFractionnements, 32),
+        ]
+    }
+
     fn do_vacation(&self, st2150: &mut ST2150, context: &mut Context) -> Result<(), ProtocolError> {
         // Contexte OK ?
         Self::availability(self, context)?;
@@ -57,25 +69,31 @@ impl CommonMessageTrait for Message34 {
 
         // #0 : Quantième
         let quantieme = context.get_option_info_u16(IdInfo::Quantieme).unwrap();
-        req.add_field(Field::encode_number(quantieme, 3)?);
+        req.add_info_field(IdInfo::Quantieme, Field::encode_number(quantieme, 3)?);
 
         // #1 : Numéro d'ordre dans la journée
         let index_journalier = context
             .get_option_info_u16(IdInfo::IndexJournalier)
             .unwrap();
-        req.add_field(Field::encode_number(index_journalier, 3)?);
+        req.add_info_field(
+            IdInfo::IndexJournalier,
+            Field::encode_number(index_journalier, 3)?,
+        );
 
         // #2 : Numéro du fractionnement
         let index_fractionnement = context
             .get_option_info_u16(IdInfo::IndexFractionnement)
             .unwrap();
-        req.add_field(Field::encode_number(index_fractionnement, 3)?);
+        req.add_info_field(
+            IdInfo::IndexFractionnement,
+            Field::encode_number(index_fractionnement, 3)?,
+        );
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[5, 1, 4, 4];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -84,25 +102,58 @@ impl CommonMessageTrait for Message34 {
         // Mise à jour du contexte
 
         // #0 - Quantité livrée
-        context.set_info_u32(
-            IdInfo::QuantitePrincipale,
-            frame.fields[0].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantitePrincipale,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #1 : Type de distribution
-        context.set_info_char(IdInfo::TypeDistribution, frame.fields[1].decode_char()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            set_rep_info(
+                context,
+                IdInfo::TypeDistribution,
+                field,
+                TValue::Char(field.decode_char()?),
+            )
+        })?;
 
         // #2 : Heure de début
-        context.set_info_u16(
-            IdInfo::HeureHHMMDebut,
-            frame.fields[2].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // #3 : Heure de fin
-        context.set_info_u16(
-            IdInfo::HeureHHMMFin,
-            frame.fields[3].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -200,12 +251,12 @@ mod tests {
             Some('D')
         );
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMDebut),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMDebut),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMFin),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMFin),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
     }
 }
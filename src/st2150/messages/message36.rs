@@ -4,11 +4,16 @@ use crate::context::Context;
 use crate::st2150::field::Field;
 
 use super::frame;
+use super::illegal_rep_value;
+use super::prerequis::Prerequis;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::{Heure, TValue};
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 36;
@@ -43,6 +48,14 @@ impl CommonMessageTrait for Message36 {
         ]
     }
 
+    fn prerequis(&self) -> Vec<Prerequis> {
+        vec![Prerequis::new(
+            IdInfo::IndexJournalier,
+            IdInfo::NbJEvents,
+            MESSAGE_NUM,
+        )]
+    }
+
     fn do_vacation(&self, st2150: &mut ST2150, context: &mut Context) -> Result<(), ProtocolError> {
         // Contexte OK ?
         Self::availability(self, context)?;
@@ -50,39 +63,83 @@ impl CommonMessageTrait for Message36 {
         // Création et envoi requête
         let mut req = frame::Frame::new(MESSAGE_NUM);
 
-        // #0 - Date
-        let data = context.get_option_info_u32(IdInfo::DateAAMMJJ).unwrap();
-        req.add_field(Field::encode_number(data, 6)?);
+        // #0 - Date (AAMMJJ)
+        let date = context.get_option_info_date(IdInfo::DateAAMMJJ).unwrap();
+        req.add_info_field(
+            IdInfo::DateAAMMJJ,
+            Field::encode_number(date.to_aammjj(), 6)?,
+        );
 
         // #1 - Numéro d'ordre dans le jour
         let index_journalier = context
             .get_option_info_u16(IdInfo::IndexJournalier)
             .unwrap();
-        req.add_field(Field::encode_number(index_journalier, 3)?);
+        req.add_info_field(
+            IdInfo::IndexJournalier,
+            Field::encode_number(index_journalier, 3)?,
+        );
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[3, 6, 12, 40];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
         let frame = st2150.try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, lens_expected)?;
 
         // Mise à jour du contexte
+        let code_page = st2150.code_page;
 
         // #0 - Nombre d'événements pour la journée demandée
-        context.set_info_u16(IdInfo::NbJEvents, frame.fields[0].decode_number::<u16>()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::NbJEvents,
+                field,
+                TValue::U16(field.decode_number::<u16>()?),
+            )
+        })?;
 
         // #1 - Heure
-        context.set_info_u32(IdInfo::HeureHHMMSS, frame.fields[1].decode_number::<u32>()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            let heure = Heure::from_hhmmss(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMSS,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMSS,
+                field,
+                TValue::HeureHHMMSS(heure),
+            )
+        })?;
 
         // #2 - Data techniques de l'événement
-        context.set_info_string(IdInfo::DataJEvent, &frame.fields[2].decode_str()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            set_rep_info(
+                context,
+                IdInfo::DataJEvent,
+                field,
+                TValue::String(field.decode_str_code_page(code_page)?),
+            )
+        })?;
 
         // #3 - Libellé de l'événement
-        context.set_info_string(IdInfo::LibelleJEvent, &frame.fields[3].decode_str()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            set_rep_info(
+                context,
+                IdInfo::LibelleJEvent,
+                field,
+                TValue::String(field.decode_str_code_page(code_page)?),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -93,6 +150,7 @@ impl CommonMessageTrait for Message36 {
 mod tests {
     use super::*;
     use crate::context::Context;
+    use crate::context::{Date, Provenance};
     use crate::st2150::protocol;
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
@@ -106,7 +164,7 @@ mod tests {
         // Contexte pour le protocole
         let mut context = Context::default();
 
-        context.set_info_u32(IdInfo::DateAAMMJJ, 12_03_04);
+        context.set_info_date(IdInfo::DateAAMMJJ, Date::from_aammjj(12_03_04).unwrap());
         context.set_info_u16(IdInfo::IndexJournalier, 1);
 
         // Trame pour message
@@ -171,7 +229,10 @@ mod tests {
 
         // Vérification de ce qui a été mis à jour dans le contexte
         assert_eq!(context.get_option_info_u16(IdInfo::NbJEvents), Some(12));
-        assert_eq!(context.get_option_info_u32(IdInfo::HeureHHMMSS), Some(12_34_56));
+        assert_eq!(
+            context.get_option_info_heure_hhmmss(IdInfo::HeureHHMMSS),
+            Some(Heure::from_hhmmss(12_34_56).unwrap())
+        );
         assert_eq!(
             context.get_option_info_string(IdInfo::DataJEvent),
             Some("AABBCCDDEEFF".to_string())
@@ -180,5 +241,21 @@ mod tests {
             context.get_option_info_string(IdInfo::LibelleJEvent),
             Some("0123456789012345678901234567890123456789".to_string())
         );
+
+        // Provenance des informations mises à jour (y compris les informations déduites)
+        for id_info in [IdInfo::HeureHHMMSS, IdInfo::HeureHHMM] {
+            assert_eq!(
+                context
+                    .get_option_info_origine(id_info)
+                    .map(|origine| origine.provenance.clone()),
+                Some(Provenance::Message(MESSAGE_NUM))
+            );
+        }
+        assert_eq!(
+            context
+                .get_option_info_origine(IdInfo::IndexJournalier)
+                .map(|origine| origine.provenance.clone()),
+            Some(Provenance::Saisie)
+        );
     }
 }
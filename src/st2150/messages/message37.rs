@@ -1,10 +1,10 @@
 //! Message 37 : Mise à jour du plan
 
-use crate::context;
 use crate::context::Context;
 use crate::st2150::field::Field;
 
 use super::frame;
+use super::rep_len_fields;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
@@ -66,28 +66,42 @@ impl CommonMessageTrait for Message37 {
         let mut req = frame::Frame::new(MESSAGE_NUM);
 
         // 9 x code produit et quantité des compartiments
+        // (code produit 0 et quantité 0 pour un compartiment absent du véhicule)
+        let profil_vehicule = context.get_profil_vehicule();
         for compart_num in 1..=9 {
-            let code_produit = context
-                .get_option_info_u8(IdInfo::CodeProduitCompartiment(compart_num))
-                .unwrap();
+            let (code_produit, quantite) =
+                if context.is_info_relevant(IdInfo::CodeProduitCompartiment(compart_num)) {
+                    (
+                        context
+                            .get_option_info_u8(IdInfo::CodeProduitCompartiment(compart_num))
+                            .unwrap(),
+                        context
+                            .get_option_info_u32(IdInfo::QuantiteCompartiment(compart_num))
+                            .unwrap(),
+                    )
+                } else {
+                    (0, 0)
+                };
             Field::check_binary_domain(
                 "code produit",
                 code_produit,
-                0_u8..=u8::try_from(context::NB_PRODUITS).unwrap(),
+                0_u8..=profil_vehicule.nb_produits(),
             )?;
-            req.add_field(Field::encode_binary(b'0' + code_produit));
-
-            let quantite = context
-                .get_option_info_u32(IdInfo::QuantiteCompartiment(compart_num))
-                .unwrap();
-            req.add_field(Field::encode_number(quantite, 5)?);
+            req.add_info_field(
+                IdInfo::CodeProduitCompartiment(compart_num),
+                Field::encode_binary(b'0' + code_produit),
+            );
+            req.add_info_field(
+                IdInfo::QuantiteCompartiment(compart_num),
+                Field::encode_number(quantite, 5)?,
+            );
         }
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -206,4 +220,56 @@ mod tests {
         assert_eq!(context.get_option_info_bool(IdInfo::Nack), Some(false));
         assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(true));
     }
+
+    #[rustfmt::skip]
+    #[test]
+    fn test_message37_profil_vehicule() {
+        // On utilise le FAKE serial port pour contrôler ce qui circule...
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+
+        // Contexte pour un véhicule à 5 compartiments
+        let mut context = Context::default();
+        context.set_profil_vehicule(crate::context::ProfilVehicule::new(16, 5, 3).unwrap());
+
+        // Message impossible tant que les 5 compartiments ne sont pas définis
+        context.set_info_u8(IdInfo::CodeProduitCompartiment(1), 1);
+        context.set_info_u32(IdInfo::QuantiteCompartiment(1), 1000);
+        assert!(ST2150::message_availability(&context, MESSAGE_NUM).is_err());
+
+        // On met code produit i & quantité 1000 * i dans le compartiment #i (sauf #6 à #9)
+        for num_compart in 1_u8..=5 {
+            context.set_info_u8(IdInfo::CodeProduitCompartiment(num_compart as usize), num_compart);
+            context.set_info_u32(IdInfo::QuantiteCompartiment(num_compart as usize), 1000_u32 * u32::from(num_compart));
+        }
+
+        // Trame pour message (compartiments #6 à #9 vides)
+        let mut trame = vec![protocol::STX, b'3', b'7', protocol::SEPARATOR];
+        for num_compart in 1_u8..=5 {
+            trame.extend([b'0' + num_compart, protocol::SEPARATOR, b'0', b'0' + num_compart, b'0', b'0', b'0', protocol::SEPARATOR]);
+        }
+        for _ in 6..=9 {
+            trame.extend([b'0', protocol::SEPARATOR, b'0', b'0', b'0', b'0', b'0', protocol::SEPARATOR]);
+        }
+        trame.extend([70, 65, protocol::ETX]); // Checksum
+        fake_port.should_write(&trame);
+
+        // Réponse simulée
+        fake_port.will_read(&[
+            protocol::STX,
+            b'3', // Numéro de message
+            b'7',
+            protocol::SEPARATOR,
+            protocol::ACK, // ACK
+            protocol::SEPARATOR,
+            b'0', // Checksum
+            b'2',
+            protocol::ETX,
+        ]);
+
+        // Vacation requête/réponse du message via le FAKE port
+        let mut st = ST2150::new(fake_port);
+        assert!(ST2150::message_availability(&context, MESSAGE_NUM).is_ok());
+        assert_eq!(st.do_message_vacation(&mut context, MESSAGE_NUM), Ok(()));
+        assert_eq!(context.get_option_info_bool(IdInfo::Ack), Some(true));
+    }
 }
@@ -4,11 +4,16 @@ use crate::context::Context;
 
 use super::field::Field;
 use super::frame;
+use super::illegal_rep_value;
+use super::prerequis::Prerequis;
+use super::rep_len_fields;
+use super::set_rep_info;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
 use super::ST2150;
 use crate::context::IdInfo;
+use crate::context::{Heure, TValue};
 
 /// Numéro de ce message
 const MESSAGE_NUM: u8 = 38;
@@ -46,6 +51,14 @@ impl CommonMessageTrait for Message38 {
         ]
     }
 
+    fn prerequis(&self) -> Vec<Prerequis> {
+        vec![Prerequis::new(
+            IdInfo::IndexJournalier,
+            IdInfo::NbMesuragesQuantieme,
+            31,
+        )]
+    }
+
     fn do_vacation(&self, st2150: &mut ST2150, context: &mut Context) -> Result<(), ProtocolError> {
         // Contexte OK ?
         Self::availability(self, context)?;
@@ -55,60 +68,117 @@ impl CommonMessageTrait for Message38 {
 
         // #0 : Quantième
         let quantieme = context.get_option_info_u16(IdInfo::Quantieme).unwrap();
-        req.add_field(Field::encode_number(quantieme, 3)?);
+        req.add_info_field(IdInfo::Quantieme, Field::encode_number(quantieme, 3)?);
 
         // #1 : Numéro d'ordre dans la journée
         let index_journalier = context
             .get_option_info_u16(IdInfo::IndexJournalier)
             .unwrap();
-        req.add_field(Field::encode_number(index_journalier, 3)?);
+        req.add_info_field(
+            IdInfo::IndexJournalier,
+            Field::encode_number(index_journalier, 3)?,
+        );
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[10, 5, 4, 3, 4, 4, 1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
         let frame = st2150.try_from_buffer(&buffer[..len_rep], MESSAGE_NUM, lens_expected)?;
 
         // Mise à jour du contexte
+        let code_page = st2150.code_page;
 
         // #0 : Libellé produit (10 cars alors que le message 32 n'est lit que 5)
-        context.set_info_string(IdInfo::LibelleProduit, &frame.fields[0].decode_str()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(0)?;
+            set_rep_info(
+                context,
+                IdInfo::LibelleProduit,
+                field,
+                TValue::String(field.decode_str_code_page(code_page)?),
+            )
+        })?;
 
         // #1 : Quantité livrée
-        context.set_info_u32(
-            IdInfo::QuantitePrincipale,
-            frame.fields[1].decode_number::<u32>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(1)?;
+            set_rep_info(
+                context,
+                IdInfo::QuantitePrincipale,
+                field,
+                TValue::U32(field.decode_number::<u32>()?),
+            )
+        })?;
 
         // #2 : Température moyenne +123 pour 12.3°C
-        let tempe10 = frame.fields[2].decode_signed_number::<i16>()?;
-        let tempe10 = f32::from(tempe10);
-        context.set_info_f32(IdInfo::TemperatureMoyen, tempe10 / 10_f32);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(2)?;
+            let tempe10 = f32::from(field.decode_signed_number::<i16>()?);
+            set_rep_info(
+                context,
+                IdInfo::TemperatureMoyen,
+                field,
+                TValue::F32(tempe10 / 10_f32),
+            )
+        })?;
 
         // #3 : Nombre de fractionnements
-        context.set_info_u16(
-            IdInfo::NbFractionnements,
-            frame.fields[3].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(3)?;
+            set_rep_info(
+                context,
+                IdInfo::NbFractionnements,
+                field,
+                TValue::U16(field.decode_number::<u16>()?),
+            )
+        })?;
 
         // #4 : Heure de début
-        context.set_info_u16(
-            IdInfo::HeureHHMMDebut,
-            frame.fields[4].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(4)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMDebut,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // #5 : Heure de fin
-        context.set_info_u16(
-            IdInfo::HeureHHMMFin,
-            frame.fields[5].decode_number::<u16>()?,
-        );
+        st2150.decode_rep_field(|| {
+            let field = frame.field(5)?;
+            let heure = Heure::from_hhmm(field.decode_number()?).map_err(illegal_rep_value(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+            ))?;
+            set_rep_info(
+                context,
+                IdInfo::HeureHHMMFin,
+                field,
+                TValue::HeureHHMM(heure),
+            )
+        })?;
 
         // #6 : Type de distribution
-        context.set_info_char(IdInfo::TypeDistribution, frame.fields[6].decode_char()?);
+        st2150.decode_rep_field(|| {
+            let field = frame.field(6)?;
+            set_rep_info(
+                context,
+                IdInfo::TypeDistribution,
+                field,
+                TValue::Char(field.decode_char()?),
+            )
+        })?;
 
         // C'est tout bon
         Ok(())
@@ -229,12 +299,12 @@ mod tests {
             Some(1)
         );
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMDebut),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMDebut),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
         assert_eq!(
-            context.get_option_info_u16(IdInfo::HeureHHMMFin),
-            Some(12_34)
+            context.get_option_info_heure_hhmm(IdInfo::HeureHHMMFin),
+            Some(Heure::from_hhmm(12_34).unwrap())
         );
         assert_eq!(
             context.get_option_info_char(IdInfo::TypeDistribution),
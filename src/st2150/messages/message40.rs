@@ -4,6 +4,7 @@ use crate::context::Context;
 use crate::st2150::field::Field;
 
 use super::frame;
+use super::rep_len_fields;
 use super::CommonMessageTrait;
 use super::Edition2150;
 use super::ProtocolError;
@@ -46,14 +47,16 @@ impl CommonMessageTrait for Message40 {
         let mut req = frame::Frame::new(MESSAGE_NUM);
 
         // #0 - Heure (HHMM)
-        let heure = context.get_option_info_u16(IdInfo::HeureHHMM).unwrap();
-        req.add_field(Field::encode_number(heure, 4)?);
+        let heure = context
+            .get_option_info_heure_hhmm(IdInfo::HeureHHMM)
+            .unwrap();
+        req.add_info_field(IdInfo::HeureHHMM, Field::encode_number(heure.to_hhmm(), 4)?);
 
         st2150.send_req(&req);
 
         // Réception réponse
         let mut buffer = [0; 200];
-        let lens_expected = &[1];
+        let lens_expected = rep_len_fields(MESSAGE_NUM);
         let len_rep = st2150.wait_rep(&mut buffer, lens_expected)?;
 
         // Décodage de la réponse reçue
@@ -74,6 +77,7 @@ impl CommonMessageTrait for Message40 {
 mod tests {
     use super::*;
     use crate::context::Context;
+    use crate::context::Heure;
     use crate::st2150::protocol;
     use crate::CommonSerialComTrait;
     use crate::SerialCom;
@@ -87,7 +91,7 @@ mod tests {
         let mut context = Context::default();
 
         // On met code produit i & quantité 1000 * i dans le compartiment #i
-        context.set_info_u16(IdInfo::HeureHHMM, 1234);
+        context.set_info_heure_hhmm(IdInfo::HeureHHMM, Heure::from_hhmm(12_34).unwrap());
 
         // Trame pour message
         fake_port.should_write(&[
@@ -58,7 +58,7 @@ impl CommonMessageTrait for Message60 {
         )?;
 
         // Mise à jour du contexte selon la réponse
-        helper::update_context_from_rep(MESSAGE_NUM, context, &frame)?;
+        helper::update_context_from_rep(MESSAGE_NUM, st2150, context, &frame)?;
 
         // C'est tout bon
         Ok(())
@@ -146,5 +146,24 @@ mod tests {
             context.get_option_info_u8(IdInfo::CodeErreurMouvementProduit),
             Some(0)
         );
+
+        // Information de chaque champ de la requête et de la réponse
+        assert_eq!(
+            st.option_last_req_frame.map(|req| req.id_infos),
+            Some(vec![
+                Some(IdInfo::Predetermination),
+                Some(IdInfo::CodeProduit),
+                Some(IdInfo::NumeroCompartiment),
+                Some(IdInfo::NumeroFlexible),
+                Some(IdInfo::FinirFlexibleVide),
+            ])
+        );
+        assert_eq!(
+            Message60.id_infos_response_fields(2),
+            vec![
+                vec![IdInfo::Ack, IdInfo::Nack],
+                vec![IdInfo::CodeErreurMouvementProduit]
+            ]
+        );
     }
 }
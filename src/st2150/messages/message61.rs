@@ -58,7 +58,7 @@ impl CommonMessageTrait for Message61 {
         )?;
 
         // Mise à jour du contexte selon la réponse
-        helper::update_context_from_rep(MESSAGE_NUM, context, &frame)?;
+        helper::update_context_from_rep(MESSAGE_NUM, st2150, context, &frame)?;
 
         // C'est tout bon
         Ok(())
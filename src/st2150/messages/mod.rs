@@ -3,6 +3,7 @@
 use super::context;
 use super::context::Context;
 use super::frame;
+use super::nostd::layout;
 use super::IdInfo;
 use super::ProtocolError;
 use super::ST2150;
@@ -79,9 +80,15 @@ use message77::Message77;
 pub mod message78;
 use message78::Message78;
 // Messages79 réservé
+pub mod diagnostic;
+use diagnostic::DiagnosticInfo;
+pub mod prerequis;
+use prerequis::Prerequis;
 
 use super::field;
+use super::field::Field;
 use super::Edition2150;
+use context::TValue;
 
 /// Liste des numéros de messages implémentés
 pub const ST2150_MESSAGE_NUMBERS: &[u8] = &[
@@ -126,6 +133,43 @@ pub fn get_dyn_message(message_num: u8) -> Box<dyn CommonMessageTrait> {
     }
 }
 
+/// Mise à jour d'une information du contexte avec une valeur décodée d'un champ de la réponse
+/// La valeur doit être dans le domaine de l'information (valeurs min/max, date et heure valides)
+/// # Errors
+/// `ProtocolError::IllegalRepFieldValue` si la valeur est hors du domaine de l'information
+fn set_rep_info(
+    context: &mut Context,
+    id_info: IdInfo,
+    field: &Field,
+    t_value: TValue,
+) -> Result<(), ProtocolError> {
+    context
+        .check_info_domain(id_info, &t_value)
+        .map_err(illegal_rep_value(context, id_info, field))?;
+    context.set_info_t_value(id_info, t_value);
+    Ok(())
+}
+
+/// Erreur pour une valeur d'un champ de la réponse hors du domaine d'une information
+/// (à partir du libellé de ce domaine)
+fn illegal_rep_value(
+    context: &Context,
+    id_info: IdInfo,
+    field: &Field,
+) -> impl FnOnce(String) -> ProtocolError {
+    let label = context.get_info_label(id_info).into_owned();
+    let field = field.clone();
+    move |domaine_valeurs| ProtocolError::IllegalRepFieldValue(field, label, domaine_valeurs)
+}
+
+/// Taille des champs de la réponse d'un message (voir `layout::MESSAGE_LAYOUTS`)
+/// # panics
+/// panic! si le message n'est pas décrit dans `layout::MESSAGE_LAYOUTS`
+fn rep_len_fields(message_num: u8) -> &'static [usize] {
+    layout::rep_len_fields(message_num)
+        .unwrap_or_else(|| panic!("Pas de description pour le message {message_num}"))
+}
+
 /// Trait à implémenter pour chaque type de message
 /// Les structures `MessageXX` doivent implémenter le `Default` trait
 pub trait CommonMessageTrait {
@@ -136,6 +180,7 @@ pub trait CommonMessageTrait {
     fn edition_st2150(&self) -> Edition2150;
 
     /// Libellé (Quelques mots) décrivant le message
+    /// (en français, clé de sa traduction, voir `langue::tr`)
     fn message_str(&self) -> &'static str;
 
     /// Id des informations contexte nécessaire pour la 'requête' du message
@@ -144,29 +189,61 @@ pub trait CommonMessageTrait {
     /// Id des informations contexte nécessaire pour la 'réponse' du message
     fn id_infos_response(&self) -> Vec<IdInfo>;
 
-    /// Indique si le contexte permet d'effectuer une requête avec ce message
-    /// (note: pas de `self` dans cette fonction)
-    fn availability(&self, context: &Context) -> Result<(), ProtocolError> {
-        for id_info in self.id_infos_request() {
-            let info_name = context.get_info_label(id_info);
-            if match context.get_info_format(id_info) {
-                context::FormatInfo::Bool => context.get_option_info_bool(id_info).is_none(),
-                context::FormatInfo::Char => context.get_option_info_char(id_info).is_none(),
-                context::FormatInfo::U8 => context.get_option_info_u8(id_info).is_none(),
-                context::FormatInfo::U8OrT => context.get_option_info_u8_or_t(id_info).is_none(),
-                context::FormatInfo::U16 => context.get_option_info_u16(id_info).is_none(),
-                context::FormatInfo::U32 => context.get_option_info_u32(id_info).is_none(),
-                context::FormatInfo::U64 => context.get_option_info_u64(id_info).is_none(),
-                context::FormatInfo::F32 => context.get_option_info_f32(id_info).is_none(),
-                context::FormatInfo::String(_width) => {
-                    context.get_option_info_string(id_info).is_none()
+    /// Informations du contexte alimentées par chaque champ de la réponse, dans l'ordre des
+    /// champs (pour l'affichage annoté de la trame reçue, selon son nombre de champs)
+    /// Par défaut, un champ par information de la réponse (un seul champ ACK/NACK)
+    fn id_infos_response_fields(&self, _nb_fields: usize) -> Vec<Vec<IdInfo>> {
+        let mut id_infos_fields: Vec<Vec<IdInfo>> = vec![];
+        for id_info in self.id_infos_response() {
+            match id_infos_fields.last_mut() {
+                Some(last) if id_info == IdInfo::Nack && last == &[IdInfo::Ack] => {
+                    last.push(id_info);
                 }
-            } {
-                return Err(ProtocolError::ContextMissing(info_name.to_string()));
+                _ => id_infos_fields.push(vec![id_info]),
             }
         }
+        id_infos_fields
+    }
 
-        Ok(())
+    /// Prérequis de la requête : Index limités par la réponse à un autre message
+    /// (par défaut, aucun prérequis)
+    fn prerequis(&self) -> Vec<Prerequis> {
+        vec![]
+    }
+
+    /// Messages prérequis à exécuter avant la requête de ce message
+    /// (valeurs max. des index inconnues ou plus à jour, voir `Prerequis::is_max_a_jour`)
+    fn prerequis_a_executer(&self, context: &Context) -> Vec<u8> {
+        prerequis::prerequis_a_executer(
+            self.message_num(),
+            &self.prerequis(),
+            context,
+            &self.id_infos_request(),
+        )
+    }
+
+    /// Diagnostic des informations du contexte pour la requête de ce message
+    /// (toutes les informations manquantes, hors domaine ou incohérentes, vide si aucune)
+    /// Les informations qui ne concernent pas le véhicule (voir `ProfilVehicule`) ne sont pas
+    /// nécessaires
+    fn diagnostic(&self, context: &Context) -> Vec<DiagnosticInfo> {
+        let id_infos_request = self.id_infos_request();
+        let mut diagnostic = diagnostic::diagnostic_request(context, &id_infos_request);
+        diagnostic.extend(prerequis::diagnostic_prerequis(
+            &self.prerequis(),
+            context,
+            &id_infos_request,
+        ));
+        diagnostic
+    }
+
+    /// Indique si le contexte permet d'effectuer une requête avec ce message
+    /// (erreur pour la première anomalie du diagnostic, voir `diagnostic`)
+    fn availability(&self, context: &Context) -> Result<(), ProtocolError> {
+        match self.diagnostic(context).first() {
+            None => Ok(()),
+            Some(diagnostic_info) => Err(diagnostic_info.to_protocol_error(context)),
+        }
     }
 
     /// Tente une vacation sur un port avec un contexte de ce message
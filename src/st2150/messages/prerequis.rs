@@ -0,0 +1,184 @@
+//! Prérequis d'une requête : Informations fournies par la réponse à un autre message
+//!
+//! Certains index d'une requête sont limités par une information reçue en réponse à un autre
+//! message. Par exemple, l'index journalier des messages 32, 34 et 38 est au plus le nombre de
+//! mesurages du quantième reçu par le message 31, et l'index de fractionnement du message 34
+//! est au plus le nombre de fractionnements reçu par le message 32.
+//!
+//! La limite n'est contrôlée que si elle est à jour : Reçue en réponse au message prérequis et pas
+//! antérieure à une modification des informations communes aux 2 requêtes (par exemple, le
+//! quantième pour les messages 31 et 32). Sinon, le message prérequis doit être exécuté
+//! avant (voir `ST2150::auto_prerequis`).
+
+use super::context::{Context, Provenance, TValue};
+use super::diagnostic::{Anomalie, DiagnosticInfo};
+use super::get_dyn_message;
+use super::IdInfo;
+use crate::langue::tr;
+
+/// Prérequis d'une requête
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prerequis {
+    /// Index de la requête limité par le prérequis
+    pub id_info: IdInfo,
+
+    /// Information qui donne la valeur max. de cet index
+    pub id_info_max: IdInfo,
+
+    /// Message qui fournit cette information dans sa réponse
+    pub message_num: u8,
+}
+
+impl Prerequis {
+    /// Constructeur
+    pub fn new(id_info: IdInfo, id_info_max: IdInfo, message_num: u8) -> Self {
+        Self {
+            id_info,
+            id_info_max,
+            message_num,
+        }
+    }
+
+    /// Indique si la valeur max. de l'index est à jour dans le contexte pour une requête
+    /// (reçue en réponse au message prérequis, après les dernières modifications des informations
+    /// communes à cette requête et à celle du message prérequis)
+    pub fn is_max_a_jour(&self, context: &Context, id_infos_request: &[IdInfo]) -> bool {
+        let Some(origine_max) = context.get_option_info_origine(self.id_info_max) else {
+            return false;
+        };
+        if origine_max.provenance != Provenance::Message(self.message_num) {
+            return false;
+        }
+        get_dyn_message(self.message_num)
+            .id_infos_request()
+            .into_iter()
+            .filter(|id_info| *id_info != self.id_info && id_infos_request.contains(id_info))
+            .all(|id_info| {
+                context
+                    .get_option_info_origine(id_info)
+                    .is_some_and(|origine| origine.num_mise_a_jour < origine_max.num_mise_a_jour)
+            })
+    }
+
+    /// Valeur max. de l'index (None si elle n'est pas à jour, voir `is_max_a_jour`)
+    fn option_max(&self, context: &Context, id_infos_request: &[IdInfo]) -> Option<TValue> {
+        if self.is_max_a_jour(context, id_infos_request) {
+            context.get_option_info_t_value(self.id_info_max)
+        } else {
+            None
+        }
+    }
+}
+
+/// Messages prérequis à exécuter avant une requête (sans doublon, dans l'ordre des prérequis)
+/// Un message n'est pas son propre prérequis : Sa limite est seulement contrôlée si elle est à
+/// jour (par exemple, le nombre d'événements du message 36)
+pub fn prerequis_a_executer(
+    message_num: u8,
+    prerequis: &[Prerequis],
+    context: &Context,
+    id_infos_request: &[IdInfo],
+) -> Vec<u8> {
+    let mut message_nums = vec![];
+    for prerequis in prerequis {
+        if prerequis.message_num != message_num
+            && !message_nums.contains(&prerequis.message_num)
+            && !prerequis.is_max_a_jour(context, id_infos_request)
+        {
+            message_nums.push(prerequis.message_num);
+        }
+    }
+    message_nums
+}
+
+/// Diagnostic des index d'une requête selon ses prérequis
+/// (index au-delà de la valeur max. à jour)
+pub fn diagnostic_prerequis(
+    prerequis: &[Prerequis],
+    context: &Context,
+    id_infos_request: &[IdInfo],
+) -> Vec<DiagnosticInfo> {
+    prerequis
+        .iter()
+        .filter_map(|prerequis| {
+            let max = prerequis.option_max(context, id_infos_request)?;
+            let t_value = context.get_option_info_t_value(prerequis.id_info)?;
+            let is_greater = match (t_value, &max) {
+                (TValue::U16(value), TValue::U16(max)) => value > *max,
+                _ => false,
+            };
+            is_greater.then(|| DiagnosticInfo {
+                id_info: prerequis.id_info,
+                anomalie: Anomalie::HorsDomaine(
+                    tr("Valeur max. {} (réponse message {})")
+                        .replacen("{}", &max.to_string(), 1)
+                        .replacen("{}", &format!("{:02}", prerequis.message_num), 1),
+                ),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prerequis_a_jour() {
+        let mut context = Context::default();
+        let prerequis = [Prerequis::new(
+            IdInfo::IndexJournalier,
+            IdInfo::NbMesuragesQuantieme,
+            31,
+        )];
+        let id_infos_request = [IdInfo::Quantieme, IdInfo::IndexJournalier];
+        context.set_info_u16(IdInfo::Quantieme, 100);
+        context.set_info_u16(IdInfo::IndexJournalier, 5);
+
+        // Nombre de mesurages inconnu ou saisi
+        assert_eq!(
+            prerequis_a_executer(32, &prerequis, &context, &id_infos_request),
+            vec![31]
+        );
+        context.set_info_u16(IdInfo::NbMesuragesQuantieme, 3);
+        assert_eq!(
+            prerequis_a_executer(32, &prerequis, &context, &id_infos_request),
+            vec![31]
+        );
+        assert!(diagnostic_prerequis(&prerequis, &context, &id_infos_request).is_empty());
+
+        // Nombre de mesurages reçu en réponse à un autre message
+        context.with_provenance(Provenance::Message(30), |context| {
+            context.set_info_u16(IdInfo::NbMesuragesQuantieme, 3);
+        });
+        assert_eq!(
+            prerequis_a_executer(32, &prerequis, &context, &id_infos_request),
+            vec![31]
+        );
+
+        // Nombre de mesurages reçu pour ce quantième
+        context.with_provenance(Provenance::Message(31), |context| {
+            context.set_info_u16(IdInfo::NbMesuragesQuantieme, 3);
+        });
+        assert!(prerequis_a_executer(32, &prerequis, &context, &id_infos_request).is_empty());
+        assert_eq!(
+            diagnostic_prerequis(&prerequis, &context, &id_infos_request),
+            vec![DiagnosticInfo {
+                id_info: IdInfo::IndexJournalier,
+                anomalie: Anomalie::HorsDomaine("Valeur max. 3 (réponse message 31)".to_string()),
+            }]
+        );
+        context.set_info_u16(IdInfo::IndexJournalier, 3);
+        assert!(diagnostic_prerequis(&prerequis, &context, &id_infos_request).is_empty());
+
+        // Nombre de mesurages d'un autre quantième
+        context.set_info_u16(IdInfo::Quantieme, 101);
+        assert_eq!(
+            prerequis_a_executer(32, &prerequis, &context, &id_infos_request),
+            vec![31]
+        );
+
+        // Pas de message prérequis de lui-même
+        assert!(prerequis_a_executer(31, &prerequis, &context, &id_infos_request).is_empty());
+    }
+}
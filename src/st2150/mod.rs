@@ -1,20 +1,29 @@
 //! Protocole ALMA IE selon ST 2150 (voir DOCS)
+use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::Display;
+use std::time::{Instant, SystemTime};
 
 use crate::context;
+use crate::langue::tr;
 
 use crate::serial_com::SerialCom;
 use crate::CommonSerialComTrait;
-use context::{Context, IdInfo};
+use context::{Context, IdInfo, Provenance};
 
+pub mod code_page;
 pub mod field;
 pub mod frame;
 pub mod messages;
+pub mod nostd;
 pub mod protocol;
+pub mod vacations;
 
+pub use code_page::{CodePage, CODE_PAGES};
 use field::Field;
-use frame::Frame;
+use frame::{Frame, NatureSegment};
+use messages::diagnostic::DiagnosticInfo;
+use vacations::{TraceVacation, ValeurInfo, VACATIONS_MAX_LEN};
 
 /// Énumération des éditions de la spécification ST 2150
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -47,8 +56,29 @@ impl Display for Edition2150 {
     }
 }
 
+/// Mode de décodage des réponses du calculateur
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum DecodingMode {
+    /// Toute réponse hors spécification est rejetée (par défaut)
+    #[default]
+    Strict,
+
+    /// Décode tous les champs possibles d'une réponse hors spécification (champ en trop ou
+    /// manquant, taille d'un champ, valeur hors domaine) avec des avertissements plutôt qu'une erreur
+    Lenient,
+}
+
+impl Display for DecodingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodingMode::Strict => write!(f, "{}", tr("Strict")),
+            DecodingMode::Lenient => write!(f, "{}", tr("Tolérant")),
+        }
+    }
+}
+
 /// Erreur détectée
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ProtocolError {
     /// Pas de réponse du calculateur
     NoReply,
@@ -77,63 +107,89 @@ pub enum ProtocolError {
     /// Impossible d'encoder le nombre dans le format demandé
     IllegalNumberEncoding(String),
 
+    /// Caractère non encodable dans un champ chaîne (jeu_de_caractères, caractère)
+    IllegalCharEncoding(String, char),
+
     /// Caractère incorrect dans un champ lors du décodage (type_de_champ, champ, caractère)
     IllegalFieldCharDecode(String, Field, u8),
 
     /// Échec conversion d'un champ dans un type (type_de_champ, champ),
     ErrFieldConversion(String, Field),
 
+    /// Champ absent de la réponse (index)
+    MissingField(usize),
+
+    /// Nombre incorrect de champs dans la réponse (nb champs, attendus)
+    BadFieldsCount(usize, usize),
+
+    /// Longueur incorrecte d'un champ de la réponse (index, nb octets, attendus)
+    BadFieldLen(usize, usize, usize),
+
     /// Valeur incorrecte dans un champ de la réponse (champ, nom, domaine_valeurs)
     IllegalRepFieldValue(Field, String, String),
 
     /// Information manquante dans le contexte (nom_de_l_info)
     ContextMissing(String),
+
+    /// Information hors domaine ou incohérente dans le contexte (nom_de_l_info, raison)
+    ContextIllegal(String, String),
+
+    /// Exécution à blanc : La requête n'est pas envoyée (voir `ST2150::message_dry_run`)
+    DryRun,
 }
 
 impl Display for ProtocolError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ProtocolError::NoReply => write!(f, "Pas de réponse du calculateur"),
-            ProtocolError::BadMessageLen(nb, nb_expected) => write!(
-                f,
+        let txt = match self {
+            ProtocolError::NoReply => "Pas de réponse du calculateur".to_string(),
+            ProtocolError::BadMessageLen(nb, nb_expected) => format!(
                 "Longueur incorrecte du message ({nb}/{nb_expected} cars)"
             ),
-            ProtocolError::BadChecksum(checksum, checksum_expected) => write!(
-                f,
+            ProtocolError::BadChecksum(checksum, checksum_expected) => format!(
                 "Checksum incorrect du message (0x{checksum:02X} vs 0x{checksum_expected:02X} attendu)"
             ),
-            ProtocolError::MissingSTX => write!(f, "Pas de 'STX' en début de message"),
-            ProtocolError::MissingETX => write!(f, "Pas de 'ETX' en fin de message"),
-            ProtocolError::ErrorMessage50(txt) => write!(f, "Réponse avec un message 50 d'erreur : {txt}"),
-            ProtocolError::BadMessageNumber(num, num_expected) => write!(
-                f,
+            ProtocolError::MissingSTX => "Pas de 'STX' en début de message".to_string(),
+            ProtocolError::MissingETX => "Pas de 'ETX' en fin de message".to_string(),
+            ProtocolError::ErrorMessage50(txt) => format!("Réponse avec un message 50 d'erreur : {txt}"),
+            ProtocolError::BadMessageNumber(num, num_expected) => format!(
                 "Numéro incorrect du message ({num} vs {num_expected} attendu)"
             ),
-            ProtocolError::SeparatorExpected(pos) => write!(
-                f,
+            ProtocolError::SeparatorExpected(pos) => format!(
                 "Séparateur de champ attendu en position {pos} dans le message"
             ),
-            ProtocolError::IllegalNumberEncoding(txt) => write!(
-                f,
+            ProtocolError::IllegalNumberEncoding(txt) => format!(
                 "Encodage trame impossible : {txt}"
             ),
-            ProtocolError::IllegalFieldCharDecode(str_decode, field, car) => write!(
-                f,
+            ProtocolError::IllegalCharEncoding(code_page, car) => format!(
+                "Caractère {car:?} non encodable en {code_page} dans un champ"
+            ),
+            ProtocolError::IllegalFieldCharDecode(str_decode, field, car) => format!(
                 "Contenu '0x{car:02X}' incorrect pour décodage en {str_decode} du champ {field:?}"
             ),
-            ProtocolError::ErrFieldConversion(str_decode, field) => write!(
-                f,
+            ProtocolError::ErrFieldConversion(str_decode, field) => format!(
                 "Erreur lors de la conversion en {str_decode} du champ {field:?}"
             ),
-            ProtocolError::IllegalRepFieldValue(field, nom, domaine_valeurs) => write!(
-                f,
+            ProtocolError::MissingField(index) => format!(
+                "Champ #{index} absent du message"
+            ),
+            ProtocolError::BadFieldsCount(nb, nb_expected) => format!(
+                "Nombre incorrect de champs du message ({nb}/{nb_expected} champs)"
+            ),
+            ProtocolError::BadFieldLen(index, nb, nb_expected) => format!(
+                "Longueur incorrecte du champ #{index} ({nb}/{nb_expected} cars)"
+            ),
+            ProtocolError::IllegalRepFieldValue(field, nom, domaine_valeurs) => format!(
                 "Valeur incorrecte du champ '{nom}'={field:?} : {domaine_valeurs}"
             ),
-            ProtocolError::ContextMissing(nom) => write!(
-                f,
+            ProtocolError::ContextMissing(nom) => format!(
                 "Valeur non renseignée du champ '{nom}'"
             ),
-        }
+            ProtocolError::ContextIllegal(nom, raison) => format!(
+                "Valeur refusée du champ '{nom}' : {raison}"
+            ),
+            ProtocolError::DryRun => "Exécution à blanc : Requête non envoyée".to_string(),
+        };
+        write!(f, "{}", tr(&txt))
     }
 }
 
@@ -148,11 +204,33 @@ pub struct ST2150 {
     /// Dernière requête envoyée
     pub last_req: Vec<u8>,
 
+    /// Dernière requête envoyée, avec l'information encodée dans chaque champ
+    pub option_last_req_frame: Option<Frame>,
+
     /// Dernière réponse reçue
     pub last_rep: Vec<u8>,
 
-    /// Libellé de la dernière erreur relevée
-    pub last_error: String,
+    /// Dernière erreur relevée
+    pub option_last_error: Option<ProtocolError>,
+
+    /// Mode de décodage des réponses
+    pub decoding_mode: DecodingMode,
+
+    /// Jeu de caractères des champs chaînes de caractères (requêtes et réponses)
+    pub code_page: CodePage,
+
+    /// Avertissements de la dernière réponse décodée en mode `DecodingMode::Lenient`
+    pub last_warnings: Vec<ProtocolError>,
+
+    /// Exécution automatique des messages prérequis avant une requête
+    /// (voir `CommonMessageTrait::prerequis`)
+    pub auto_prerequis: bool,
+
+    /// Journal des vacations de la session (de la plus ancienne à la plus récente)
+    pub vacations: VecDeque<TraceVacation>,
+
+    /// Exécution à blanc : Les requêtes sont construites sans être envoyées sur le port
+    dry_run: bool,
 }
 
 impl ST2150 {
@@ -161,22 +239,44 @@ impl ST2150 {
         Self {
             port,
             last_req: vec![],
+            option_last_req_frame: None,
             last_rep: vec![],
-            last_error: String::new(),
+            option_last_error: None,
+            decoding_mode: DecodingMode::default(),
+            code_page: CodePage::default(),
+            last_warnings: vec![],
+            auto_prerequis: false,
+            vacations: VecDeque::new(),
+            dry_run: false,
         }
     }
 
+    /// Efface la trace de la dernière vacation
+    pub fn clear_last_vacation(&mut self) {
+        self.last_req = vec![];
+        self.option_last_req_frame = None;
+        self.last_rep = vec![];
+        self.option_last_error = None;
+        self.last_warnings = vec![];
+    }
+
     /// Envoi d'un message (requête)
     fn send_req(&mut self, req: &Frame) {
         self.last_req = req.to_frame();
+        self.option_last_req_frame = Some(req.clone());
         self.last_rep = vec![];
-        self.last_error = String::new();
+        self.option_last_error = None;
+        self.last_warnings = vec![];
 
+        if self.dry_run {
+            return;
+        }
         self.port.write(&req.to_frame());
     }
 
     /// Helper pour renseigner la trace de ce qu'on a reçu
     fn set_last_rep(&mut self, buffer: &[u8], len_buffer: usize) {
+        let len_buffer = usize::min(len_buffer, buffer.len());
         self.last_rep = Vec::with_capacity(len_buffer);
         for v in &buffer[0..len_buffer] {
             self.last_rep.push(*v);
@@ -184,14 +284,24 @@ impl ST2150 {
     }
 
     /// Attente d'un message (réponse)
+    /// En mode `DecodingMode::Lenient`, la réponse est lue jusqu'à son ETX (ou le timeout
+    /// inter-caractères), même si elle est plus longue que la réponse attendue
     fn wait_rep(
         &mut self,
         buffer: &mut [u8],
         len_fields: &[usize],
     ) -> Result<usize, ProtocolError> {
-        let max_expected_len = Frame::len_expected_response(len_fields);
+        if self.dry_run {
+            return Err(ProtocolError::DryRun);
+        }
         self.last_rep = vec![];
-        let len_rep = protocol::waiting_frame(&mut self.port, buffer, max_expected_len);
+        let len_rep = match self.decoding_mode {
+            DecodingMode::Strict => {
+                let max_expected_len = Frame::len_expected_response(len_fields);
+                protocol::waiting_frame(&mut self.port, buffer, max_expected_len)
+            }
+            DecodingMode::Lenient => protocol::waiting_frame_until_etx(&mut self.port, buffer),
+        };
         self.set_last_rep(buffer, len_rep);
         if len_rep == 0 {
             return Err(ProtocolError::NoReply);
@@ -207,40 +317,213 @@ impl ST2150 {
         num_message: u8,
         len_fields: &[usize],
     ) -> Result<Frame, ProtocolError> {
-        let ret = Frame::try_from_buffer(buffer, num_message, len_fields);
+        let ret = match self.decoding_mode {
+            DecodingMode::Strict => Frame::try_from_buffer(buffer, num_message, len_fields),
+            DecodingMode::Lenient => {
+                Frame::try_from_buffer_lenient(buffer, num_message, len_fields).map(
+                    |(frame, warnings)| {
+                        self.last_warnings.extend(warnings);
+                        frame
+                    },
+                )
+            }
+        };
         match ret {
             Ok(frame) => {
-                self.last_error = String::new();
+                self.option_last_error = None;
                 Ok(frame)
             }
             Err(e) => {
-                self.last_error = format!("{e}");
+                self.option_last_error = Some(e.clone());
                 Err(e)
             }
         }
     }
 
+    /// Décodage d'un champ de la réponse et mise à jour du contexte associée
+    /// En mode `DecodingMode::Strict`, la première erreur interrompt le décodage de la réponse.
+    /// En mode `DecodingMode::Lenient`, l'erreur est ajoutée aux avertissements et le décodage
+    /// des champs suivants continue
+    fn decode_rep_field<F>(&mut self, decode: F) -> Result<(), ProtocolError>
+    where
+        F: FnOnce() -> Result<(), ProtocolError>,
+    {
+        match (decode(), self.decoding_mode) {
+            (Ok(()), _) => Ok(()),
+            (Err(e), DecodingMode::Strict) => Err(e),
+            (Err(e), DecodingMode::Lenient) => {
+                self.last_warnings.push(e);
+                Ok(())
+            }
+        }
+    }
+
     /// Message disponible (toutes les informations nécessaires disponibles dans le contexte) ?
     pub fn message_availability(context: &Context, message_num: u8) -> Result<(), ProtocolError> {
         messages::get_dyn_message(message_num).availability(context)
     }
 
-    /// Vacation (requête/réponse) d'un message
+    /// Diagnostic des informations du contexte pour la requête d'un message
+    /// (toutes les anomalies, vide si le message est disponible)
+    pub fn message_diagnostic(context: &Context, message_num: u8) -> Vec<DiagnosticInfo> {
+        messages::get_dyn_message(message_num).diagnostic(context)
+    }
+
+    /// Messages prérequis à exécuter avant la requête d'un message
+    /// (vide si les valeurs max. des index de la requête sont à jour)
+    pub fn message_prerequis(context: &Context, message_num: u8) -> Vec<u8> {
+        messages::get_dyn_message(message_num).prerequis_a_executer(context)
+    }
+
+    /// Exécution à blanc de la requête d'un message : La trame de la requête est construite
+    /// selon le contexte (sans le modifier) mais n'est pas envoyée sur le port
+    /// # Errors
+    /// Information manquante ou refusée du contexte, ou encodage impossible d'un champ de la trame
+    /// (par exemple `ProtocolError::IllegalCharEncoding`)
+    pub fn message_dry_run(
+        &self,
+        context: &Context,
+        message_num: u8,
+    ) -> Result<Frame, ProtocolError> {
+        let mut st2150 = ST2150 {
+            decoding_mode: self.decoding_mode,
+            code_page: self.code_page,
+            dry_run: true,
+            ..ST2150::default()
+        };
+        let mut context = context.clone();
+        context.set_message_courant(Some(message_num));
+        match messages::get_dyn_message(message_num).do_vacation(&mut st2150, &mut context) {
+            Ok(()) | Err(ProtocolError::DryRun) => {
+                st2150.option_last_req_frame.ok_or(ProtocolError::DryRun)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Vacation (requête/réponse) d'un message, mémorisée dans le journal des vacations
+    /// Les messages prérequis sont exécutés avant si `auto_prerequis`
     pub fn do_message_vacation(
         &mut self,
         context: &mut Context,
         message_num: u8,
     ) -> Result<(), ProtocolError> {
-        self.last_req = vec![];
-        self.last_rep = vec![];
-        self.last_error = String::new();
-        match messages::get_dyn_message(message_num).do_vacation(self, context) {
+        if self.auto_prerequis {
+            for prerequis_num in Self::message_prerequis(context, message_num) {
+                self.do_message_vacation(context, prerequis_num)?;
+            }
+        }
+
+        let dyn_message = messages::get_dyn_message(message_num);
+        let horodatage = SystemTime::now();
+        let debut = Instant::now();
+        // Libellés des valeurs de la requête selon ce message (voir `set_message_courant`)
+        context.set_message_courant(Some(message_num));
+        let request_values =
+            ValeurInfo::from_context(context, &dyn_message.id_infos_request(), |_| true);
+
+        let ret = self.do_single_vacation(context, message_num);
+
+        // Informations mises à jour par la réponse lors de cette vacation
+        let num_vacation = context.num_vacation();
+        let response_values =
+            ValeurInfo::from_context(context, &dyn_message.id_infos_response(), |id_info| {
+                context
+                    .get_option_info_origine(id_info)
+                    .is_some_and(|origine| {
+                        origine.num_vacation == num_vacation
+                            && origine.provenance == Provenance::Message(message_num)
+                    })
+            });
+
+        // Informations de chaque champ des trames échangées
+        let req_id_infos_fields = self
+            .option_last_req_frame
+            .as_ref()
+            .map(|req| {
+                req.id_infos
+                    .iter()
+                    .map(|option_id_info| option_id_info.iter().copied().collect())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let nb_rep_fields = Frame::segments(&self.last_rep)
+            .iter()
+            .filter(|segment| matches!(segment.nature, NatureSegment::Champ(_)))
+            .count();
+        let rep_id_infos_fields = dyn_message.id_infos_response_fields(nb_rep_fields);
+
+        self.vacations.push_back(TraceVacation {
+            num_vacation,
+            horodatage,
+            message_num,
+            request_values,
+            response_values,
+            req: self.last_req.clone(),
+            rep: self.last_rep.clone(),
+            req_id_infos_fields,
+            rep_id_infos_fields,
+            duree: debut.elapsed(),
+            option_error: self.option_last_error.clone(),
+            warnings: self.last_warnings.clone(),
+        });
+        if self.vacations.len() > VACATIONS_MAX_LEN {
+            self.vacations.pop_front();
+        }
+
+        ret
+    }
+
+    /// Rejeu d'une vacation du journal : Les valeurs de sa requête sont reprises dans le contexte
+    /// avant une nouvelle vacation du même message
+    /// # Errors
+    /// Voir `do_message_vacation`
+    pub fn rerun_vacation(
+        &mut self,
+        context: &mut Context,
+        trace: &TraceVacation,
+    ) -> Result<(), ProtocolError> {
+        context.with_provenance(Provenance::Rejeu(trace.num_vacation), |context| {
+            for valeur_info in &trace.request_values {
+                context.set_info_t_value(valeur_info.id_info, valeur_info.t_value.clone());
+            }
+        });
+        self.do_message_vacation(context, trace.message_num)
+    }
+
+    /// Vacation (requête/réponse) d'un message, sans ses prérequis
+    fn do_single_vacation(
+        &mut self,
+        context: &mut Context,
+        message_num: u8,
+    ) -> Result<(), ProtocolError> {
+        self.clear_last_vacation();
+        let dyn_message = messages::get_dyn_message(message_num);
+        context.begin_vacation();
+        // Formats et libellés des informations selon ce message (voir `set_message_courant`)
+        context.set_message_courant(Some(message_num));
+        // Les informations de la réponse ne sont mises à jour que si tout le décodage réussit
+        match context.with_provenance(Provenance::Message(message_num), |context| {
+            context.transaction(|context| dyn_message.do_vacation(self, context))
+        }) {
             Ok(()) => {
-                self.last_error = String::new();
+                self.option_last_error = None;
                 Ok(())
             }
             Err(e) => {
-                self.last_error = format!("{e}");
+                // Les valeurs précédentes des informations de la réponse ne sont plus valides
+                // (sauf si la requête n'a pas été envoyée : informations manquantes ou refusées
+                // pour la requête, encodage impossible d'un champ...)
+                if !self.last_req.is_empty() && !self.dry_run {
+                    let id_infos_request = dyn_message.id_infos_request();
+                    let id_infos_response: Vec<IdInfo> = dyn_message
+                        .id_infos_response()
+                        .into_iter()
+                        .filter(|id_info| !id_infos_request.contains(id_info))
+                        .collect();
+                    context.clear_infos(&id_infos_response);
+                }
+                self.option_last_error = Some(e.clone());
                 Err(e)
             }
         }
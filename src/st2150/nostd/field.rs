@@ -0,0 +1,458 @@
+//! Champ de taille fixe pour le codec `no_std`
+//!
+//! Les règles d'encodage et de décodage du contenu d'un champ sont ici des fonctions sur des
+//! octets (`write_xxx` et `read_xxx`), communes à ce `Field` et à `st2150::field::Field`.
+
+use super::{car_hexa_to_value, CodecError, FIELD_CAPACITY};
+
+/// Helper pour encoder `value` en base `radix` dans `digits` (0 padded à gauche)
+/// # Errors
+/// Si `digits` est vide ou trop petit pour la valeur
+fn write_digits(digits: &mut [u8], mut value: u64, radix: u64) -> Result<(), CodecError> {
+    if digits.is_empty() {
+        return Err(CodecError::IllegalNumberEncoding(0));
+    }
+    for digit in digits.iter_mut().rev() {
+        let n = value.checked_rem(radix).unwrap_or_default();
+        value = value.checked_div(radix).unwrap_or_default();
+        // n < 16, pas de débordement possible ici
+        let n = u8::try_from(n).unwrap_or_default();
+        *digit = if n < 10 {
+            b'0'.wrapping_add(n)
+        } else {
+            b'A'.wrapping_add(n.wrapping_sub(10))
+        };
+    }
+    if value == 0 {
+        Ok(())
+    } else {
+        Err(CodecError::IllegalNumberEncoding(digits.len()))
+    }
+}
+
+/// Helper pour extraire une valeur encodée en base `radix` (chiffres validés par `is_digit`)
+/// # Errors
+/// Si un caractère n'est pas un chiffre ou si la valeur dépasse un u64
+fn read_digits(bytes: &[u8], radix: u64, is_digit: fn(&u8) -> bool) -> Result<u64, CodecError> {
+    let mut ret = 0_u64;
+    for car in bytes {
+        if !is_digit(car) {
+            return Err(CodecError::IllegalFieldCharDecode(*car));
+        }
+        // Débordement possible si le champ est trop long pour un u64
+        ret = ret
+            .checked_mul(radix)
+            .and_then(|ret| ret.checked_add(u64::from(car_hexa_to_value(*car))))
+            .ok_or(CodecError::ErrFieldConversion)?;
+    }
+    Ok(ret)
+}
+
+/// Encodage d'une valeur numérique entière dans `digits` (0 padded à gauche)
+/// Par exemple la valeur 2 dans 2 octets donne "02"
+/// # Errors
+/// Si `digits` est vide ou trop petit pour la valeur
+pub fn write_number(value: u64, digits: &mut [u8]) -> Result<(), CodecError> {
+    write_digits(digits, value, 10)
+}
+
+/// Extraction d'une valeur numérique entière encodée en ASCII
+/// # Errors
+/// Si les caractères ne sont pas des chiffres `b'0'..=b'9'`
+/// Si la valeur dépasse un u64
+pub fn read_number(bytes: &[u8]) -> Result<u64, CodecError> {
+    read_digits(bytes, 10, u8::is_ascii_digit)
+}
+
+/// Encodage d'une valeur numérique entière signée dans `digits` (Le 1er octet est un signe
+/// '+' ou '-', puis 0 padded à gauche)
+/// Par exemple la valeur -12 dans 4 octets donne "-012"
+/// # Errors
+/// Si `digits` fait moins de 2 octets ou est trop petit pour la valeur
+pub fn write_signed_number(value: i64, digits: &mut [u8]) -> Result<(), CodecError> {
+    let width = digits.len();
+    match digits.split_first_mut() {
+        Some((sign, digits)) if !digits.is_empty() => {
+            *sign = if value < 0 { b'-' } else { b'+' };
+            write_digits(digits, value.unsigned_abs(), 10)
+                .map_err(|_e| CodecError::IllegalNumberEncoding(width))
+        }
+        _ => Err(CodecError::IllegalNumberEncoding(width)),
+    }
+}
+
+/// Extraction d'une valeur numérique entière et signée encodée en ASCII
+/// # Errors
+/// Si `bytes` est vide
+/// Si le premier caractère n'est pas '+', '-' ou un chiffre
+/// Si les autres caractères ne sont pas des chiffres
+/// Si la valeur dépasse un i64
+pub fn read_signed_number(bytes: &[u8]) -> Result<i64, CodecError> {
+    let (is_negative, digits) = match bytes {
+        [] => return Err(CodecError::ErrFieldConversion),
+        [b'-', digits @ ..] => (true, digits),
+        [b'+', digits @ ..] => (false, digits),
+        digits => (false, digits),
+    };
+    let ret = i64::try_from(read_number(digits)?).map_err(|_e| CodecError::ErrFieldConversion)?;
+    if is_negative {
+        ret.checked_neg().ok_or(CodecError::ErrFieldConversion)
+    } else {
+        Ok(ret)
+    }
+}
+
+/// Encodage d'une valeur en hexadécimal dans `digits` (cars hexadécimal en majuscule)
+/// Par exemple 0xA23 dans 4 octets donne "0A23"
+/// # Errors
+/// Si `digits` est vide ou trop petit pour la valeur
+pub fn write_hexa(value: u64, digits: &mut [u8]) -> Result<(), CodecError> {
+    write_digits(digits, value, 16)
+}
+
+/// Extraction d'une valeur hexa en ASCII
+/// # Errors
+/// Si les caractères ne sont pas des chiffres hexadécimaux
+/// Si la valeur dépasse un u64
+pub fn read_hexa(bytes: &[u8]) -> Result<u64, CodecError> {
+    read_digits(bytes, 16, u8::is_ascii_hexdigit)
+}
+
+/// Encodage d'une chaîne de caractères dans `dst` (space padded à droite)
+/// La chaîne est tronquée si trop grande pour `dst`
+/// (sans couper un caractère UTF-8 multi-octets, complété alors par des espaces)
+pub fn write_str(value: &str, dst: &mut [u8]) {
+    let mut len = value.len().min(dst.len());
+    while !value.is_char_boundary(len) {
+        len = len.saturating_sub(1);
+    }
+    let mut src = value.as_bytes().get(..len).unwrap_or_default().iter();
+    for car in dst.iter_mut() {
+        *car = src.next().copied().unwrap_or(b' ');
+    }
+}
+
+/// Extraction d'une chaîne de caractère
+/// # Errors
+/// Si le contenu n'est pas une chaîne UTF-8 valide
+pub fn read_str(bytes: &[u8]) -> Result<&str, CodecError> {
+    core::str::from_utf8(bytes).map_err(|_e| CodecError::ErrFieldConversion)
+}
+
+/// Octet d'un caractère ASCII
+/// # Errors
+/// Si le caractère n'est pas de l'ASCII
+pub fn write_char(car: char) -> Result<u8, CodecError> {
+    u8::try_from(car)
+        .ok()
+        .filter(u8::is_ascii)
+        .ok_or(CodecError::IllegalNumberEncoding(1))
+}
+
+/// Extraction d'un caractère en ASCII
+/// # Errors
+/// Si `bytes` ne contient pas qu'un caractère ASCII (0x20 - 0x7F)
+pub fn read_char(bytes: &[u8]) -> Result<char, CodecError> {
+    match bytes {
+        [car] if (0x20..=0x7F).contains(car) => Ok(char::from(*car)),
+        [car] => Err(CodecError::IllegalFieldCharDecode(*car)),
+        _ => Err(CodecError::ErrFieldConversion),
+    }
+}
+
+/// Extraction d'une valeur binaire (typiquement ACK ou NACK)
+/// # Errors
+/// Si `bytes` ne contient pas qu'un seul octet
+pub fn read_binary(bytes: &[u8]) -> Result<u8, CodecError> {
+    match bytes {
+        [value] => Ok(*value),
+        _ => Err(CodecError::ErrFieldConversion),
+    }
+}
+
+/// Champ d'une requête ou d'une réponse (au plus `FIELD_CAPACITY` octets)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// Octets du champ (seuls les `len` premiers sont significatifs)
+    data: [u8; FIELD_CAPACITY],
+
+    /// Nombre d'octets du champ
+    len: usize,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self {
+            data: [0; FIELD_CAPACITY],
+            len: 0,
+        }
+    }
+}
+
+impl Field {
+    /// Constructeur champ avec un array de u8
+    /// # Errors
+    /// Si `field` fait plus de `FIELD_CAPACITY` octets
+    pub fn new(field: &[u8]) -> Result<Self, CodecError> {
+        Self::encode_with(field.len(), |dst| {
+            dst.copy_from_slice(field);
+            Ok(())
+        })
+    }
+
+    /// Contenu du champ
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.get(..self.len).unwrap_or_default()
+    }
+
+    /// Nombre d'octets du champ
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Champ vide ?
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Helper pour un champ d'une taille définie, rempli par `encode`
+    /// # Errors
+    /// Si la taille est plus grande que `FIELD_CAPACITY` ou erreur de `encode`
+    fn encode_with(
+        width: usize,
+        encode: impl FnOnce(&mut [u8]) -> Result<(), CodecError>,
+    ) -> Result<Self, CodecError> {
+        let mut ret = Self::default();
+        let dst = ret
+            .data
+            .get_mut(..width)
+            .ok_or(CodecError::CapacityOverflow(width, FIELD_CAPACITY))?;
+        encode(dst)?;
+        ret.len = width;
+        Ok(ret)
+    }
+
+    /// Constructeur champ avec une valeur binaire (typiquement ACK ou NACK)
+    pub fn encode_binary(value: u8) -> Self {
+        let mut ret = Self::default();
+        if let Some(dst) = ret.data.first_mut() {
+            *dst = value;
+            ret.len = 1;
+        }
+        ret
+    }
+
+    /// Extraction champ d'une valeur binaire (typiquement ACK ou NACK)
+    /// # Errors
+    /// Si le champ est d'une taille autre qu'un seul caractère
+    pub fn decode_binary(&self) -> Result<u8, CodecError> {
+        read_binary(self.as_bytes())
+    }
+
+    /// Constructeur champ numérique entier (voir `write_number`)
+    /// # Errors
+    /// si la valeur est trop grande pour la taille demandée
+    /// si taille demandée = 0 ou plus grande que `FIELD_CAPACITY`
+    pub fn encode_number(value: u64, width: usize) -> Result<Self, CodecError> {
+        Self::encode_with(width, |digits| write_number(value, digits))
+    }
+
+    /// Extraction d'un valeur numérique entière encodée en ASCII (voir `read_number`)
+    /// # Errors
+    /// Si les caractères ne sont pas des chiffres `b'0'..=b'9'`
+    /// Si la valeur ne peut pas être convertie dans le type attendu
+    pub fn decode_number<T>(&self) -> Result<T, CodecError>
+    where
+        T: TryFrom<u64>,
+    {
+        T::try_from(read_number(self.as_bytes())?).map_err(|_e| CodecError::ErrFieldConversion)
+    }
+
+    /// Constructeur champ numérique entier signé (voir `write_signed_number`)
+    /// # Errors
+    /// si la valeur est trop grande pour la taille demandée
+    /// si taille demandée < 2 ou plus grande que `FIELD_CAPACITY`
+    pub fn encode_signed_number(value: i64, width: usize) -> Result<Self, CodecError> {
+        Self::encode_with(width, |digits| write_signed_number(value, digits))
+    }
+
+    /// Extraction d'un valeur numérique entière et signée encodée en ASCII
+    /// (voir `read_signed_number`)
+    /// # Errors
+    /// Si le champ est vide ou ne contient pas un nombre signé
+    /// Si la valeur ne peut pas être convertie dans le type attendu
+    pub fn decode_signed_number<T>(&self) -> Result<T, CodecError>
+    where
+        T: TryFrom<i64>,
+    {
+        T::try_from(read_signed_number(self.as_bytes())?)
+            .map_err(|_e| CodecError::ErrFieldConversion)
+    }
+
+    /// Constructeur champ chaîne de caractères (voir `write_str`)
+    /// # Errors
+    /// Si la taille demandée est plus grande que `FIELD_CAPACITY`
+    pub fn encode_str(value: &str, width: usize) -> Result<Self, CodecError> {
+        Self::encode_with(width, |dst| {
+            write_str(value, dst);
+            Ok(())
+        })
+    }
+
+    /// Extraction d'une chaîne de caractère
+    /// # Errors
+    /// Si le contenu n'est pas une chaîne UTF-8 valide
+    pub fn decode_str(&self) -> Result<&str, CodecError> {
+        read_str(self.as_bytes())
+    }
+
+    /// Constructeur champ d'un caractère ASCII
+    /// # Errors
+    /// Si le caractère n'est pas de l'ASCII
+    pub fn encode_char(car: char) -> Result<Self, CodecError> {
+        Self::new(&[write_char(car)?])
+    }
+
+    /// Extraction d'un caractère en ASCII
+    /// # Errors
+    /// Si le champ ne contient pas qu'un caractère ASCII (0x20 - 0x7F)
+    pub fn decode_char(&self) -> Result<char, CodecError> {
+        read_char(self.as_bytes())
+    }
+
+    /// Constructeur champ en hexadécimal (voir `write_hexa`)
+    /// # Errors
+    /// si la valeur est trop grande pour la taille demandée
+    /// si taille demandée = 0 ou plus grande que `FIELD_CAPACITY`
+    pub fn encode_hexa(value: u64, width: usize) -> Result<Self, CodecError> {
+        Self::encode_with(width, |digits| write_hexa(value, digits))
+    }
+
+    /// Extraction d'une valeur hexa en ASCII (voir `read_hexa`)
+    /// # Errors
+    /// Si les caractères ne sont pas des chiffres hexadécimaux
+    /// Si la valeur ne peut pas être convertie dans le type attendu
+    pub fn decode_hexa<T>(&self) -> Result<T, CodecError>
+    where
+        T: TryFrom<u64>,
+    {
+        T::try_from(read_hexa(self.as_bytes())?).map_err(|_e| CodecError::ErrFieldConversion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_number() {
+        assert_eq!(Field::encode_number(5, 2).unwrap().as_bytes(), b"05");
+        assert_eq!(Field::encode_number(56, 2).unwrap().as_bytes(), b"56");
+        assert_eq!(
+            Field::encode_number(12_345, 5)
+                .unwrap()
+                .decode_number::<u32>(),
+            Ok(12_345)
+        );
+        assert!(Field::encode_number(567, 2).is_err());
+        assert!(Field::encode_number(0, 0).is_err());
+        assert!(Field::encode_number(0, FIELD_CAPACITY + 1).is_err());
+
+        // Pas que des chiffres ou débordement
+        assert_eq!(
+            Field::new(b"1X2").unwrap().decode_number::<u8>(),
+            Err(CodecError::IllegalFieldCharDecode(b'X'))
+        );
+        assert!(Field::new(b"999999")
+            .unwrap()
+            .decode_number::<u16>()
+            .is_err());
+        assert!(Field::new(&[b'9'; 30])
+            .unwrap()
+            .decode_number::<u64>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_signed_number() {
+        assert_eq!(
+            Field::encode_signed_number(0, 3).unwrap().as_bytes(),
+            b"+00"
+        );
+        assert_eq!(
+            Field::encode_signed_number(-12, 4).unwrap().as_bytes(),
+            b"-012"
+        );
+        assert_eq!(
+            Field::encode_signed_number(-123, 4)
+                .unwrap()
+                .decode_signed_number::<i16>(),
+            Ok(-123)
+        );
+        assert!(Field::encode_signed_number(-1234, 4).is_err());
+        assert!(Field::encode_signed_number(0, 1).is_err());
+
+        // Champ vide ou incorrect
+        assert!(Field::new(&[])
+            .unwrap()
+            .decode_signed_number::<i16>()
+            .is_err());
+        assert!(Field::new(b"-1F")
+            .unwrap()
+            .decode_signed_number::<i16>()
+            .is_err());
+        assert!(Field::new(b"-12")
+            .unwrap()
+            .decode_signed_number::<u16>()
+            .is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_str() {
+        assert_eq!(Field::encode_str("ABC", 2).unwrap().as_bytes(), b"AB");
+        assert_eq!(Field::encode_str("ABC", 5).unwrap().as_bytes(), b"ABC  ");
+        assert_eq!(Field::encode_str("ABC", 0).unwrap().as_bytes(), b"");
+        assert_eq!(
+            Field::encode_str("HELLO", 5).unwrap().decode_str(),
+            Ok("HELLO")
+        );
+        assert!(Field::new(&[0xFF]).unwrap().decode_str().is_err());
+
+        // Pas de caractère UTF-8 coupé en plein milieu
+        assert_eq!(Field::encode_str("SUPÉR", 4).unwrap().as_bytes(), b"SUP ");
+        assert_eq!(
+            Field::encode_str("SUPÉR", 5).unwrap().decode_str(),
+            Ok("SUPÉ")
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_char_binary_hexa() {
+        assert_eq!(Field::encode_char('A').unwrap().decode_char(), Ok('A'));
+        assert!(Field::encode_char('é').is_err());
+        assert!(Field::new(&[0xFF]).unwrap().decode_char().is_err());
+
+        assert_eq!(Field::encode_binary(0xAB).decode_binary(), Ok(0xAB));
+        assert!(Field::new(&[]).unwrap().decode_binary().is_err());
+
+        assert_eq!(Field::encode_hexa(0xA23, 4).unwrap().as_bytes(), b"0A23");
+        assert_eq!(
+            Field::encode_hexa(0xABCD, 4).unwrap().decode_hexa::<u16>(),
+            Ok(0xABCD)
+        );
+        assert!(Field::encode_hexa(0x1234, 2).is_err());
+        assert!(Field::new(b"1G2").unwrap().decode_hexa::<u16>().is_err());
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert!(Field::new(&[0; FIELD_CAPACITY]).is_ok());
+        assert_eq!(
+            Field::new(&[0; FIELD_CAPACITY + 1]),
+            Err(CodecError::CapacityOverflow(
+                FIELD_CAPACITY + 1,
+                FIELD_CAPACITY
+            ))
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Trame de taille fixe pour le codec `no_std`
+//!
+//! Rappel : Un message est : STX + num(2) + { SEPARATOR + champ(n) }* + SEPARATOR + checksum(2) + ETX
+//!
+//! Les règles d'encodage et de décodage d'une trame sont ici des fonctions sur des octets
+//! (`write_frame`, `check_frame` et `read_fields`), communes à ce `Frame` et à
+//! `st2150::frame::Frame`.
+
+use super::field::{read_number, write_hexa, write_number, Field};
+use super::{calcul_checksum, car_hexa_to_value, CodecError};
+use super::{ACK, ETX, FRAME_CAPACITY, MAX_FIELDS, NACK, SEPARATOR, STX};
+
+/// Écriture de la trame d'un message, octets par octets, via `write`
+/// # Errors
+/// Si le numéro de message ne tient pas sur 2 chiffres ou erreur de `write`
+pub fn write_frame<'a>(
+    message_num: u8,
+    fields: impl IntoIterator<Item = &'a [u8]>,
+    mut write: impl FnMut(&[u8]) -> Result<(), CodecError>,
+) -> Result<(), CodecError> {
+    // STX au début
+    write(&[STX])?;
+
+    // Le checksum est calculé sur l'ensemble de la trame sans le STX initial mais
+    // avec le SEPARATOR avant le checksum
+    let mut checksum = 0;
+    let mut write_with_checksum = |data: &[u8]| {
+        checksum ^= calcul_checksum(data);
+        write(data)
+    };
+
+    // Numéro de message sur 2 octets
+    let mut num = [0; 2];
+    write_number(u64::from(message_num), &mut num)?;
+    write_with_checksum(&num)?;
+
+    // Tous le champs du message précédés d'un SEPARATOR
+    for field in fields {
+        write_with_checksum(&[SEPARATOR])?;
+        write_with_checksum(field)?;
+    }
+
+    // checksum précédé d'un SEPARATOR
+    write_with_checksum(&[SEPARATOR])?;
+    let mut lrc = [0; 2];
+    write_hexa(u64::from(checksum), &mut lrc)?;
+    write(&lrc)?;
+
+    // ETX final
+    write(&[ETX])
+}
+
+/// Contrôles communs à tous les décodages d'une trame : Longueur min., STX, ETX, checksum
+/// et numéro du message (y compris le cas d'un message 50 d'erreur)
+/// Retourne les octets entre le numéro du message et le checksum :
+/// { SEPARATOR + champ(n) }* + SEPARATOR
+/// # Errors
+/// Toute non-conformité de la trame (jamais de `panic!`, quel que soit le contenu de `buffer`)
+pub fn check_frame(buffer: &[u8], message_num: u8) -> Result<&[u8], CodecError> {
+    // Le message doit faire au moins STX + num(2) + SEPARATOR + checksum(2) + ETX
+    let (stx, num, body, checksum_0, checksum_1, etx) = match buffer {
+        [stx, num_0, num_1, body @ .., checksum_0, checksum_1, etx] if !body.is_empty() => {
+            (stx, [*num_0, *num_1], body, checksum_0, checksum_1, etx)
+        }
+        _ => return Err(CodecError::BadMessageLen(buffer.len(), 7)),
+    };
+
+    // Commence par STX ?
+    if *stx != STX {
+        return Err(CodecError::MissingSTX);
+    }
+
+    // Termine par ETX ?
+    if *etx != ETX {
+        return Err(CodecError::MissingETX);
+    }
+
+    // Checksum OK ?
+    let rec_checksum =
+        (car_hexa_to_value(*checksum_0) << 4) | (car_hexa_to_value(*checksum_1) & 0x0F);
+    let checksum = calcul_checksum(&num) ^ calcul_checksum(body);
+    if checksum != rec_checksum {
+        return Err(CodecError::BadChecksum(rec_checksum, checksum));
+    }
+
+    // Numéro de message (2 chiffres ASCII)
+    let rec_message_num =
+        u8::try_from(read_number(&num)?).map_err(|_e| CodecError::ErrFieldConversion)?;
+
+    // Message 50 d'erreur ?
+    if rec_message_num == 50 {
+        // Un message 50 devrait être de la forme STX + "50" + SEP  + "ERREUR" + SEP + checksum(2) + ETX
+        return match body {
+            [SEPARATOR, txt @ .., SEPARATOR] => {
+                Err(CodecError::ErrorMessage50(<[u8; 6]>::try_from(txt).ok()))
+            }
+            _ => Err(CodecError::ErrorMessage50(None)),
+        };
+    }
+
+    // Numéro de message OK ?
+    if rec_message_num != message_num {
+        return Err(CodecError::BadMessageNumber(rec_message_num, message_num));
+    }
+
+    Ok(body)
+}
+
+/// Décodage et validation d'une trame d'un message : Chaque champ est transmis à `add_field`
+/// `buffer` : Message à décoder
+/// `message_num` : Numéro de message attendu
+/// `len_fields` : Nombre et taille (en octet) des différents champs attendus dans le message
+/// # Errors
+/// Toute non-conformité de la trame (jamais de `panic!`, quel que soit le contenu de `buffer`)
+/// ou erreur de `add_field`
+pub fn read_fields<'a>(
+    buffer: &'a [u8],
+    message_num: u8,
+    len_fields: &[usize],
+    mut add_field: impl FnMut(&'a [u8]) -> Result<(), CodecError>,
+) -> Result<(), CodecError> {
+    let body = check_frame(buffer, message_num)?;
+
+    // Longueur du message OK ?
+    let expected_rec_len = Frame::len_expected_response(len_fields);
+    if buffer.len() != expected_rec_len {
+        return Err(CodecError::BadMessageLen(buffer.len(), expected_rec_len));
+    }
+
+    // On est plutôt bien parti, reste à valider les champs..
+    let mut rest = body;
+    for len_field in len_fields {
+        // On doit trouver un SEPARATOR avant le champ
+        let position = buffer.len().saturating_sub(rest.len()).saturating_sub(3);
+        rest = match rest {
+            [SEPARATOR, rest @ ..] => rest,
+            _ => return Err(CodecError::SeparatorExpected(position)),
+        };
+        // Les len_field caractères qui suivent sont un champ
+        let (field, next) = rest
+            .split_at_checked(*len_field)
+            .ok_or(CodecError::BadMessageLen(buffer.len(), expected_rec_len))?;
+        add_field(field)?;
+        rest = next;
+    }
+
+    // On doit trouver encore un SEPARATOR avant le checksum
+    if rest != [SEPARATOR] {
+        let position = buffer.len().saturating_sub(rest.len()).saturating_sub(3);
+        return Err(CodecError::SeparatorExpected(position));
+    }
+
+    // On est tout bon :)
+    Ok(())
+}
+
+/// Support générique pour un message du protocole (au plus `MAX_FIELDS` champs)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Frame {
+    /// Numéro de message
+    message_num: u8,
+
+    /// Champs du message (seuls les `nb_fields` premiers sont significatifs)
+    fields: [Field; MAX_FIELDS],
+
+    /// Nombre de champs du message
+    nb_fields: usize,
+}
+
+impl Frame {
+    /// Constructeur
+    pub fn new(message_num: u8) -> Self {
+        Self {
+            message_num,
+            fields: [Field::default(); MAX_FIELDS],
+            nb_fields: 0,
+        }
+    }
+
+    /// Numéro de message
+    pub fn message_num(&self) -> u8 {
+        self.message_num
+    }
+
+    /// Champs du message
+    pub fn fields(&self) -> &[Field] {
+        self.fields.get(..self.nb_fields).unwrap_or_default()
+    }
+
+    /// Accès à un champ du message
+    /// # Errors
+    /// Si le champ n'existe pas dans le message
+    pub fn field(&self, index: usize) -> Result<&Field, CodecError> {
+        self.fields()
+            .get(index)
+            .ok_or(CodecError::MissingField(index))
+    }
+
+    /// Est-ce un message ACK ?
+    pub fn is_ack(&self) -> bool {
+        matches!(self.fields().first(), Some(field) if field.as_bytes() == [ACK])
+    }
+
+    /// Est-ce un message NACK ?
+    pub fn is_nack(&self) -> bool {
+        matches!(self.fields().first(), Some(field) if field.as_bytes() == [NACK])
+    }
+
+    /// Longueur d'une trame de réponse selon longueur des champs attendus
+    pub fn len_expected_response(len_fields: &[usize]) -> usize {
+        // Longueur attendue = STX(1) + message_num(2) + [SEP(1) + len_field]* + SEP(1) + checksum(2) + ETx(1)
+        len_fields.iter().fold(7_usize, |len, len_field| {
+            len.saturating_add(len_field.saturating_add(1))
+        })
+    }
+
+    /// Ajout d'un champ dans le message
+    /// # Errors
+    /// Si le message a déjà `MAX_FIELDS` champs
+    pub fn add_field(&mut self, field: Field) -> Result<(), CodecError> {
+        let nb_fields = self.nb_fields.saturating_add(1);
+        let dst = self
+            .fields
+            .get_mut(self.nb_fields)
+            .ok_or(CodecError::CapacityOverflow(nb_fields, MAX_FIELDS))?;
+        *dst = field;
+        self.nb_fields = nb_fields;
+        Ok(())
+    }
+
+    /// Création de la trame pour le message dans `buffer` (voir `write_frame`)
+    /// Retourne le nombre d'octets de la trame
+    /// # Errors
+    /// Si `buffer` est trop petit pour la trame
+    /// Si le numéro de message ne tient pas sur 2 chiffres
+    pub fn to_frame(&self, buffer: &mut [u8]) -> Result<usize, CodecError> {
+        let mut writer = Writer { buffer, len: 0 };
+        write_frame(
+            self.message_num,
+            self.fields().iter().map(Field::as_bytes),
+            |data| writer.push(data),
+        )?;
+        Ok(writer.len)
+    }
+
+    /// Décodage et validation d'une trame d'un message (voir `read_fields`)
+    /// `buffer` : Message à décoder
+    /// `message_num` : Numéro de message attendu
+    /// `len_fields` : Nombre et taille (en octet) des différents champs attendus dans le message
+    /// # Errors
+    /// Toute non-conformité de la trame (jamais de `panic!`, quel que soit le contenu de `buffer`)
+    pub fn try_from_buffer(
+        buffer: &[u8],
+        message_num: u8,
+        len_fields: &[usize],
+    ) -> Result<Self, CodecError> {
+        let mut frame = Self::new(message_num);
+        read_fields(buffer, message_num, len_fields, |field| {
+            frame.add_field(Field::new(field)?)
+        })?;
+        Ok(frame)
+    }
+}
+
+/// Helper pour écrire une trame dans un buffer de taille fixe
+struct Writer<'a> {
+    /// Buffer de destination
+    buffer: &'a mut [u8],
+
+    /// Nombre d'octets déjà écrits
+    len: usize,
+}
+
+impl Writer<'_> {
+    /// Ajout d'octets à la suite de ce qui est déjà écrit
+    fn push(&mut self, data: &[u8]) -> Result<(), CodecError> {
+        let end = self.len.saturating_add(data.len());
+        let capacity = self.buffer.len().min(FRAME_CAPACITY);
+        let dst = self
+            .buffer
+            .get_mut(self.len..end)
+            .filter(|_| end <= capacity)
+            .ok_or(CodecError::CapacityOverflow(end, capacity))?;
+        dst.copy_from_slice(data);
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_construction_message() {
+        // Message 12 avec un champ ABCD
+        let mut req = Frame::new(12);
+        req.add_field(Field::encode_str("ABCD", 4).unwrap())
+            .unwrap();
+
+        let mut buffer = [0; FRAME_CAPACITY];
+        let len = req.to_frame(&mut buffer).unwrap();
+        assert_eq!(
+            buffer[..len],
+            [STX, b'1', b'2', SEPARATOR, b'A', b'B', b'C', b'D', SEPARATOR, b'0', b'7', ETX]
+        );
+
+        // Buffer trop petit
+        let mut buffer = [0; 5];
+        assert_eq!(
+            req.to_frame(&mut buffer),
+            Err(CodecError::CapacityOverflow(8, 5))
+        );
+    }
+
+    #[test]
+    fn test_same_as_std_frame() {
+        // Même trame que celle construite par `st2150::frame::Frame`
+        let mut std_frame = crate::st2150::frame::Frame::new(34);
+        let mut frame = Frame::new(34);
+        for (value, width) in [(123_u64, 3_usize), (1, 3), (45, 3)] {
+            std_frame.add_field(crate::st2150::field::Field::encode_number(value, width).unwrap());
+            frame
+                .add_field(Field::encode_number(value, width).unwrap())
+                .unwrap();
+        }
+
+        let mut buffer = [0; FRAME_CAPACITY];
+        let len = frame.to_frame(&mut buffer).unwrap();
+        assert_eq!(buffer[..len], std_frame.to_frame()[..]);
+    }
+
+    #[test]
+    fn test_ack_nack() {
+        let mut req = Frame::new(12);
+        req.add_field(Field::encode_binary(ACK)).unwrap();
+        assert!(req.is_ack());
+        assert!(!req.is_nack());
+    }
+
+    #[test]
+    fn test_try_from_buffer_is_ok() {
+        let mut frame = Frame::new(12);
+        frame
+            .add_field(Field::encode_str("VALUE", 10).unwrap())
+            .unwrap();
+        frame.add_field(Field::new(&[]).unwrap()).unwrap();
+        frame
+            .add_field(Field::encode_number(123, 3).unwrap())
+            .unwrap();
+
+        let mut buffer = [0; FRAME_CAPACITY];
+        let len = frame.to_frame(&mut buffer).unwrap();
+        let ret = Frame::try_from_buffer(&buffer[..len], 12, &[10, 0, 3]);
+        assert_eq!(ret, Ok(frame));
+        assert_eq!(
+            ret.unwrap().field(2).unwrap().decode_number::<u8>(),
+            Ok(123)
+        );
+        assert_eq!(ret.unwrap().field(3), Err(CodecError::MissingField(3)));
+    }
+
+    #[test]
+    fn test_try_from_buffer_is_err() {
+        /* buffer / num message / len_fields / CodecError */
+        let err_tests: [(&[u8], u8, &[usize], CodecError); 8] = [
+            (&[STX, 0x01, ETX], 0, &[], CodecError::BadMessageLen(3, 7)),
+            (&[0x01, 0, 0, 0, 0, 0, ETX], 0, &[], CodecError::MissingSTX),
+            (&[STX, 0, 0, 0, 0, 0, 0x01], 0, &[], CodecError::MissingETX),
+            (
+                &[STX, b'0', b'0', SEPARATOR, 0x00, 0x00, ETX],
+                0,
+                &[],
+                CodecError::BadChecksum(0, 0xFE),
+            ),
+            // Numéro de message qui n'est pas en chiffres
+            (
+                &[STX, b'X', b'0', SEPARATOR, b'9', b'6', ETX],
+                0,
+                &[],
+                CodecError::IllegalFieldCharDecode(b'X'),
+            ),
+            (
+                &[
+                    STX, b'5', b'0', SEPARATOR, b'E', b'R', b'R', b'E', b'U', b'R', SEPARATOR,
+                    b'0', b'2', ETX,
+                ],
+                12,
+                &[],
+                CodecError::ErrorMessage50(Some(*b"ERREUR")),
+            ),
+            (
+                &[STX, b'0', b'0', SEPARATOR, b'F', b'E', ETX],
+                12,
+                &[],
+                CodecError::BadMessageNumber(0, 12),
+            ),
+            (
+                &[
+                    STX, b'0', b'0', SEPARATOR, b'1', SEPARATOR, b'2', b'2', SEPARATOR, b'C', b'F',
+                    ETX,
+                ],
+                0,
+                &[2, 1],
+                CodecError::SeparatorExpected(6),
+            ),
+        ];
+
+        for (buffer, message_num, len_fields, expected) in err_tests {
+            assert_eq!(
+                Frame::try_from_buffer(buffer, message_num, len_fields),
+                Err(expected)
+            );
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Description des champs (nombre et taille) des requêtes et réponses de chaque message
+
+/// Longueur variable d'un champ de requête : Sa longueur est donnée par le champ qui précède
+/// (cas de l'identification TAG du message 22)
+pub const LEN_VARIABLE: usize = usize::MAX;
+
+/// Taille des champs en requête et en réponse d'un message
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageLayout {
+    /// Numéro de message
+    pub message_num: u8,
+
+    /// Taille des champs de la requête
+    pub req_len_fields: &'static [usize],
+
+    /// Taille des champs de la réponse
+    pub rep_len_fields: &'static [usize],
+}
+
+/// Réponse des messages de mouvement de produit (60 à 79) : Ack/Nack + code erreur
+const REP_MOUVEMENT_PRODUIT: &[usize] = &[1, 2];
+
+/// Réponse par un simple ACK ou NACK
+const REP_ACK_NACK: &[usize] = &[1];
+
+/// Description de tous les messages gérés
+#[rustfmt::skip]  // On demande à 'cargo fmt' de garder une ligne par message
+pub const MESSAGE_LAYOUTS: &[MessageLayout] = &[
+    MessageLayout { message_num: 0, req_len_fields: &[], rep_len_fields: &[1, 1, 1, 1, 1] },
+    MessageLayout { message_num: 10, req_len_fields: &[], rep_len_fields: &[8, 4, 5, 4, 5] },
+    MessageLayout {
+        message_num: 11,
+        req_len_fields: &[],
+        rep_len_fields: &[1, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 4],
+    },
+    MessageLayout { message_num: 20, req_len_fields: &[5, 1], rep_len_fields: REP_ACK_NACK },
+    // Le message 21 peut aussi répondre par un simple NACK (voir `REP_ACK_NACK`)
+    MessageLayout { message_num: 21, req_len_fields: &[], rep_len_fields: &[5, 4, 5, 8, 3, 3, 3, 1, 4, 4] },
+    MessageLayout { message_num: 22, req_len_fields: &[3, LEN_VARIABLE], rep_len_fields: REP_ACK_NACK },
+    MessageLayout { message_num: 30, req_len_fields: &[], rep_len_fields: &[15, 10, 12, 1] },
+    MessageLayout { message_num: 31, req_len_fields: &[3], rep_len_fields: &[3] },
+    MessageLayout { message_num: 32, req_len_fields: &[3, 3], rep_len_fields: &[5, 5, 4, 3, 4, 4] },
+    MessageLayout { message_num: 33, req_len_fields: &[], rep_len_fields: &[5; 8] },
+    MessageLayout { message_num: 34, req_len_fields: &[3, 3, 3], rep_len_fields: &[5, 1, 4, 4] },
+    MessageLayout { message_num: 35, req_len_fields: &[], rep_len_fields: &[10; 16] },
+    MessageLayout { message_num: 36, req_len_fields: &[6, 3], rep_len_fields: &[3, 6, 12, 40] },
+    MessageLayout {
+        message_num: 37,
+        req_len_fields: &[1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5, 1, 5],
+        rep_len_fields: REP_ACK_NACK,
+    },
+    MessageLayout { message_num: 38, req_len_fields: &[3, 3], rep_len_fields: &[10, 5, 4, 3, 4, 4, 1] },
+    MessageLayout { message_num: 40, req_len_fields: &[4], rep_len_fields: REP_ACK_NACK },
+    MessageLayout { message_num: 60, req_len_fields: &[5, 1, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 61, req_len_fields: &[5, 1, 9, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 62, req_len_fields: &[1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 63, req_len_fields: &[1, 9, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 65, req_len_fields: &[1, 1, 1, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 66, req_len_fields: &[5, 1, 1, 1, 1, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 67, req_len_fields: &[5, 1, 1, 9, 1, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 70, req_len_fields: &[5, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 71, req_len_fields: &[1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 75, req_len_fields: &[5, 1, 1, 1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 76, req_len_fields: &[1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 77, req_len_fields: &[1, 1], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+    MessageLayout { message_num: 78, req_len_fields: &[], rep_len_fields: REP_MOUVEMENT_PRODUIT },
+];
+
+/// Description d'un message (None si le message n'est pas géré)
+pub fn message_layout(message_num: u8) -> Option<&'static MessageLayout> {
+    MESSAGE_LAYOUTS
+        .iter()
+        .find(|layout| layout.message_num == message_num)
+}
+
+/// Taille des champs de la requête d'un message (None si le message n'est pas géré)
+pub fn req_len_fields(message_num: u8) -> Option<&'static [usize]> {
+    message_layout(message_num).map(|layout| layout.req_len_fields)
+}
+
+/// Taille des champs de la réponse d'un message (None si le message n'est pas géré)
+pub fn rep_len_fields(message_num: u8) -> Option<&'static [usize]> {
+    message_layout(message_num).map(|layout| layout.rep_len_fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::{Context, Date, Heure, IdInfo, U8OrT};
+    use crate::st2150::messages::ST2150_MESSAGE_NUMBERS;
+    use crate::st2150::ST2150;
+
+    #[test]
+    fn test_all_messages_have_layout() {
+        for message_num in ST2150_MESSAGE_NUMBERS {
+            assert!(
+                message_layout(*message_num).is_some(),
+                "Pas de description pour le message {message_num}"
+            );
+        }
+        assert!(message_layout(99).is_none());
+    }
+
+    #[test]
+    fn test_layout_requetes() {
+        // Contexte avec toutes les infos possibles pour une requête
+        let mut context = Context::default();
+        context.set_info_u32(IdInfo::Predetermination, 12345);
+        context.set_info_u8(IdInfo::CodeProduit, 1);
+        context.set_info_u8(IdInfo::CodeProduitFinal, 2);
+        context.set_info_u8_or_t(IdInfo::NumeroCompartiment, U8OrT::U8(3));
+        context.set_info_u8_or_t(IdInfo::NumeroCompartimentFinal, U8OrT::T);
+        context.set_info_string(IdInfo::OrdreCompartiments, "123");
+        context.set_info_u8(IdInfo::NumeroFlexible, 1);
+        context.set_info_u8(IdInfo::NumeroFlexibleFinal, 2);
+        context.set_info_bool(IdInfo::FinirFlexibleVide, true);
+        context.set_info_string(IdInfo::IdentificationTag, "ABCDE");
+        context.set_info_u16(IdInfo::Quantieme, 123);
+        context.set_info_u16(IdInfo::IndexJournalier, 1);
+        context.set_info_u16(IdInfo::IndexFractionnement, 1);
+        context.set_info_date(IdInfo::DateAAMMJJ, Date::from_aammjj(12_03_04).unwrap());
+        context.set_info_heure_hhmm(IdInfo::HeureHHMM, Heure::from_hhmm(12_34).unwrap());
+        for compart_num in 1..=9 {
+            context.set_info_u8(IdInfo::CodeProduitCompartiment(compart_num), 1);
+            context.set_info_u32(IdInfo::QuantiteCompartiment(compart_num), 1000);
+        }
+
+        // La description doit correspondre aux requêtes effectivement construites
+        let st = ST2150::default();
+        for message_num in ST2150_MESSAGE_NUMBERS {
+            let req = st.message_dry_run(&context, *message_num).unwrap();
+            let len_fields: Vec<usize> = req
+                .fields
+                .iter()
+                .map(|field| field.to_frame().len())
+                .collect();
+            let req_len_fields = req_len_fields(*message_num).unwrap();
+            assert_eq!(
+                req_len_fields.len(),
+                len_fields.len(),
+                "Message {message_num}"
+            );
+            for (len_layout, len_field) in req_len_fields.iter().zip(&len_fields) {
+                if *len_layout != LEN_VARIABLE {
+                    assert_eq!(len_layout, len_field, "Message {message_num}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_libelles_produits() {
+        // La taille des libellés produits dans les réponses est celle de l'information pour le
+        // message (voir `Context::get_info_string_width`)
+        let mut context = Context::default();
+        for (message_num, id_info) in [
+            (32, IdInfo::LibelleProduit),
+            (33, IdInfo::LibelleTableProduits(1)),
+            (35, IdInfo::LibelleTableProduits(1)),
+            (38, IdInfo::LibelleProduit),
+        ] {
+            context.set_message_courant(Some(message_num));
+            assert_eq!(
+                rep_len_fields(message_num).unwrap()[0],
+                context.get_info_string_width(id_info),
+                "Message {message_num}"
+            );
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Codec ALMA IE - ST2150 sans allocation, compatible `no_std`
+//!
+//! Ce module reprend les règles des trames (`Frame`), des champs (`Field`), du checksum et la
+//! description des champs de chaque message (`layout`) pour un usage embarqué (microcontrôleur)
+//! d'un côté ou de l'autre de la liaison.
+//!
+//! Contraintes respectées par tout ce module :
+//! * Uniquement `core` (pas de `std`, ni `alloc`) : Pas de `Vec`, `String` ou `format!`
+//! * Buffers de taille fixe (voir `FIELD_CAPACITY`, `MAX_FIELDS` et `FRAME_CAPACITY`)
+//! * Aucun `panic!` possible : Toutes les erreurs sont retournées en `CodecError`
+//!
+//! Ces contraintes sont vérifiées par `cargo clippy` (voir les lints en tête de ce module, hors
+//! tests) et par la compilation de ce même module dans le crate `#![no_std]` `sim_ie_nostd`
+//! (voir `nostd/` et `cargo build --workspace`).
+//!
+//! Les règles d'encodage et de décodage des champs et des trames ne sont écrites qu'ici :
+//! `st2150::field::Field` et `st2150::frame::Frame` s'appuient sur ce module.
+
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::std_instead_of_core,
+        clippy::std_instead_of_alloc,
+        clippy::alloc_instead_of_core,
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::arithmetic_side_effects
+    )
+)]
+
+use core::fmt::Display;
+
+pub mod field;
+pub mod frame;
+pub mod layout;
+
+pub use field::Field;
+pub use frame::Frame;
+
+/// Début de message
+pub const STX: u8 = 0x02;
+
+/// Séparateur de champ
+pub const SEPARATOR: u8 = 0xFE;
+
+/// Fin de message
+pub const ETX: u8 = 0x03;
+
+/// Acquit de message
+pub const ACK: u8 = 0x06;
+
+/// Non-acquit de message
+pub const NACK: u8 = 0x15;
+
+/// Nombre max. d'octets dans un champ (Identification TAG du message 22)
+pub const FIELD_CAPACITY: usize = 100;
+
+/// Nombre max. de champs dans un message (Requête du message 37 ou réponse du message 11)
+pub const MAX_FIELDS: usize = 24;
+
+/// Nombre max. d'octets d'une trame complète (STX, numéro, champs, séparateurs, checksum et ETX)
+pub const FRAME_CAPACITY: usize = 256;
+
+/// Erreur détectée par le codec `no_std`
+///
+/// Équivalent sans allocation de `ProtocolError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CodecError {
+    /// Longueur incorrecte de message (nb octets message, attendus)
+    BadMessageLen(usize, usize),
+
+    /// Checksum incorrect du message (checksum, attendu)
+    BadChecksum(u8, u8),
+
+    /// Pas de STX en début de message
+    MissingSTX,
+
+    /// Pas de ETX en fin de message
+    MissingETX,
+
+    /// Réponse avec un message d'erreur 50 (texte de l'erreur si bien formé)
+    ErrorMessage50(Option<[u8; 6]>),
+
+    /// Numéro de message incorrect (num, attendu)
+    BadMessageNumber(u8, u8),
+
+    /// Séparateur de champ attendu (position)
+    SeparatorExpected(usize),
+
+    /// Impossible d'encoder la valeur dans la taille demandée (taille)
+    IllegalNumberEncoding(usize),
+
+    /// Caractère incorrect dans un champ lors du décodage (caractère)
+    IllegalFieldCharDecode(u8),
+
+    /// Échec conversion d'un champ dans le type demandé
+    ErrFieldConversion,
+
+    /// Champ absent de la trame (index)
+    MissingField(usize),
+
+    /// Capacité d'un buffer de taille fixe dépassée (taille demandée, capacité)
+    CapacityOverflow(usize, usize),
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CodecError::BadMessageLen(nb, nb_expected) => write!(
+                f,
+                "Longueur incorrecte du message ({nb}/{nb_expected} cars)"
+            ),
+            CodecError::BadChecksum(checksum, checksum_expected) => write!(
+                f,
+                "Checksum incorrect du message (0x{checksum:02X} vs 0x{checksum_expected:02X} attendu)"
+            ),
+            CodecError::MissingSTX => write!(f, "Pas de 'STX' en début de message"),
+            CodecError::MissingETX => write!(f, "Pas de 'ETX' en fin de message"),
+            CodecError::ErrorMessage50(_) => write!(f, "Réponse avec un message 50 d'erreur"),
+            CodecError::BadMessageNumber(num, num_expected) => write!(
+                f,
+                "Numéro incorrect du message ({num} vs {num_expected} attendu)"
+            ),
+            CodecError::SeparatorExpected(pos) => write!(
+                f,
+                "Séparateur de champ attendu en position {pos} dans le message"
+            ),
+            CodecError::IllegalNumberEncoding(width) => {
+                write!(f, "Encodage impossible sur {width} cars")
+            }
+            CodecError::IllegalFieldCharDecode(car) => {
+                write!(f, "Contenu '0x{car:02X}' incorrect pour décodage du champ")
+            }
+            CodecError::ErrFieldConversion => write!(f, "Erreur lors de la conversion du champ"),
+            CodecError::MissingField(index) => write!(f, "Champ #{index} absent du message"),
+            CodecError::CapacityOverflow(len, capacity) => {
+                write!(f, "Capacité dépassée ({len}/{capacity} max)")
+            }
+        }
+    }
+}
+
+/// Helper pour convertir un caractère hexadécimal en binaire décimal
+pub const fn car_hexa_to_value(car: u8) -> u8 {
+    match car {
+        b'0'..=b'9' => car.wrapping_sub(b'0'),
+        b'A'..=b'F' => car.wrapping_sub(b'A').wrapping_add(10),
+        b'a'..=b'f' => car.wrapping_sub(b'a').wrapping_add(10),
+        _ => 0,
+    }
+}
+
+/// Calcul du checksum pour trame (XOR des octets)
+/// Attention : Dans la ST2150, le checksum n'intègre pas le STX initial et contient un SEPARATOR avant
+pub fn calcul_checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0_u8, |lrc, byte| lrc ^ (*byte))
+}
@@ -9,20 +9,8 @@ use std::time::SystemTime;
 
 use crate::serial_com::{CommonSerialComTrait, SerialCom};
 
-/// Début de message
-pub const STX: u8 = 0x02;
-
-/// Séparateur de champ
-pub const SEPARATOR: u8 = 0xFE;
-
-/// Fin de message
-pub const ETX: u8 = 0x03;
-
-/// Acquit de message
-pub const ACK: u8 = 0x06;
-
-/// Non-acquit de message
-pub const NACK: u8 = 0x15;
+// Constantes du protocole, communes avec le codec `no_std`
+pub use super::nostd::{ACK, ETX, NACK, SEPARATOR, STX};
 
 /// Timeout réception réponse (en seconde) : Absence de toute réponse
 pub const TIMEOUT_READ_FRAME: f32 = 1.0;
@@ -30,35 +18,45 @@ pub const TIMEOUT_READ_FRAME: f32 = 1.0;
 /// Timeout fin de trame (en seconde) : Si reçu quelque chose mais pas assez
 pub const TIMEOUT_END_FRAME: f32 = 0.3;
 
-/// Helper pour convertir un caractère hexadécimal en binaire décimal
-pub fn car_hexa_to_value(car: u8) -> u8 {
-    match car {
-        b'0'..=b'9' => car - b'0',
-        b'A'..=b'F' => car - b'A' + 10,
-        b'a'..=b'f' => car - b'a' + 10,
-        _ => 0,
-    }
-}
-
-/// Calcul du checksum pour trame (XOR des octets)
-/// Attention : Dans la ST2150, le checksum n'intègre pas le STX initial et contient un SEPARATOR avant
-pub fn calcul_checksum(data: &[u8]) -> u8 {
-    data.iter().fold(0_u8, |lrc, byte| lrc ^ (*byte))
-}
+// Helpers communs avec le codec `no_std`
+pub use super::nostd::{calcul_checksum, car_hexa_to_value};
 
 /// Primitive générique pour attendre une réponse sur la liaison série
 /// `port` : Référence au port série (true ou FAKE) à utiliser
 /// `buffer` : Buffer pour les octets reçus sur le port
 /// `max_expected_len` : Longueur max. de la réponse attendue. Dès que ce nombre max. est reçu,
 /// la fonction retourne. Sinon, c'est le timeout qui agit (un timeout différent entre aucune réponse
-/// et un timeout inter-caractères)
+/// et un timeout inter-caractères). Une réponse complète de la longueur attendue (plus courte que
+/// la trame d'erreur 50) est aussi retournée sans attendre le timeout inter-caractères
 pub fn waiting_frame(port: &mut SerialCom, buffer: &mut [u8], max_expected_len: usize) -> usize {
-    let mut total_len_received = 0;
-    let mut start_time = SystemTime::now();
-
     // Prise en compte de la trame d'erreur qui fait 14 caractères
+    let expected_len = max_expected_len;
     let max_expected_len = usize::max(14, max_expected_len);
 
+    waiting(port, buffer, |received| {
+        // On a reçu au moins le nombre max d'octets attendus ou une réponse courte complète
+        // (et qui n'est pas le début d'une trame d'erreur)
+        received.len() >= max_expected_len
+            || (received.len() >= expected_len && is_complete_frame(received))
+    })
+}
+
+/// Primitive pour attendre une réponse de longueur quelconque sur la liaison série
+/// (réponse hors spécification décodée en mode `DecodingMode::Lenient`)
+/// `port` : Référence au port série (true ou FAKE) à utiliser
+/// `buffer` : Buffer pour les octets reçus sur le port
+/// La fonction retourne dès la réception d'une trame terminée par ETX, sinon c'est le timeout
+/// qui agit, comme pour `waiting_frame` (ou le remplissage du buffer)
+pub fn waiting_frame_until_etx(port: &mut SerialCom, buffer: &mut [u8]) -> usize {
+    waiting(port, buffer, |received| received.last() == Some(&ETX))
+}
+
+/// Boucle de lecture du port série jusqu'à ce que les octets reçus soient `complete`,
+/// le remplissage du buffer ou l'un des timeouts
+fn waiting(port: &mut SerialCom, buffer: &mut [u8], complete: impl Fn(&[u8]) -> bool) -> usize {
+    let mut total_len_received = 0;
+    let mut start_time = SystemTime::now();
+
     // Boucle de lecture du port série
     loop {
         let len_received = port.read(&mut buffer[total_len_received..]);
@@ -67,8 +65,7 @@ pub fn waiting_frame(port: &mut SerialCom, buffer: &mut [u8], max_expected_len:
             total_len_received += len_received;
             start_time = SystemTime::now();
         }
-        if total_len_received >= max_expected_len {
-            // On a reçu au moins le nombre max d'octets attendus, on retourne
+        if total_len_received >= buffer.len() || complete(&buffer[..total_len_received]) {
             return total_len_received;
         }
         if total_len_received > 0 {
@@ -89,6 +86,15 @@ pub fn waiting_frame(port: &mut SerialCom, buffer: &mut [u8], max_expected_len:
     }
 }
 
+/// Helper pour savoir si une trame reçue est complète (STX ... ETX) et n'est pas un message 50
+/// d'erreur (plus long que la réponse attendue)
+fn is_complete_frame(buffer: &[u8]) -> bool {
+    buffer.len() >= 3
+        && buffer[0] == STX
+        && buffer[buffer.len() - 1] == ETX
+        && buffer[1..3] != *b"50"
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,4 +126,19 @@ mod tests {
         assert_eq!(rep_len, 3);
         assert_eq!(buffer[0..3], [0x01, 0x02, 0x03]);
     }
+
+    #[test]
+    fn test_waiting_short_frame() {
+        // Une réponse courte et complète est retournée sans attendre le timeout fin de trame
+        let mut fake_port = SerialCom::new("FAKE", 9600);
+        let frame = [STX, b'2', b'0', SEPARATOR, ACK, SEPARATOR, b'F', b'8', ETX];
+        fake_port.will_read(&frame);
+
+        let start_time = SystemTime::now();
+        let mut buffer = [0; 500];
+        let rep_len = waiting_frame(&mut fake_port, &mut buffer, frame.len());
+
+        assert_eq!(rep_len, frame.len());
+        assert!(start_time.elapsed().unwrap().as_secs_f32() < TIMEOUT_END_FRAME);
+    }
 }
@@ -0,0 +1,147 @@
+//! Journal des vacations de la session
+//!
+//! Chaque vacation (requête/réponse d'un message) est mémorisée avec son horodatage, les valeurs
+//! des informations de sa requête et de sa réponse, les trames échangées, sa durée et son
+//! compte rendu, dans la limite des `VACATIONS_MAX_LEN` dernières vacations.
+//!
+//! Une vacation du journal peut être rejouée avec les mêmes valeurs pour sa requête
+//! (voir `ST2150::rerun_vacation`), par exemple pour reprendre pas à pas un scénario de livraison.
+
+use std::time::{Duration, SystemTime};
+
+use super::ProtocolError;
+use crate::context::{Context, FormatInfo, IdInfo, TValue};
+use crate::langue::tr;
+
+/// Nombre max de vacations mémorisées dans le journal
+pub const VACATIONS_MAX_LEN: usize = 1000;
+
+/// Valeur d'une information de la requête ou de la réponse d'une vacation
+/// Le libellé et la valeur sont mémorisés tels qu'affichés lors de la vacation (selon le message,
+/// la langue et les unités de ce moment)
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValeurInfo {
+    /// Information du contexte
+    pub id_info: IdInfo,
+
+    /// Valeur de l'information
+    pub t_value: TValue,
+
+    /// Libellé de l'information lors de la vacation
+    pub label: String,
+
+    /// Représentation 'textuelle' de la valeur suivie de son unité lors de la vacation
+    /// (par exemple `5000 L`)
+    pub valeur: String,
+}
+
+impl ValeurInfo {
+    /// Valeurs actuelles de certaines informations du contexte
+    /// (les informations non définies ou non retenues par `filtre` sont ignorées)
+    pub fn from_context(
+        context: &Context,
+        id_infos: &[IdInfo],
+        filtre: impl Fn(IdInfo) -> bool,
+    ) -> Vec<Self> {
+        id_infos
+            .iter()
+            .filter(|id_info| filtre(**id_info))
+            .filter_map(|id_info| {
+                let t_value = context.get_option_info_t_value(*id_info)?;
+                let mut valeur = context.get_info_to_string_with_unite(*id_info, "");
+                if context.get_info_format(*id_info) == FormatInfo::Bool {
+                    valeur = tr(&valeur);
+                }
+                Some(Self {
+                    id_info: *id_info,
+                    t_value,
+                    label: context.get_info_label(*id_info).into_owned(),
+                    valeur,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Trace d'une vacation du journal
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceVacation {
+    /// Numéro de la vacation (voir `Context::begin_vacation`)
+    pub num_vacation: u32,
+
+    /// Instant du début de la vacation
+    pub horodatage: SystemTime,
+
+    /// Numéro du message
+    pub message_num: u8,
+
+    /// Valeurs des informations de la requête
+    pub request_values: Vec<ValeurInfo>,
+
+    /// Valeurs des informations mises à jour par la réponse
+    pub response_values: Vec<ValeurInfo>,
+
+    /// Trame de la requête envoyée (vide si la requête n'a pas été envoyée)
+    pub req: Vec<u8>,
+
+    /// Trame de la réponse reçue (vide si pas de réponse)
+    pub rep: Vec<u8>,
+
+    /// Informations encodées dans chaque champ de la requête, dans l'ordre des champs
+    pub req_id_infos_fields: Vec<Vec<IdInfo>>,
+
+    /// Informations alimentées par chaque champ de la réponse, dans l'ordre des champs
+    pub rep_id_infos_fields: Vec<Vec<IdInfo>>,
+
+    /// Durée de la vacation
+    pub duree: Duration,
+
+    /// Erreur de la vacation (None si la vacation a réussi)
+    pub option_error: Option<ProtocolError>,
+
+    /// Avertissements du décodage de la réponse en mode `DecodingMode::Lenient`
+    pub warnings: Vec<ProtocolError>,
+}
+
+impl TraceVacation {
+    /// Informations encodées dans un champ de la requête, avec leur valeur lors de la vacation
+    pub fn request_field_values(&self, index: usize) -> Vec<(IdInfo, Option<&ValeurInfo>)> {
+        Self::field_values(&self.req_id_infos_fields, index, &self.request_values)
+    }
+
+    /// Informations alimentées par un champ de la réponse, avec leur valeur décodée lors de la
+    /// vacation (None si l'information n'a pas été mise à jour par la réponse)
+    pub fn response_field_values(&self, index: usize) -> Vec<(IdInfo, Option<&ValeurInfo>)> {
+        Self::field_values(&self.rep_id_infos_fields, index, &self.response_values)
+    }
+
+    /// Informations d'un champ d'une trame et leur valeur parmi `valeurs`
+    fn field_values<'a>(
+        id_infos_fields: &[Vec<IdInfo>],
+        index: usize,
+        valeurs: &'a [ValeurInfo],
+    ) -> Vec<(IdInfo, Option<&'a ValeurInfo>)> {
+        id_infos_fields
+            .get(index)
+            .map_or(&[][..], Vec::as_slice)
+            .iter()
+            .map(|id_info| {
+                (
+                    *id_info,
+                    valeurs
+                        .iter()
+                        .find(|valeur_info| valeur_info.id_info == *id_info),
+                )
+            })
+            .collect()
+    }
+
+    /// Compte rendu de la vacation : OK (avec le nombre d'avertissements) ou l'erreur relevée
+    pub fn compte_rendu(&self) -> String {
+        match (&self.option_error, self.warnings.len()) {
+            (Some(e), _) => e.to_string(),
+            (None, 0) => "OK".to_string(),
+            (None, nb_warnings) => tr(&format!("OK ({nb_warnings} avertissements)")),
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! Fuzzing des décodeurs de trames et de tous les messages du protocole ALMA IE - ST2150
+//!
+//! Des réponses aléatoires (octets quelconques, trames bien formées avec des champs aléatoires
+//! ou trames bien formées puis altérées) sont injectées via un FAKE port série pour chaque
+//! message, en décodage strict ou tolérant : Le décodage doit toujours retourner `Ok` ou une
+//! `ProtocolError`, jamais un `panic!`
+//!
+//! Variables d'environnement (optionnelles) pour les campagnes plus longues :
+//! * `SIM_IE_FUZZ_ITERATIONS` : Nombre d'itérations par message et par stratégie (défaut 200)
+//! * `SIM_IE_FUZZ_SEED` : Graine du générateur pseudo-aléatoire (pour rejouer une campagne)
+//!
+//! Par exemple : `SIM_IE_FUZZ_ITERATIONS=100000 cargo test --release --test fuzz_decoders`
+
+use std::panic::{self, AssertUnwindSafe};
+
+use sim_ie::context::Context;
+use sim_ie::st2150::frame::Frame;
+use sim_ie::st2150::messages::{get_dyn_message, ST2150_MESSAGE_NUMBERS};
+use sim_ie::st2150::{nostd, protocol, DecodingMode};
+use sim_ie::{CommonSerialComTrait, SerialCom, ST2150};
+
+/// Nombre d'itérations par défaut (par message et par stratégie)
+const DEFAULT_ITERATIONS: usize = 200;
+
+/// Graine par défaut du générateur pseudo-aléatoire
+const DEFAULT_SEED: u64 = 0x2150_2150_2150_2150;
+
+/// Générateur pseudo-aléatoire `xorshift64*` (reproductible et sans dépendance)
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // L'état d'un xorshift ne doit jamais être nul
+        Self(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        usize::try_from(self.next_u64() % max as u64).unwrap()
+    }
+
+    fn byte(&mut self) -> u8 {
+        self.next_u64().to_le_bytes()[0]
+    }
+
+    /// Octet 'plausible' dans un champ : Le plus souvent un caractère attendu par les décodeurs
+    fn field_byte(&mut self) -> u8 {
+        const PLAUSIBLE: &[u8] = b"0123456789+-TFONABCDEFabcdef ";
+        match self.below(10) {
+            0 => protocol::ACK,
+            1 => protocol::NACK,
+            2 => self.byte(),
+            _ => PLAUSIBLE[self.below(PLAUSIBLE.len())],
+        }
+    }
+}
+
+/// Paramètres de la campagne
+fn fuzz_params() -> (usize, u64) {
+    let iterations = std::env::var("SIM_IE_FUZZ_ITERATIONS")
+        .ok()
+        .and_then(|txt| txt.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let seed = std::env::var("SIM_IE_FUZZ_SEED")
+        .ok()
+        .and_then(|txt| txt.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    (iterations, seed)
+}
+
+/// Trame bien formée (STX, numéro, checksum, ETX) avec des champs aléatoires de la taille attendue
+fn random_well_formed_frame(rng: &mut Rng, message_num: u8, len_fields: &[usize]) -> Vec<u8> {
+    let mut buffer = vec![
+        protocol::STX,
+        b'0' + message_num / 10,
+        b'0' + message_num % 10,
+    ];
+    for len_field in len_fields {
+        buffer.push(protocol::SEPARATOR);
+        for _ in 0..*len_field {
+            buffer.push(rng.field_byte());
+        }
+    }
+    buffer.push(protocol::SEPARATOR);
+    let checksum = protocol::calcul_checksum(&buffer[1..]);
+    buffer.extend(format!("{checksum:02X}").as_bytes());
+    buffer.push(protocol::ETX);
+    buffer
+}
+
+/// Réponse aléatoire selon la stratégie
+/// La réponse fait toujours au moins la taille attendue pour ne pas attendre le timeout fin de trame
+fn random_response(
+    rng: &mut Rng,
+    strategy: usize,
+    message_num: u8,
+    len_fields: &[usize],
+) -> Vec<u8> {
+    let min_len = usize::max(14, Frame::len_expected_response(len_fields));
+    match strategy {
+        // Octets quelconques
+        0 => (0..min_len + rng.below(20)).map(|_| rng.byte()).collect(),
+        // Trame bien formée avec des champs aléatoires
+        1 => random_well_formed_frame(rng, message_num, len_fields),
+        // Trame bien formée puis altérée (hors STX et ETX déjà couverts par les octets quelconques)
+        _ => {
+            let mut buffer = random_well_formed_frame(rng, message_num, len_fields);
+            for _ in 0..=rng.below(3) {
+                let index = 1 + rng.below(buffer.len() - 2);
+                buffer[index] = rng.byte();
+            }
+            buffer
+        }
+    }
+}
+
+/// Contexte avec toutes les informations nécessaires à la requête d'un message
+/// (première valeur acceptée pour chaque information)
+fn context_for_request(message_num: u8) -> Context {
+    const VALUES: &[&str] = &["1", "240101", "1200"];
+    let mut context = Context::default();
+    for id_info in get_dyn_message(message_num).id_infos_request() {
+        assert!(
+            VALUES
+                .iter()
+                .any(|value| context.set_info_from_string(id_info, value).is_ok()),
+            "Pas de valeur pour {id_info:?}"
+        );
+    }
+    context
+}
+
+/// Représentation hexadécimale d'un buffer pour rejouer un cas en erreur
+fn to_hexa(buffer: &[u8]) -> String {
+    buffer.iter().map(|byte| format!("{byte:02X} ")).collect()
+}
+
+#[test]
+fn fuzz_frame_try_from_buffer() {
+    let (iterations, seed) = fuzz_params();
+    let mut rng = Rng::new(seed);
+
+    for _ in 0..iterations * ST2150_MESSAGE_NUMBERS.len() {
+        let message_num = ST2150_MESSAGE_NUMBERS[rng.below(ST2150_MESSAGE_NUMBERS.len())];
+        let len_fields = nostd::layout::rep_len_fields(message_num).unwrap();
+        let strategy = rng.below(3);
+        let mut buffer = random_response(&mut rng, strategy, message_num, len_fields);
+        // Tronque parfois pour tester aussi les trames trop courtes
+        if rng.below(4) == 0 {
+            buffer.truncate(rng.below(buffer.len() + 1));
+        }
+
+        let res = panic::catch_unwind(|| {
+            let _ = Frame::try_from_buffer(&buffer, message_num, len_fields);
+            let _ = Frame::try_from_buffer_lenient(&buffer, message_num, len_fields);
+            let _ = nostd::Frame::try_from_buffer(&buffer, message_num, len_fields);
+        });
+        assert!(
+            res.is_ok(),
+            "panic! pour le message {message_num} avec la trame {}",
+            to_hexa(&buffer)
+        );
+    }
+}
+
+#[test]
+fn fuzz_all_messages_decoders() {
+    let (iterations, seed) = fuzz_params();
+    let mut rng = Rng::new(seed);
+
+    for message_num in ST2150_MESSAGE_NUMBERS {
+        let message_num = *message_num;
+        let len_fields = nostd::layout::rep_len_fields(message_num).unwrap();
+        let mut context = context_for_request(message_num);
+        assert_eq!(
+            ST2150::message_availability(&context, message_num),
+            Ok(()),
+            "Message {message_num} indisponible pour le fuzzing"
+        );
+
+        for strategy in 0..3 {
+            for _ in 0..iterations {
+                let mut buffer = random_response(&mut rng, strategy, message_num, len_fields);
+                let decoding_mode = if rng.below(2) == 0 {
+                    // La réponse est lue jusqu'à son ETX en mode tolérant : Sans ETX final,
+                    // chaque itération attendrait le timeout inter-caractères
+                    if buffer.last() != Some(&protocol::ETX) {
+                        buffer.push(protocol::ETX);
+                    }
+                    DecodingMode::Lenient
+                } else {
+                    DecodingMode::Strict
+                };
+
+                let mut fake_port = SerialCom::new("FAKE", 9600);
+                fake_port.will_read(&buffer);
+                let mut st = ST2150::new(fake_port);
+                st.decoding_mode = decoding_mode;
+
+                let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                    let _ = st.do_message_vacation(&mut context, message_num);
+                }));
+                assert!(
+                    res.is_ok(),
+                    "panic! pour le message {message_num} avec la réponse {}",
+                    to_hexa(&buffer)
+                );
+            }
+        }
+    }
+}